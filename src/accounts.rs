@@ -0,0 +1,144 @@
+use std::path::PathBuf;
+use std::time::Duration;
+
+use zbus::proxy;
+use zbus::zvariant::OwnedObjectPath;
+
+use crate::users::User;
+
+/// accounts-daemon is started on demand, which shouldn't hold up the greeter for long.
+const TIMEOUT: Duration = Duration::from_secs(2);
+
+#[proxy(
+    interface = "org.freedesktop.Accounts",
+    default_service = "org.freedesktop.Accounts",
+    default_path = "/org/freedesktop/Accounts"
+)]
+trait Accounts {
+    fn find_user_by_name(&self, name: &str) -> zbus::Result<OwnedObjectPath>;
+}
+
+#[proxy(
+    interface = "org.freedesktop.Accounts.User",
+    default_service = "org.freedesktop.Accounts"
+)]
+trait AccountsUser {
+    #[zbus(property)]
+    fn real_name(&self) -> zbus::Result<String>;
+    #[zbus(property)]
+    fn icon_file(&self) -> zbus::Result<String>;
+    #[zbus(property)]
+    fn system_account(&self) -> zbus::Result<bool>;
+    #[zbus(property)]
+    fn locked(&self) -> zbus::Result<bool>;
+}
+
+/// What AccountsService knows about a user beyond passwd(5), set from their desktop's
+/// settings.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct Account {
+    real_name: Option<String>,
+    icon: Option<PathBuf>,
+    /// System and locked accounts, which desktop greeters leave out of their lists too.
+    hidden: bool,
+}
+
+/// Fills in real names and icons from AccountsService and drops the users it hides. Leaves
+/// `users` as they are when the service isn't there.
+pub async fn enrich(users: &mut Vec<User>) {
+    let accounts = async {
+        let bus = zbus::Connection::system().await?;
+        let accounts = AccountsProxy::new(&bus).await?;
+        let mut found = Vec::with_capacity(users.len());
+        for user in users.iter() {
+            found.push(account(&bus, &accounts, &user.name).await);
+        }
+        zbus::Result::Ok(found)
+    };
+    let accounts = match tokio::time::timeout(TIMEOUT, accounts).await {
+        Ok(Ok(accounts)) => accounts,
+        Ok(Err(err)) => {
+            tracing::debug!("failed to reach AccountsService: {err}");
+            return;
+        }
+        Err(_) => {
+            tracing::debug!("AccountsService didn't answer in time");
+            return;
+        }
+    };
+    let mut accounts = accounts.into_iter();
+    users.retain_mut(|user| match accounts.next().flatten() {
+        Some(account) => apply(user, account),
+        None => true,
+    });
+}
+
+/// `name`'s account, `None` if AccountsService doesn't know them.
+async fn account(
+    bus: &zbus::Connection,
+    accounts: &AccountsProxy<'_>,
+    name: &str,
+) -> Option<Account> {
+    let path = accounts.find_user_by_name(name).await.ok()?;
+    let user = AccountsUserProxy::builder(bus)
+        .path(path)
+        .ok()?
+        .build()
+        .await
+        .ok()?;
+    let real_name = user.real_name().await.unwrap_or_default();
+    let icon = user.icon_file().await.unwrap_or_default();
+    let hidden =
+        user.system_account().await.unwrap_or(false) || user.locked().await.unwrap_or(false);
+    Some(Account {
+        real_name: (!real_name.trim().is_empty()).then(|| real_name.trim().to_string()),
+        icon: (!icon.is_empty()).then(|| PathBuf::from(icon)),
+        hidden,
+    })
+}
+
+/// Updates `user` from their account, returning whether they stay in the list.
+fn apply(user: &mut User, account: Account) -> bool {
+    if let Some(real_name) = account.real_name {
+        user.real_name = Some(real_name.into());
+    }
+    if account.icon.is_some() {
+        user.icon = account.icon;
+    }
+    !account.hidden
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use crate::accounts::{Account, apply};
+    use crate::users::User;
+
+    #[test]
+    fn prefers_what_accounts_service_says() {
+        let mut user = User {
+            name: "alice".into(),
+            real_name: None,
+            icon: None,
+        };
+        let account = Account {
+            real_name: Some("Alice Liddell".into()),
+            icon: Some(PathBuf::from("/var/lib/AccountsService/icons/alice")),
+            hidden: false,
+        };
+
+        assert!(apply(&mut user, account));
+        assert_eq!(user.display_name(), "Alice Liddell");
+        assert!(user.icon.is_some());
+
+        assert!(!apply(
+            &mut user,
+            Account {
+                hidden: true,
+                ..Default::default()
+            }
+        ));
+        assert_eq!(user.display_name(), "Alice Liddell");
+    }
+}
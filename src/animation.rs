@@ -0,0 +1,377 @@
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use flume::{Receiver, Sender};
+use ratatui::style::Color;
+
+use crate::Msg;
+use crate::clock;
+use crate::color;
+use crate::queue;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Easing {
+    #[default]
+    Linear,
+    EaseIn,
+    EaseOut,
+    EaseInOut,
+}
+
+impl Easing {
+    pub fn apply(self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Easing::Linear => t,
+            Easing::EaseIn => t * t * t,
+            Easing::EaseOut => 1.0 - (1.0 - t).powi(3),
+            Easing::EaseInOut if t < 0.5 => 4.0 * t * t * t,
+            Easing::EaseInOut => 1.0 - (-2.0 * t + 2.0).powi(3) / 2.0,
+        }
+    }
+}
+
+pub trait Lerp {
+    fn lerp(&self, to: &Self, t: f32) -> Self;
+}
+
+impl Lerp for f32 {
+    fn lerp(&self, to: &Self, t: f32) -> Self {
+        self + (to - self) * t
+    }
+}
+
+impl Lerp for i32 {
+    fn lerp(&self, to: &Self, t: f32) -> Self {
+        (*self as f32).lerp(&(*to as f32), t).round() as i32
+    }
+}
+
+impl Lerp for Color {
+    fn lerp(&self, to: &Self, t: f32) -> Self {
+        color::mix(*self, *to, t)
+    }
+}
+
+/// A value moving from `from` to `to` over `duration`, starting at `start`.
+#[derive(Debug, Clone, Copy)]
+pub struct Tween<T> {
+    from: T,
+    to: T,
+    start: Instant,
+    duration: Duration,
+    easing: Easing,
+}
+
+impl<T: Lerp + Clone> Tween<T> {
+    pub fn new(from: T, to: T, duration: Duration, easing: Easing) -> Self {
+        Self {
+            from,
+            to,
+            start: Instant::now(),
+            duration,
+            easing,
+        }
+    }
+
+    /// Progress in `0.0..=1.0` at `now`, before easing.
+    pub fn progress(&self, now: Instant) -> f32 {
+        if self.duration.is_zero() {
+            return 1.0;
+        }
+        let elapsed = now.saturating_duration_since(self.start);
+        (elapsed.as_secs_f32() / self.duration.as_secs_f32()).min(1.0)
+    }
+
+    pub fn value_at(&self, now: Instant) -> T {
+        self.from
+            .lerp(&self.to, self.easing.apply(self.progress(now)))
+    }
+
+    pub fn value(&self) -> T {
+        self.value_at(Instant::now())
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.progress(Instant::now()) >= 1.0
+    }
+
+    pub fn duration(&self) -> Duration {
+        self.duration
+    }
+}
+
+/// Horizontal offset of a damped shake, for `t` in `0.0..=1.0`.
+pub fn shake(t: f32, amplitude: f32) -> i32 {
+    let decay = 1.0 - t.clamp(0.0, 1.0);
+    ((t * std::f32::consts::TAU * 3.0).sin() * amplitude * decay).round() as i32
+}
+
+pub const SPINNER: &[&str] = &["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"];
+
+/// The spinner frame to show `since` an operation started.
+pub fn spinner_frame(since: Instant) -> &'static str {
+    let frame = since.elapsed().as_millis() / 80;
+    SPINNER[frame as usize % SPINNER.len()]
+}
+
+/// How long a frame lasts at `fps`.
+fn frame(fps: u32) -> Duration {
+    Duration::from_secs(1) / fps.clamp(1, 60)
+}
+
+/// The one source of [`Msg::Tick`], held in the model. Animations, the spinner, the idle check
+/// and the clocks subscribe to it with how often they need drawing, and a tick goes out once
+/// for all of them that are due, so those running at once draw each frame once rather than
+/// once each.
+#[derive(Debug, Clone)]
+pub struct Ticker {
+    schedule: Arc<Mutex<Schedule>>,
+    /// Wakes [`Ticker::run`] when the schedule changed.
+    wake: (Sender<()>, Receiver<()>),
+}
+
+impl Ticker {
+    pub fn new() -> Self {
+        Self {
+            schedule: Arc::default(),
+            wake: flume::bounded(1),
+        }
+    }
+
+    /// Ticks at `fps` until the subscription is dropped.
+    pub fn every(&self, fps: u32) -> Subscription {
+        self.subscribe(Every::Frame(frame(fps)))
+    }
+
+    /// Ticks on each `interval` of the wall clock, so a time shown changes when the real one
+    /// does, until the subscription is dropped.
+    pub fn wall(&self, interval: Duration) -> Subscription {
+        self.subscribe(Every::Wall(interval))
+    }
+
+    /// Ticks at `fps` for `duration`, enough to play a tween to the end, and once more at the
+    /// end so its final value gets drawn.
+    pub fn play(&self, duration: Duration, fps: u32) {
+        let until = Instant::now() + duration;
+        self.add(Every::Frame(frame(fps)), Some(until));
+    }
+
+    fn subscribe(&self, every: Every) -> Subscription {
+        Subscription {
+            id: self.add(every, None),
+            ticker: self.clone(),
+        }
+    }
+
+    fn add(&self, every: Every, until: Option<Instant>) -> u64 {
+        let id = self
+            .schedule()
+            .add(every, until, Instant::now(), since_epoch());
+        _ = self.wake.0.try_send(());
+        id
+    }
+
+    fn schedule(&self) -> MutexGuard<'_, Schedule> {
+        // Nothing panics while holding it, and the schedule holds up either way.
+        self.schedule.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Sends the ticks the subscribers are due until the UI is gone.
+    pub async fn run(&self, tx: Sender<Msg>) {
+        loop {
+            let next = self.schedule().next();
+            let wake = self.wake.1.recv_async();
+            match next {
+                Some(next) => tokio::select! {
+                    _ = tokio::time::sleep_until(next.into()) => {}
+                    _ = wake => continue,
+                },
+                None => {
+                    _ = wake.await;
+                    continue;
+                }
+            }
+            let due = self.schedule().advance(Instant::now(), since_epoch());
+            // A tick the UI is too far behind to draw is made up for by the next one.
+            if due && queue::offer(&tx, Msg::Tick).is_err() {
+                break;
+            }
+        }
+    }
+}
+
+impl Default for Ticker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Keeps a subscription to the [`Ticker`] going until it's dropped.
+#[derive(Debug)]
+pub struct Subscription {
+    id: u64,
+    ticker: Ticker,
+}
+
+impl Drop for Subscription {
+    fn drop(&mut self) {
+        self.ticker.schedule().remove(self.id);
+        _ = self.ticker.wake.0.try_send(());
+    }
+}
+
+#[derive(Debug, Default)]
+struct Schedule {
+    next_id: u64,
+    subscribers: Vec<Subscriber>,
+}
+
+#[derive(Debug)]
+struct Subscriber {
+    id: u64,
+    every: Every,
+    /// When it's next due.
+    next: Instant,
+    /// When it stops on its own, for one playing a tween.
+    until: Option<Instant>,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Every {
+    Frame(Duration),
+    Wall(Duration),
+}
+
+impl Every {
+    /// When it's next due after `now`, `since_epoch` on the wall clock.
+    fn after(self, now: Instant, since_epoch: Duration) -> Instant {
+        match self {
+            Every::Frame(frame) => now + frame,
+            Every::Wall(interval) => now + clock::until_next(since_epoch, interval),
+        }
+    }
+}
+
+impl Schedule {
+    fn add(
+        &mut self,
+        every: Every,
+        until: Option<Instant>,
+        now: Instant,
+        since_epoch: Duration,
+    ) -> u64 {
+        let id = self.next_id;
+        self.next_id += 1;
+        let next = every.after(now, since_epoch);
+        self.subscribers.push(Subscriber {
+            id,
+            every,
+            next: until.map_or(next, |until| next.min(until)),
+            until,
+        });
+        id
+    }
+
+    fn remove(&mut self, id: u64) {
+        self.subscribers.retain(|subscriber| subscriber.id != id);
+    }
+
+    /// When the next tick is due, `None` with nothing subscribed.
+    fn next(&self) -> Option<Instant> {
+        self.subscribers
+            .iter()
+            .map(|subscriber| subscriber.next)
+            .min()
+    }
+
+    /// Moves the subscribers due by `now` on to when they're next due, and drops those that
+    /// played to their end. Returns whether any was due, for one tick between them.
+    fn advance(&mut self, now: Instant, since_epoch: Duration) -> bool {
+        let mut due = false;
+        self.subscribers.retain_mut(|subscriber| {
+            if subscriber.next > now {
+                return true;
+            }
+            due = true;
+            if subscriber.until.is_some_and(|until| until <= now) {
+                return false;
+            }
+            let next = subscriber.every.after(now, since_epoch);
+            subscriber.next = subscriber.until.map_or(next, |until| next.min(until));
+            true
+        });
+        due
+    }
+}
+
+fn since_epoch() -> Duration {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use crate::animation::{Easing, Every, Schedule, Tween, shake};
+
+    #[test]
+    fn easing_endpoints() {
+        for easing in [
+            Easing::Linear,
+            Easing::EaseIn,
+            Easing::EaseOut,
+            Easing::EaseInOut,
+        ] {
+            assert_eq!(easing.apply(0.0), 0.0);
+            assert_eq!(easing.apply(1.0), 1.0);
+        }
+        assert_eq!(Easing::EaseInOut.apply(0.5), 0.5);
+    }
+
+    #[test]
+    fn tween_interpolates() {
+        let tween = Tween::new(0.0, 10.0, Duration::from_secs(1), Easing::Linear);
+        let start = tween.start;
+
+        assert_eq!(tween.value_at(start), 0.0);
+        assert_eq!(tween.value_at(start + Duration::from_millis(500)), 5.0);
+        assert_eq!(tween.value_at(start + Duration::from_secs(2)), 10.0);
+    }
+
+    #[test]
+    fn tween_zero_duration_is_done() {
+        let tween = Tween::new(0, 4, Duration::ZERO, Easing::Linear);
+        assert_eq!(tween.value_at(Instant::now()), 4);
+    }
+
+    #[test]
+    fn subscribers_due_together_share_a_tick() {
+        let mut schedule = Schedule::default();
+        let (start, epoch) = (Instant::now(), Duration::from_secs(120));
+        let second = Duration::from_secs(1);
+        let clock = schedule.add(Every::Wall(Duration::from_secs(60)), None, start, epoch);
+        schedule.add(Every::Frame(second), Some(start + 2 * second), start, epoch);
+        schedule.add(Every::Frame(second), None, start, epoch);
+        assert_eq!(schedule.next(), Some(start + second));
+
+        assert!(schedule.advance(start + second, epoch + second));
+        assert!(!schedule.advance(start + second, epoch + second));
+        // The last frame of the tween, which is done after it.
+        assert!(schedule.advance(start + 2 * second, epoch + 2 * second));
+        assert_eq!(schedule.subscribers.len(), 2);
+
+        schedule.remove(2);
+        assert_eq!(schedule.next(), Some(start + 60 * second));
+        schedule.remove(clock);
+        assert_eq!(schedule.next(), None);
+    }
+
+    #[test]
+    fn shake_settles() {
+        assert_eq!(shake(0.0, 2.0), 0);
+        assert_eq!(shake(1.0, 2.0), 0);
+    }
+}
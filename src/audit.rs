@@ -0,0 +1,86 @@
+use std::ffi::CString;
+use std::sync::Once;
+
+/// What happened to a login, for the audit log.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    /// The username was sent to greetd.
+    Attempt,
+    Success,
+    Failure,
+    Cancelled,
+}
+
+impl Outcome {
+    fn name(self) -> &'static str {
+        match self {
+            Outcome::Attempt => "attempt",
+            Outcome::Success => "success",
+            Outcome::Failure => "failure",
+            Outcome::Cancelled => "cancelled",
+        }
+    }
+
+    fn priority(self) -> libc::c_int {
+        match self {
+            Outcome::Failure => libc::LOG_NOTICE,
+            _ => libc::LOG_INFO,
+        }
+    }
+}
+
+/// Records a login to syslog under the authpriv facility, next to sshd and login(1), where
+/// journald picks it up too. Syslog adds the time, and nothing the user answered PAM with is
+/// ever part of it.
+pub fn log(user: &str, outcome: Outcome, reason: Option<&str>) {
+    static OPEN: Once = Once::new();
+    OPEN.call_once(|| {
+        // SAFETY: the ident is a static string, which openlog keeps a pointer to.
+        unsafe { libc::openlog(c"impolite".as_ptr(), libc::LOG_PID, libc::LOG_AUTHPRIV) };
+    });
+    let line = format(user, outcome, reason, &tty());
+    // NUL can't be typed, but would cut the line short.
+    let Ok(line) = CString::new(line.replace('\0', "")) else {
+        return;
+    };
+    // SAFETY: both are NUL terminated, and `%s` makes the line data rather than a format.
+    unsafe { libc::syslog(outcome.priority(), c"%s".as_ptr(), line.as_ptr()) };
+}
+
+/// The line logged, as `key=value` pairs with the free text quoted.
+fn format(user: &str, outcome: Outcome, reason: Option<&str>, tty: &str) -> String {
+    let mut line = format!("login user={user:?} outcome={} tty={tty}", outcome.name());
+    if let Some(reason) = reason {
+        line.push_str(&format!(" reason={reason:?}"));
+    }
+    line
+}
+
+/// The terminal the greeter runs on, like `/dev/tty1`.
+fn tty() -> String {
+    std::fs::read_link("/proc/self/fd/0")
+        .map(|path| path.display().to_string())
+        .unwrap_or_else(|_| "unknown".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::audit::{Outcome, format};
+
+    #[test]
+    fn key_value_lines() {
+        assert_eq!(
+            format("alice", Outcome::Attempt, None, "/dev/tty1"),
+            r#"login user="alice" outcome=attempt tty=/dev/tty1"#
+        );
+        assert_eq!(
+            format(
+                "bob",
+                Outcome::Failure,
+                Some("Authentication failed"),
+                "/dev/tty2"
+            ),
+            r#"login user="bob" outcome=failure tty=/dev/tty2 reason="Authentication failed""#
+        );
+    }
+}
@@ -0,0 +1,121 @@
+use crate::Str;
+use crate::greetd::AuthMessageType;
+
+/// A message PAM sent through greetd during a login.
+#[derive(Debug, Clone)]
+pub struct Prompt {
+    pub kind: AuthMessageType,
+    pub text: Str,
+}
+
+impl Prompt {
+    /// Whether the user has to answer it, as opposed to just reading it.
+    pub fn is_question(&self) -> bool {
+        matches!(
+            self.kind,
+            AuthMessageType::Visible | AuthMessageType::Secret
+        )
+    }
+}
+
+/// The messages of the login in progress, to show how far along a conversation like
+/// password, then one time code, then confirmation is.
+#[derive(Debug, Clone, Default)]
+pub struct AuthProgress {
+    prompts: Vec<Prompt>,
+    answered: bool,
+    /// The most questions an earlier login asked, as the best guess at how many there are.
+    most_steps: usize,
+}
+
+impl AuthProgress {
+    /// Forgets the prompts of the last login, keeping how many steps it took.
+    pub fn start(&mut self) {
+        self.most_steps = self.most_steps.max(self.step());
+        self.prompts.clear();
+        self.answered = false;
+    }
+
+    /// Forgets everything about the logins so far, once a session has started.
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+
+    pub fn push(&mut self, kind: AuthMessageType, text: Str) {
+        self.prompts.push(Prompt { kind, text });
+        self.answered = false;
+    }
+
+    /// Marks the latest question as answered.
+    pub fn answer(&mut self) {
+        self.answered = true;
+    }
+
+    /// How many questions have been asked so far.
+    pub fn step(&self) -> usize {
+        self.prompts
+            .iter()
+            .filter(|prompt| prompt.is_question())
+            .count()
+    }
+
+    /// The question waiting on the user, past the first one that the password field answers.
+    pub fn question(&self) -> Option<&Prompt> {
+        self.prompts
+            .last()
+            .filter(|prompt| prompt.is_question() && !self.answered && self.step() > 1)
+    }
+
+    /// Every prompt so far except the [`AuthProgress::question`] being asked.
+    pub fn history(&self) -> &[Prompt] {
+        match self.question() {
+            Some(_) => &self.prompts[..self.prompts.len() - 1],
+            None => &self.prompts,
+        }
+    }
+
+    /// `2/3` once the login has more than one step, or just `step 2` until a login has
+    /// gotten far enough to know the total.
+    pub fn indicator(&self) -> Option<String> {
+        let step = self.step();
+        match step {
+            0 => None,
+            1 if self.most_steps < 2 => None,
+            step if step <= self.most_steps => Some(format!("{step}/{}", self.most_steps)),
+            step => Some(format!("step {step}")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::auth::AuthProgress;
+    use crate::greetd::AuthMessageType;
+
+    #[test]
+    fn indicator_learns_the_step_count() {
+        let mut auth = AuthProgress::default();
+        auth.push(AuthMessageType::Secret, "Password:".into());
+        assert_eq!(auth.indicator(), None);
+        assert!(auth.question().is_none());
+
+        auth.answer();
+        auth.push(AuthMessageType::Info, "Check your phone".into());
+        auth.push(AuthMessageType::Visible, "Code:".into());
+        assert_eq!(auth.indicator().as_deref(), Some("step 2"));
+        assert_eq!(auth.question().map(|prompt| &*prompt.text), Some("Code:"));
+        assert_eq!(auth.history().len(), 2);
+
+        auth.answer();
+        assert!(auth.question().is_none());
+        assert_eq!(auth.history().len(), 3);
+
+        auth.start();
+        auth.push(AuthMessageType::Secret, "Password:".into());
+        assert_eq!(auth.indicator().as_deref(), Some("1/2"));
+
+        auth.clear();
+        assert!(auth.history().is_empty());
+        assert_eq!(auth.indicator(), None);
+    }
+}
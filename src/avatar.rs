@@ -0,0 +1,138 @@
+use std::path::{Path, PathBuf};
+
+use ratatui::style::{Color, Style};
+use ratatui::text::{Line, Span};
+
+use crate::term::ColorDepth;
+use crate::users;
+
+/// Size of a picture avatar in cells. Every cell shows two pixels stacked on top of another.
+pub const WIDTH: u32 = 10;
+pub const HEIGHT: u32 = 5;
+
+/// Where AccountsService keeps the icons users pick in their desktop's settings.
+const ACCOUNTS_SERVICE_ICONS: &str = "/var/lib/AccountsService/icons";
+
+#[derive(Debug, Clone)]
+pub enum Avatar {
+    /// The user's picture drawn with half blocks.
+    Picture(Vec<Line<'static>>),
+    /// Shown when there is no picture or the terminal can't do it justice.
+    Initials(String),
+}
+
+impl Avatar {
+    /// `username`'s picture if they have one and the terminal has true color, their initials
+    /// otherwise. `icon` is tried before the usual places.
+    pub fn load(
+        username: &str,
+        real_name: Option<&str>,
+        icon: Option<&Path>,
+        depth: ColorDepth,
+    ) -> Self {
+        let picture = (depth == ColorDepth::TrueColor)
+            .then(|| {
+                icon.map(Path::to_path_buf)
+                    .into_iter()
+                    .chain(picture_paths(username))
+                    .find_map(|path| load_picture(&path))
+            })
+            .flatten();
+        match picture {
+            Some(lines) => Avatar::Picture(lines),
+            None => Avatar::Initials(initials(real_name.unwrap_or(username))),
+        }
+    }
+}
+
+fn picture_paths(username: &str) -> Vec<PathBuf> {
+    let mut paths = vec![Path::new(ACCOUNTS_SERVICE_ICONS).join(username)];
+    paths.extend(users::home_dir(username).map(|home| home.join(".face")));
+    paths
+}
+
+#[cfg(feature = "images")]
+fn load_picture(path: &Path) -> Option<Vec<Line<'static>>> {
+    let image = image::ImageReader::open(path)
+        .ok()?
+        .with_guessed_format()
+        .ok()?
+        .decode()
+        .ok()?;
+    let image = image
+        .resize_exact(WIDTH, HEIGHT * 2, image::imageops::FilterType::Triangle)
+        .to_rgba8();
+    Some(half_blocks(WIDTH, HEIGHT, |x, y| image.get_pixel(x, y).0))
+}
+
+#[cfg(not(feature = "images"))]
+fn load_picture(_path: &Path) -> Option<Vec<Line<'static>>> {
+    None
+}
+
+/// Draws a `width` by `height * 2` pixel RGBA image as `height` lines of upper half blocks.
+/// Mostly transparent pixels are left to the terminal's background.
+pub fn half_blocks(
+    width: u32,
+    height: u32,
+    pixel: impl Fn(u32, u32) -> [u8; 4],
+) -> Vec<Line<'static>> {
+    let color = |[r, g, b, a]: [u8; 4]| match a < 128 {
+        true => Color::Reset,
+        false => Color::Rgb(r, g, b),
+    };
+    (0..height)
+        .map(|row| {
+            (0..width)
+                .map(|x| {
+                    let style = Style::new()
+                        .fg(color(pixel(x, row * 2)))
+                        .bg(color(pixel(x, row * 2 + 1)));
+                    Span::styled("▀", style)
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Up to two initials, from the first and last word of `name`.
+pub fn initials(name: &str) -> String {
+    let mut words = name.split_whitespace();
+    let first = words.next().and_then(|word| word.chars().next());
+    let last = words.next_back().and_then(|word| word.chars().next());
+    first
+        .into_iter()
+        .chain(last)
+        .flat_map(char::to_uppercase)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::style::Color;
+
+    use crate::avatar::{half_blocks, initials};
+
+    #[test]
+    fn initials_of_first_and_last_name() {
+        assert_eq!(initials("ada lovelace"), "AL");
+        assert_eq!(initials("Grace Brewster Murray Hopper"), "GH");
+        assert_eq!(initials("root"), "R");
+        assert_eq!(initials(""), "");
+    }
+
+    #[test]
+    fn half_blocks_stack_two_pixels() {
+        let lines = half_blocks(2, 1, |x, y| match (x, y) {
+            (0, 0) => [255, 0, 0, 255],
+            (0, 1) => [0, 0, 255, 255],
+            _ => [0, 0, 0, 0],
+        });
+
+        assert_eq!(lines.len(), 1);
+        let spans = &lines[0].spans;
+        assert_eq!(spans[0].style.fg, Some(Color::Rgb(255, 0, 0)));
+        assert_eq!(spans[0].style.bg, Some(Color::Rgb(0, 0, 255)));
+        assert_eq!(spans[1].style.fg, Some(Color::Reset));
+    }
+}
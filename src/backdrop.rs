@@ -0,0 +1,232 @@
+use std::time::{Duration, Instant};
+
+use ratatui::style::{Color, Style};
+use ratatui::text::{Line, Span};
+use serde::{Deserialize, Serialize};
+
+use crate::color;
+use crate::theme::Theme;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum BackdropEffect {
+    Matrix,
+    Starfield,
+    GradientShift,
+}
+
+const MATRIX_GLYPHS: &[char] = &[
+    'ｱ', 'ｲ', 'ｳ', 'ｴ', 'ｵ', 'ｶ', 'ｷ', 'ｸ', 'ｹ', 'ｺ', '0', '1', '2', '3', '4', '5', '7', '8', '9',
+    ':', '=', '*', '+', '<', '>',
+];
+
+/// xorshift32, plenty for eye candy and keeps us off a `rand` dependency.
+#[derive(Debug, Clone)]
+struct Rng(u32);
+
+impl Rng {
+    fn next(&mut self) -> u32 {
+        let mut x = self.0;
+        x ^= x << 13;
+        x ^= x >> 17;
+        x ^= x << 5;
+        self.0 = x;
+        x
+    }
+
+    fn below(&mut self, n: u32) -> u32 {
+        self.next() % n.max(1)
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Raindrop {
+    head: i32,
+    speed: i32,
+    trail: i32,
+}
+
+#[derive(Debug, Clone)]
+struct Star {
+    x: i32,
+    y: i32,
+    layer: u8,
+}
+
+#[derive(Debug, Clone)]
+enum Particles {
+    Matrix(Vec<Raindrop>),
+    Starfield(Vec<Star>),
+    GradientShift,
+}
+
+/// An animated backdrop, advanced once per tick and rendered into lines.
+#[derive(Debug, Clone)]
+pub struct Backdrop {
+    width: u16,
+    height: u16,
+    frame: u64,
+    frame_time: Duration,
+    last_step: Instant,
+    from: Color,
+    to: Color,
+    rng: Rng,
+    particles: Particles,
+}
+
+impl Backdrop {
+    pub fn new(effect: BackdropEffect, width: u16, height: u16, fps: u32, theme: &Theme) -> Self {
+        let mut rng = Rng(0x9e37_79b9);
+        let particles = match effect {
+            BackdropEffect::Matrix => Particles::Matrix(
+                (0..width)
+                    .map(|_| Raindrop {
+                        head: -(rng.below(height as u32 * 2) as i32),
+                        speed: 1 + rng.below(2) as i32,
+                        trail: 3 + rng.below(height as u32) as i32,
+                    })
+                    .collect(),
+            ),
+            BackdropEffect::Starfield => Particles::Starfield(
+                (0..(width as u32 * height as u32 / 12))
+                    .map(|_| Star {
+                        x: rng.below(width as u32) as i32,
+                        y: rng.below(height as u32) as i32,
+                        layer: rng.below(3) as u8,
+                    })
+                    .collect(),
+            ),
+            BackdropEffect::GradientShift => Particles::GradientShift,
+        };
+        Self {
+            width,
+            height,
+            frame: 0,
+            frame_time: Duration::from_secs(1) / fps.clamp(1, 60),
+            last_step: Instant::now(),
+            from: theme.banner_from,
+            to: theme.accent,
+            rng,
+            particles,
+        }
+    }
+
+    /// Steps the simulation as many frames as fit between the last step and `now`, so the
+    /// speed doesn't depend on how often we get ticked.
+    pub fn advance(&mut self, now: Instant) {
+        while now.saturating_duration_since(self.last_step) >= self.frame_time {
+            self.last_step += self.frame_time;
+            self.step();
+        }
+    }
+
+    fn step(&mut self) {
+        self.frame += 1;
+        let (width, height) = (self.width as i32, self.height as i32);
+        match &mut self.particles {
+            Particles::Matrix(drops) => {
+                for drop in drops {
+                    drop.head += drop.speed;
+                    if drop.head - drop.trail > height {
+                        drop.head = -(self.rng.below(height as u32) as i32);
+                        drop.trail = 3 + self.rng.below(height as u32) as i32;
+                    }
+                }
+            }
+            Particles::Starfield(stars) => {
+                for star in stars {
+                    // Near stars move every frame, far ones every third.
+                    if self.frame.is_multiple_of(3 - star.layer as u64) {
+                        star.x -= 1;
+                    }
+                    if star.x < 0 {
+                        star.x = width - 1;
+                        star.y = self.rng.below(height as u32) as i32;
+                    }
+                }
+            }
+            Particles::GradientShift => {}
+        }
+    }
+
+    fn cell(&self, x: i32, y: i32) -> (char, Style) {
+        match &self.particles {
+            Particles::Matrix(drops) => {
+                let drop = &drops[x as usize];
+                let dist = drop.head - y;
+                if !(0..drop.trail).contains(&dist) {
+                    return (' ', Style::new());
+                }
+                let seed = (x as u64 * 31 + y as u64 * 17 + self.frame / 4) as usize;
+                let glyph = MATRIX_GLYPHS[seed % MATRIX_GLYPHS.len()];
+                let color = match dist {
+                    0 => color::lighten(self.to, 0.8),
+                    _ => color::darken(self.to, dist as f32 / drop.trail as f32),
+                };
+                (glyph, Style::new().fg(color))
+            }
+            Particles::Starfield(stars) => stars
+                .iter()
+                .find(|star| star.x == x && star.y == y)
+                .map(|star| {
+                    let color = color::mix(self.from, self.to, star.layer as f32 / 2.0);
+                    let glyph = match star.layer {
+                        0 => '.',
+                        1 => '+',
+                        _ => '*',
+                    };
+                    (glyph, Style::new().fg(color))
+                })
+                .unwrap_or((' ', Style::new())),
+            Particles::GradientShift => {
+                let phase = self.frame as f32 / 120.0;
+                let t = (x as f32 / self.width.max(1) as f32 + phase).fract();
+                // Triangle wave so the gradient loops without a seam.
+                let t = 1.0 - (2.0 * t - 1.0).abs();
+                let color = color::mix(self.from, self.to, t);
+                (' ', Style::new().bg(color))
+            }
+        }
+    }
+
+    pub fn render(&self) -> Vec<Line<'static>> {
+        (0..self.height as i32)
+            .map(|y| {
+                let mut spans: Vec<Span<'static>> = Vec::new();
+                for x in 0..self.width as i32 {
+                    let (ch, style) = self.cell(x, y);
+                    match spans.last_mut() {
+                        Some(span) if span.style == style => span.content.to_mut().push(ch),
+                        _ => spans.push(Span::styled(ch.to_string(), style)),
+                    }
+                }
+                Line::from(spans)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::backdrop::{Backdrop, BackdropEffect};
+    use crate::term::Appearance;
+    use crate::theme::ThemePreset;
+
+    #[test]
+    fn render_fills_the_area() {
+        for effect in [
+            BackdropEffect::Matrix,
+            BackdropEffect::Starfield,
+            BackdropEffect::GradientShift,
+        ] {
+            let theme = ThemePreset::Lipgloss.theme(Appearance::Dark);
+            let mut backdrop = Backdrop::new(effect, 20, 6, 15, &theme);
+            for _ in 0..50 {
+                backdrop.step();
+            }
+            let lines = backdrop.render();
+            assert_eq!(lines.len(), 6);
+            assert!(lines.iter().all(|line| line.width() == 20));
+        }
+    }
+}
@@ -0,0 +1,86 @@
+use std::path::Path;
+
+use ansi_to_tui::IntoText;
+use color_eyre::{Result, eyre::Context};
+use ratatui::text::{Line, Span, Text};
+use serde::{Deserialize, Serialize};
+use tracing::instrument;
+
+/// Where the art goes relative to the login form.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum Placement {
+    #[default]
+    Above,
+    Below,
+    Left,
+    Right,
+}
+
+/// Horizontal alignment of the art's lines against each other.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum Align {
+    Start,
+    #[default]
+    Center,
+    End,
+}
+
+/// Loads a plain text or ANSI art file.
+#[instrument(err)]
+pub fn load(path: &Path) -> Result<Text<'static>> {
+    let bytes = std::fs::read(path)
+        .wrap_err_with(|| format!("failed to read background art at {}", path.display()))?;
+    let text = bytes
+        .into_text()
+        .wrap_err("failed to parse ANSI escapes in background art")?;
+    Ok(text)
+}
+
+/// Pads every line of `text` so the block reads as aligned once laid out left to right.
+pub fn align(text: &Text<'static>, align: Align) -> Vec<Line<'static>> {
+    let width = text.lines.iter().map(Line::width).max().unwrap_or_default();
+    text.lines
+        .iter()
+        .map(|line| {
+            let slack = width - line.width();
+            let pad = match align {
+                Align::Start => 0,
+                Align::Center => slack / 2,
+                Align::End => slack,
+            };
+            let mut spans = Vec::with_capacity(line.spans.len() + 1);
+            spans.push(Span::raw(" ".repeat(pad)));
+            spans.extend(
+                line.spans
+                    .iter()
+                    .cloned()
+                    .map(|span| span.patch_style(line.style)),
+            );
+            Line::from(spans)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::text::Text;
+
+    use crate::background::{Align, align};
+
+    #[test]
+    fn align_pads_lines() {
+        let text = Text::from("#\n###");
+        let widths = |align_to| {
+            align(&text, align_to)
+                .iter()
+                .map(|line| line.spans[0].content.len())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(widths(Align::Start), [0, 0]);
+        assert_eq!(widths(Align::Center), [1, 0]);
+        assert_eq!(widths(Align::End), [2, 0]);
+    }
+}
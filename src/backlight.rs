@@ -0,0 +1,99 @@
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+use color_eyre::{Result, eyre::Context, eyre::eyre};
+
+const BACKLIGHT: &str = "/sys/class/backlight";
+
+/// Never dims below this percentage, so a few presses too many can't leave the screen dark.
+const MIN_PERCENT: u64 = 5;
+
+/// Changes the screen's brightness by `percent` of its range, clamped so it stays readable,
+/// and returns the new brightness in percent. Uses `device` under `/sys/class/backlight`, or
+/// the first one there.
+pub fn step(device: Option<&str>, percent: i8) -> Result<u8> {
+    let device = match device {
+        Some(device) => Path::new(BACKLIGHT).join(device),
+        None => first_device(Path::new(BACKLIGHT)).ok_or_else(|| eyre!("no backlight found"))?,
+    };
+    step_at(&device, percent)
+}
+
+fn first_device(root: &Path) -> Option<PathBuf> {
+    let mut devices: Vec<_> = std::fs::read_dir(root)
+        .ok()?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .collect();
+    devices.sort();
+    devices.into_iter().next()
+}
+
+fn step_at(device: &Path, percent: i8) -> Result<u8> {
+    let read = |name: &str| -> Result<u64> {
+        let path = device.join(name);
+        let value = std::fs::read_to_string(&path)
+            .wrap_err_with(|| format!("failed to read {}", path.display()))?;
+        value
+            .trim()
+            .parse()
+            .wrap_err_with(|| format!("{} isn't a number", path.display()))
+    };
+    let max = read("max_brightness")?;
+    if max == 0 {
+        return Err(eyre!("{} can't be dimmed", device.display()));
+    }
+    let current = read("brightness")?;
+    let level = adjust(current, max, percent);
+    let path = device.join("brightness");
+    // Only root can write it, unless a udev rule hands it to the greeter's group.
+    std::fs::write(&path, level.to_string()).map_err(|err| match err.kind() {
+        ErrorKind::PermissionDenied => {
+            eyre!("the greeter isn't allowed to write {}", path.display())
+        }
+        _ => eyre!("failed to write {}: {err}", path.display()),
+    })?;
+    Ok((level * 100 / max) as u8)
+}
+
+/// The brightness `percent` of `max` away from `current`, between [`MIN_PERCENT`] and `max`.
+fn adjust(current: u64, max: u64, percent: i8) -> u64 {
+    let by = (max * percent.unsigned_abs() as u64).div_ceil(100);
+    let floor = (max * MIN_PERCENT).div_ceil(100).min(current);
+    match percent < 0 {
+        true => current.saturating_sub(by).max(floor),
+        false => (current + by).min(max),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::backlight::{adjust, step_at};
+
+    #[test]
+    fn clamps_to_the_range() {
+        assert_eq!(adjust(50, 100, 10), 60);
+        assert_eq!(adjust(95, 100, 10), 100);
+        assert_eq!(adjust(12, 100, -10), 5);
+        assert_eq!(adjust(3, 100, -10), 3);
+        assert_eq!(adjust(0, 7, 10), 1);
+    }
+
+    #[test]
+    fn writes_the_new_level() -> color_eyre::Result<()> {
+        let device =
+            std::env::temp_dir().join(format!("impolite-backlight-{}", std::process::id()));
+        std::fs::create_dir_all(&device)?;
+        std::fs::write(device.join("max_brightness"), "1000\n")?;
+        std::fs::write(device.join("brightness"), "400\n")?;
+
+        let percent = step_at(&device, -10)?;
+        let written = std::fs::read_to_string(device.join("brightness"))?;
+        std::fs::remove_dir_all(&device)?;
+
+        assert_eq!(percent, 30);
+        assert_eq!(written, "300");
+
+        Ok(())
+    }
+}
@@ -0,0 +1,53 @@
+use std::time::Duration;
+
+/// Delays between restarts of something that keeps failing, doubling from `min` up to `max`.
+#[derive(Debug, Clone)]
+pub struct Backoff {
+    min: Duration,
+    max: Duration,
+    next: Duration,
+}
+
+impl Backoff {
+    pub fn new(min: Duration, max: Duration) -> Self {
+        Self {
+            min,
+            max,
+            next: min,
+        }
+    }
+
+    /// How long to wait before the next restart. The one after that waits twice as long.
+    pub fn delay(&mut self) -> Duration {
+        let delay = self.next;
+        self.next = (self.next * 2).min(self.max);
+        delay
+    }
+
+    /// Back to `min`, once whatever failed has worked for a while.
+    pub fn reset(&mut self) {
+        self.next = self.min;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::backoff::Backoff;
+
+    #[test]
+    fn doubles_up_to_max() {
+        let second = Duration::from_secs(1);
+        let mut backoff = Backoff::new(second, second * 5);
+
+        let delays = (0..5).map(|_| backoff.delay()).collect::<Vec<_>>();
+        assert_eq!(
+            delays,
+            [second, second * 2, second * 4, second * 5, second * 5]
+        );
+
+        backoff.reset();
+        assert_eq!(backoff.delay(), second);
+    }
+}
@@ -0,0 +1,206 @@
+use serde::{Deserialize, Serialize};
+
+/// Bundled banner fonts, all drawn from the same 3x5 bitmap glyphs.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum BannerFont {
+    /// Two cells per pixel, five rows tall.
+    Block,
+    /// One cell per pixel, five rows tall.
+    Slim,
+    /// One cell per pixel, packed into three rows with half blocks.
+    Half,
+}
+
+const GLYPH_WIDTH: usize = 3;
+const GLYPH_HEIGHT: usize = 5;
+
+#[rustfmt::skip]
+const GLYPHS: &[(char, [&str; GLYPH_HEIGHT])] = &[
+    ('A', [".#.", "#.#", "###", "#.#", "#.#"]),
+    ('B', ["##.", "#.#", "##.", "#.#", "##."]),
+    ('C', [".##", "#..", "#..", "#..", ".##"]),
+    ('D', ["##.", "#.#", "#.#", "#.#", "##."]),
+    ('E', ["###", "#..", "##.", "#..", "###"]),
+    ('F', ["###", "#..", "##.", "#..", "#.."]),
+    ('G', [".##", "#..", "#.#", "#.#", ".##"]),
+    ('H', ["#.#", "#.#", "###", "#.#", "#.#"]),
+    ('I', ["###", ".#.", ".#.", ".#.", "###"]),
+    ('J', ["..#", "..#", "..#", "#.#", ".#."]),
+    ('K', ["#.#", "#.#", "##.", "#.#", "#.#"]),
+    ('L', ["#..", "#..", "#..", "#..", "###"]),
+    ('M', ["#.#", "###", "###", "#.#", "#.#"]),
+    ('N', ["##.", "#.#", "#.#", "#.#", "#.#"]),
+    ('O', [".#.", "#.#", "#.#", "#.#", ".#."]),
+    ('P', ["##.", "#.#", "##.", "#..", "#.."]),
+    ('Q', [".#.", "#.#", "#.#", "##.", ".##"]),
+    ('R', ["##.", "#.#", "##.", "#.#", "#.#"]),
+    ('S', [".##", "#..", ".#.", "..#", "##."]),
+    ('T', ["###", ".#.", ".#.", ".#.", ".#."]),
+    ('U', ["#.#", "#.#", "#.#", "#.#", "###"]),
+    ('V', ["#.#", "#.#", "#.#", "#.#", ".#."]),
+    ('W', ["#.#", "#.#", "###", "###", "#.#"]),
+    ('X', ["#.#", "#.#", ".#.", "#.#", "#.#"]),
+    ('Y', ["#.#", "#.#", ".#.", ".#.", ".#."]),
+    ('Z', ["###", "..#", ".#.", "#..", "###"]),
+    ('0', ["###", "#.#", "#.#", "#.#", "###"]),
+    ('1', [".#.", "##.", ".#.", ".#.", "###"]),
+    ('2', ["##.", "..#", ".#.", "#..", "###"]),
+    ('3', ["##.", "..#", ".#.", "..#", "##."]),
+    ('4', ["#.#", "#.#", "###", "..#", "..#"]),
+    ('5', ["###", "#..", "##.", "..#", "##."]),
+    ('6', [".##", "#..", "###", "#.#", "###"]),
+    ('7', ["###", "..#", ".#.", ".#.", ".#."]),
+    ('8', ["###", "#.#", "###", "#.#", "###"]),
+    ('9', ["###", "#.#", "###", "..#", "##."]),
+    ('-', ["...", "...", "###", "...", "..."]),
+    ('.', ["...", "...", "...", "...", ".#."]),
+    ('_', ["...", "...", "...", "...", "###"]),
+    ('!', [".#.", ".#.", ".#.", "...", ".#."]),
+    ('?', ["##.", "..#", ".#.", "...", ".#."]),
+    (':', ["...", ".#.", "...", ".#.", "..."]),
+    (' ', ["...", "...", "...", "...", "..."]),
+];
+
+fn glyph(ch: char) -> &'static [&'static str; GLYPH_HEIGHT] {
+    let ch = ch.to_ascii_uppercase();
+    GLYPHS
+        .iter()
+        .find(|(glyph, _)| *glyph == ch)
+        .or_else(|| GLYPHS.iter().find(|(glyph, _)| *glyph == '?'))
+        .map(|(_, rows)| rows)
+        .expect("'?' glyph is always present")
+}
+
+fn pixel(rows: &[&str; GLYPH_HEIGHT], row: usize, col: usize) -> bool {
+    rows.get(row)
+        .and_then(|line| line.as_bytes().get(col))
+        .is_some_and(|&byte| byte == b'#')
+}
+
+impl BannerFont {
+    pub const fn height(self) -> usize {
+        match self {
+            BannerFont::Block | BannerFont::Slim => GLYPH_HEIGHT,
+            BannerFont::Half => GLYPH_HEIGHT.div_ceil(2),
+        }
+    }
+
+    const fn cell_width(self) -> usize {
+        match self {
+            BannerFont::Block => 2,
+            BannerFont::Slim | BannerFont::Half => 1,
+        }
+    }
+
+    /// Width in cells of `text` rendered in this font, including letter spacing.
+    pub fn width(self, text: &str) -> usize {
+        let chars = text.chars().count();
+        let glyph = GLYPH_WIDTH * self.cell_width();
+        (chars * (glyph + 1)).saturating_sub(1)
+    }
+
+    /// The next smaller font, if any.
+    pub const fn downscale(self) -> Option<Self> {
+        match self {
+            BannerFont::Block => Some(BannerFont::Slim),
+            BannerFont::Slim => Some(BannerFont::Half),
+            BannerFont::Half => None,
+        }
+    }
+
+    pub fn render(self, text: &str) -> Vec<String> {
+        let glyphs = text.chars().map(glyph).collect::<Vec<_>>();
+        (0..self.height())
+            .map(|line| {
+                glyphs
+                    .iter()
+                    .map(|rows| {
+                        (0..GLYPH_WIDTH)
+                            .map(|col| self.cell(rows, line, col))
+                            .collect::<String>()
+                    })
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            })
+            .collect()
+    }
+
+    fn cell(self, rows: &[&str; GLYPH_HEIGHT], line: usize, col: usize) -> &'static str {
+        match self {
+            BannerFont::Block => match pixel(rows, line, col) {
+                true => "██",
+                false => "  ",
+            },
+            BannerFont::Slim => match pixel(rows, line, col) {
+                true => "█",
+                false => " ",
+            },
+            BannerFont::Half => {
+                let top = pixel(rows, line * 2, col);
+                let bottom = pixel(rows, line * 2 + 1, col);
+                match (top, bottom) {
+                    (true, true) => "█",
+                    (true, false) => "▀",
+                    (false, true) => "▄",
+                    (false, false) => " ",
+                }
+            }
+        }
+    }
+}
+
+/// Renders `text` in `font`, downscaling until it fits in `max_width` x `max_height`.
+///
+/// Returns `None` when not even the smallest font fits.
+pub fn fit(
+    text: &str,
+    font: BannerFont,
+    max_width: usize,
+    max_height: usize,
+) -> Option<Vec<String>> {
+    let mut font = Some(font);
+    while let Some(current) = font {
+        if current.width(text) <= max_width && current.height() <= max_height {
+            return Some(current.render(text));
+        }
+        font = current.downscale();
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::banner::{BannerFont, fit};
+
+    #[test]
+    fn render_dimensions() {
+        for font in [BannerFont::Block, BannerFont::Slim, BannerFont::Half] {
+            let lines = font.render("hi");
+            assert_eq!(lines.len(), font.height());
+            for line in lines {
+                assert_eq!(line.chars().count(), font.width("hi"));
+            }
+        }
+    }
+
+    #[test]
+    fn render_slim_glyph() {
+        assert_eq!(
+            BannerFont::Slim.render("t"),
+            ["███", " █ ", " █ ", " █ ", " █ "]
+        );
+    }
+
+    #[test]
+    fn fit_downscales() {
+        let text = "impolite";
+        let lines = fit(text, BannerFont::Block, BannerFont::Slim.width(text), 5);
+        assert_eq!(lines, Some(BannerFont::Slim.render(text)));
+
+        let lines = fit(text, BannerFont::Block, 80, 3);
+        assert_eq!(lines, Some(BannerFont::Half.render(text)));
+
+        assert_eq!(fit(text, BannerFont::Block, 4, 5), None);
+    }
+}
@@ -0,0 +1,115 @@
+use std::path::Path;
+
+const POWER_SUPPLY: &str = "/sys/class/power_supply";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BatteryStatus {
+    Charging,
+    Discharging,
+    Full,
+    /// Plugged in but held below full by the charge controller.
+    NotCharging,
+    Unknown,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Battery {
+    /// Charge in percent.
+    pub capacity: u8,
+    pub status: BatteryStatus,
+}
+
+impl Battery {
+    pub fn is_charging(&self) -> bool {
+        matches!(self.status, BatteryStatus::Charging)
+    }
+}
+
+/// All batteries in the system combined into one: the average charge, charging if any of
+/// them is. `None` on machines without a battery.
+pub fn read() -> Option<Battery> {
+    combine(&read_from(Path::new(POWER_SUPPLY)))
+}
+
+fn read_from(root: &Path) -> Vec<Battery> {
+    let Ok(entries) = std::fs::read_dir(root) else {
+        return Vec::new();
+    };
+    entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|supply| {
+            let kind = std::fs::read_to_string(supply.join("type")).unwrap_or_default();
+            kind.trim() == "Battery"
+        })
+        .filter_map(|supply| {
+            let capacity = std::fs::read_to_string(supply.join("capacity")).ok()?;
+            let status = std::fs::read_to_string(supply.join("status")).unwrap_or_default();
+            let status = match status.trim() {
+                "Charging" => BatteryStatus::Charging,
+                "Discharging" => BatteryStatus::Discharging,
+                "Full" => BatteryStatus::Full,
+                "Not charging" => BatteryStatus::NotCharging,
+                _ => BatteryStatus::Unknown,
+            };
+            Some(Battery {
+                capacity: capacity.trim().parse::<u8>().ok()?.min(100),
+                status,
+            })
+        })
+        .collect()
+}
+
+fn combine(batteries: &[Battery]) -> Option<Battery> {
+    let first = batteries.first()?;
+    let capacity = batteries
+        .iter()
+        .map(|battery| battery.capacity as usize)
+        .sum::<usize>()
+        / batteries.len();
+    let status = match batteries.iter().find(|battery| battery.is_charging()) {
+        Some(charging) => charging.status,
+        None => first.status,
+    };
+    Some(Battery {
+        capacity: capacity as u8,
+        status,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::battery::{Battery, BatteryStatus, combine, read_from};
+
+    #[test]
+    fn read_power_supplies() -> std::io::Result<()> {
+        let root = std::env::temp_dir().join(format!("impolite-battery-{}", std::process::id()));
+        for (name, kind, capacity, status) in [
+            ("BAT0", "Battery", "40", "Discharging"),
+            ("BAT1", "Battery", "81", "Charging"),
+            ("AC", "Mains", "", ""),
+        ] {
+            let supply = root.join(name);
+            std::fs::create_dir_all(&supply)?;
+            std::fs::write(supply.join("type"), format!("{kind}\n"))?;
+            std::fs::write(supply.join("capacity"), format!("{capacity}\n"))?;
+            std::fs::write(supply.join("status"), format!("{status}\n"))?;
+        }
+
+        let mut batteries = read_from(&root);
+        batteries.sort_by_key(|battery| battery.capacity);
+        std::fs::remove_dir_all(&root)?;
+
+        assert_eq!(batteries.len(), 2);
+        assert_eq!(
+            combine(&batteries),
+            Some(Battery {
+                capacity: 60,
+                status: BatteryStatus::Charging
+            })
+        );
+        assert_eq!(combine(&[]), None);
+
+        Ok(())
+    }
+}
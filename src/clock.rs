@@ -0,0 +1,60 @@
+use std::fmt::Write;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+/// Where on the screen the clock goes.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum ClockPosition {
+    #[default]
+    Top,
+    Bottom,
+}
+
+/// The local time formatted with the strftime style `format`.
+///
+/// An invalid format is shown as is instead of failing the whole render.
+pub fn now(format: &str) -> String {
+    let mut out = String::new();
+    match write!(out, "{}", chrono::Local::now().format(format)) {
+        Ok(()) => out,
+        Err(_) => format.to_string(),
+    }
+}
+
+/// How long until the next multiple of `interval` on the wall clock, `since_epoch` now, so a
+/// time shown changes when the real one does.
+pub fn until_next(since_epoch: Duration, interval: Duration) -> Duration {
+    let interval = interval.max(Duration::from_secs(1)).as_millis();
+    let elapsed = since_epoch.as_millis() % interval;
+    Duration::from_millis((interval - elapsed) as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::clock::{now, until_next};
+
+    #[test]
+    fn aligns_to_interval() {
+        let minute = Duration::from_secs(60);
+
+        assert_eq!(
+            until_next(Duration::from_secs(125), minute),
+            Duration::from_secs(55)
+        );
+        assert_eq!(until_next(Duration::from_secs(120), minute), minute);
+        assert_eq!(
+            until_next(Duration::from_millis(1_250), Duration::ZERO),
+            Duration::from_millis(750)
+        );
+    }
+
+    #[test]
+    fn invalid_format_falls_back() {
+        assert_eq!(now("%Q"), "%Q");
+        assert_eq!(now("literal"), "literal");
+    }
+}
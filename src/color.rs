@@ -0,0 +1,207 @@
+use ratatui::style::Color;
+
+/// The 16 base colors as xterm draws them by default.
+const ANSI: [(u8, u8, u8); 16] = [
+    (0x00, 0x00, 0x00),
+    (0xcd, 0x00, 0x00),
+    (0x00, 0xcd, 0x00),
+    (0xcd, 0xcd, 0x00),
+    (0x00, 0x00, 0xee),
+    (0xcd, 0x00, 0xcd),
+    (0x00, 0xcd, 0xcd),
+    (0xe5, 0xe5, 0xe5),
+    (0x7f, 0x7f, 0x7f),
+    (0xff, 0x00, 0x00),
+    (0x00, 0xff, 0x00),
+    (0xff, 0xff, 0x00),
+    (0x5c, 0x5c, 0xff),
+    (0xff, 0x00, 0xff),
+    (0x00, 0xff, 0xff),
+    (0xff, 0xff, 0xff),
+];
+
+/// The RGB value `color` is most likely drawn as.
+///
+/// Named and indexed colors are resolved against the xterm defaults, `Reset` has no known
+/// value and returns `None`.
+pub fn to_rgb(color: Color) -> Option<(u8, u8, u8)> {
+    let index = match color {
+        Color::Reset => return None,
+        Color::Rgb(r, g, b) => return Some((r, g, b)),
+        Color::Indexed(index) => index,
+        Color::Black => 0,
+        Color::Red => 1,
+        Color::Green => 2,
+        Color::Yellow => 3,
+        Color::Blue => 4,
+        Color::Magenta => 5,
+        Color::Cyan => 6,
+        Color::Gray => 7,
+        Color::DarkGray => 8,
+        Color::LightRed => 9,
+        Color::LightGreen => 10,
+        Color::LightYellow => 11,
+        Color::LightBlue => 12,
+        Color::LightMagenta => 13,
+        Color::LightCyan => 14,
+        Color::White => 15,
+    };
+    let rgb = match index {
+        0..16 => ANSI[index as usize],
+        16..232 => {
+            let level = |n: u8| if n == 0 { 0 } else { 55 + n * 40 };
+            let n = index - 16;
+            (level(n / 36), level(n / 6 % 6), level(n % 6))
+        }
+        232.. => {
+            let gray = 8 + (index - 232) * 10;
+            (gray, gray, gray)
+        }
+    };
+    Some(rgb)
+}
+
+/// A color in hue (degrees), saturation and lightness (both `0.0..=1.0`).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Hsl {
+    pub h: f32,
+    pub s: f32,
+    pub l: f32,
+}
+
+impl Hsl {
+    pub fn from_rgb((r, g, b): (u8, u8, u8)) -> Self {
+        let [r, g, b] = [r, g, b].map(|c| c as f32 / 255.0);
+        let max = r.max(g).max(b);
+        let min = r.min(g).min(b);
+        let l = (max + min) / 2.0;
+        let delta = max - min;
+        if delta == 0.0 {
+            return Self { h: 0.0, s: 0.0, l };
+        }
+        let s = delta / (1.0 - (2.0 * l - 1.0).abs());
+        let h = if max == r {
+            60.0 * ((g - b) / delta).rem_euclid(6.0)
+        } else if max == g {
+            60.0 * ((b - r) / delta + 2.0)
+        } else {
+            60.0 * ((r - g) / delta + 4.0)
+        };
+        Self { h, s, l }
+    }
+
+    pub fn to_rgb(self) -> (u8, u8, u8) {
+        let c = (1.0 - (2.0 * self.l - 1.0).abs()) * self.s;
+        let h = self.h.rem_euclid(360.0) / 60.0;
+        let x = c * (1.0 - (h % 2.0 - 1.0).abs());
+        let (r, g, b) = match h as u32 {
+            0 => (c, x, 0.0),
+            1 => (x, c, 0.0),
+            2 => (0.0, c, x),
+            3 => (0.0, x, c),
+            4 => (x, 0.0, c),
+            _ => (c, 0.0, x),
+        };
+        let m = self.l - c / 2.0;
+        let channel = |v: f32| ((v + m) * 255.0).round().clamp(0.0, 255.0) as u8;
+        (channel(r), channel(g), channel(b))
+    }
+}
+
+impl From<Hsl> for Color {
+    fn from(hsl: Hsl) -> Self {
+        let (r, g, b) = hsl.to_rgb();
+        Color::Rgb(r, g, b)
+    }
+}
+
+/// Linearly interpolates between two colors, `t` in `0.0..=1.0`.
+///
+/// Colors without a known RGB value can't be blended; the nearest endpoint is returned.
+pub fn mix(from: Color, to: Color, t: f32) -> Color {
+    let t = t.clamp(0.0, 1.0);
+    match (to_rgb(from), to_rgb(to)) {
+        _ if t == 0.0 => from,
+        _ if t == 1.0 => to,
+        (Some((r1, g1, b1)), Some((r2, g2, b2))) => {
+            let lerp = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * t).round() as u8;
+            Color::Rgb(lerp(r1, r2), lerp(g1, g2), lerp(b1, b2))
+        }
+        _ if t < 0.5 => from,
+        _ => to,
+    }
+}
+
+/// `fg` drawn at `alpha` opacity over `bg`.
+pub fn blend(fg: Color, bg: Color, alpha: f32) -> Color {
+    mix(bg, fg, alpha)
+}
+
+/// Moves `color`'s lightness `amount` of the way towards white.
+pub fn lighten(color: Color, amount: f32) -> Color {
+    with_hsl(color, |hsl| Hsl {
+        l: hsl.l + (1.0 - hsl.l) * amount.clamp(0.0, 1.0),
+        ..hsl
+    })
+}
+
+/// Moves `color`'s lightness `amount` of the way towards black.
+pub fn darken(color: Color, amount: f32) -> Color {
+    with_hsl(color, |hsl| Hsl {
+        l: hsl.l * (1.0 - amount.clamp(0.0, 1.0)),
+        ..hsl
+    })
+}
+
+fn with_hsl(color: Color, f: impl FnOnce(Hsl) -> Hsl) -> Color {
+    match to_rgb(color) {
+        Some(rgb) => f(Hsl::from_rgb(rgb)).into(),
+        None => color,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::style::Color;
+
+    use crate::color::{Hsl, darken, lighten, mix, to_rgb};
+
+    #[test]
+    fn mix_midpoint() {
+        let color = mix(Color::Rgb(0, 0, 0), Color::Rgb(255, 100, 10), 0.5);
+        assert_eq!(color, Color::Rgb(128, 50, 5));
+    }
+
+    #[test]
+    fn mix_resolves_indexed_colors() {
+        assert_eq!(
+            mix(Color::Black, Color::White, 0.5),
+            Color::Rgb(128, 128, 128)
+        );
+        assert_eq!(mix(Color::Reset, Color::Blue, 0.2), Color::Reset);
+        assert_eq!(mix(Color::Reset, Color::Blue, 0.8), Color::Blue);
+    }
+
+    #[test]
+    fn indexed_palette() {
+        assert_eq!(to_rgb(Color::Indexed(16)), Some((0, 0, 0)));
+        assert_eq!(to_rgb(Color::Indexed(196)), Some((255, 0, 0)));
+        assert_eq!(to_rgb(Color::Indexed(244)), Some((128, 128, 128)));
+    }
+
+    #[test]
+    fn hsl_round_trip() {
+        for rgb in [(0xf2, 0x5d, 0x94), (0x00, 0x87, 0x5f), (0x12, 0x12, 0x12)] {
+            assert_eq!(Hsl::from_rgb(rgb).to_rgb(), rgb);
+        }
+    }
+
+    #[test]
+    fn lighten_and_darken() {
+        let color = Color::Rgb(0x80, 0x20, 0x20);
+
+        assert_eq!(lighten(color, 1.0), Color::Rgb(255, 255, 255));
+        assert_eq!(darken(color, 1.0), Color::Rgb(0, 0, 0));
+        assert_eq!(darken(Color::Reset, 0.5), Color::Reset);
+    }
+}
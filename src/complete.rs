@@ -0,0 +1,109 @@
+use crate::Str;
+
+/// How many candidates the completion popup lists at once.
+pub const HEIGHT: usize = 5;
+
+/// Tab completion of the username from the accounts NSS knows.
+///
+/// Like in a shell, one match is filled in right away. More than one fill in what they have in
+/// common and open a popup, which completing again cycles through.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Completion {
+    candidates: Vec<Str>,
+    selected: Option<usize>,
+}
+
+impl Completion {
+    /// The candidates in the popup, empty while it's closed.
+    pub fn candidates(&self) -> &[Str] {
+        &self.candidates
+    }
+
+    pub fn selected(&self) -> Option<usize> {
+        self.selected
+    }
+
+    /// Completes `value` against `names`, giving the new state and what the field should
+    /// hold, or `None` if there's nothing to complete.
+    pub fn complete(&self, names: &[Str], value: &str) -> Option<(Completion, String)> {
+        if !self.candidates.is_empty() {
+            let selected = self
+                .selected
+                .map_or(0, |selected| (selected + 1) % self.candidates.len());
+            let completion = Completion {
+                candidates: self.candidates.clone(),
+                selected: Some(selected),
+            };
+            return Some((completion, self.candidates[selected].to_string()));
+        }
+        if value.is_empty() {
+            return None;
+        }
+        let candidates = names
+            .iter()
+            .filter(|name| name.starts_with(value))
+            .cloned()
+            .collect::<Vec<_>>();
+        match candidates.as_slice() {
+            [] => None,
+            [only] if **only == *value => None,
+            [only] => Some((Completion::default(), only.to_string())),
+            _ => {
+                let prefix = common_prefix(&candidates).to_string();
+                let completion = Completion {
+                    candidates,
+                    selected: None,
+                };
+                Some((completion, prefix))
+            }
+        }
+    }
+}
+
+/// The longest prefix all of `names` start with.
+fn common_prefix(names: &[Str]) -> &str {
+    let Some((first, rest)) = names.split_first() else {
+        return "";
+    };
+    let len = rest.iter().fold(first.len(), |len, name| {
+        first[..len]
+            .char_indices()
+            .zip(name.chars())
+            .find(|((_, a), b)| a != b)
+            .map_or(len.min(name.len()), |((idx, _), _)| idx)
+    });
+    &first[..len]
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Str;
+    use crate::complete::Completion;
+
+    #[test]
+    fn completes_like_a_shell() {
+        let names = ["alice", "alex", "bob", "bobby"].map(Str::from);
+        let none = Completion::default();
+
+        assert_eq!(none.complete(&names, ""), None);
+        assert_eq!(none.complete(&names, "x"), None);
+        assert_eq!(none.complete(&names, "bobby"), None);
+        assert_eq!(
+            none.complete(&names, "ali"),
+            Some((Completion::default(), "alice".into()))
+        );
+
+        let (popup, value) = none.complete(&names, "a").unwrap();
+        assert_eq!(value, "al");
+        assert_eq!(popup.candidates(), ["alice", "alex"].map(Str::from));
+
+        let (popup, value) = popup.complete(&names, &value).unwrap();
+        assert_eq!((value.as_str(), popup.selected()), ("alice", Some(0)));
+        let (popup, value) = popup.complete(&names, &value).unwrap();
+        let (popup, value) = popup.complete(&names, &value).unwrap();
+        assert_eq!((value.as_str(), popup.selected()), ("alice", Some(0)));
+
+        let (popup, _) = none.complete(&names, "bob").unwrap();
+        assert_eq!(popup.candidates().len(), 2);
+    }
+}
@@ -0,0 +1,686 @@
+use std::path::{Path, PathBuf};
+
+use color_eyre::{Result, Section, eyre::Context};
+use serde::{Deserialize, Serialize};
+use tracing::instrument;
+
+use crate::Str;
+use crate::backdrop::BackdropEffect;
+use crate::background::{Align, Placement};
+use crate::banner::BannerFont;
+use crate::clock::ClockPosition;
+use crate::keymap::{EscapeAction, KeyMap};
+use crate::layout::Columns;
+use crate::readline::PastePolicy;
+use crate::secret::PasswordMask;
+use crate::status_bar::SegmentKind;
+use crate::styling::CursorShape;
+use crate::term::AppearancePreference;
+use crate::theme::{ThemeOverrides, ThemePreset};
+use crate::widgets::WidgetPosition;
+
+pub const DEFAULT_CONFIG_PATH: &str = "/etc/impolite/config.toml";
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct Config {
+    pub banner: BannerConfig,
+    pub background: BackgroundConfig,
+    pub greeting: GreetingConfig,
+    pub animation: AnimationConfig,
+    pub theme: ThemeConfig,
+    pub symbols: SymbolsConfig,
+    pub idle: IdleConfig,
+    pub clock: ClockConfig,
+    pub motd: MotdConfig,
+    pub os_info: OsInfoConfig,
+    pub uptime: UptimeConfig,
+    pub battery: BatteryConfig,
+    pub network: NetworkConfig,
+    pub disk: DiskConfig,
+    pub status_bar: StatusBarConfig,
+    pub user_menu: UserMenuConfig,
+    pub avatar: AvatarConfig,
+    pub power: PowerConfig,
+    pub backlight: BacklightConfig,
+    pub keyboard: KeyboardConfig,
+    pub console_keymaps: ConsoleKeymapsConfig,
+    pub input: InputConfig,
+    pub remember: RememberConfig,
+    pub escape: EscapeConfig,
+    pub quit: QuitConfig,
+    pub layout: LayoutConfig,
+    /// Key bindings replacing the defaults, see [`KeyMap`].
+    pub keys: KeyMap,
+    /// Shell commands whose output is shown as a widget, see [`CommandWidgetConfig`].
+    pub widgets: Vec<CommandWidgetConfig>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct BannerConfig {
+    pub enabled: bool,
+    /// Text to render instead of the hostname.
+    pub text: Option<Str>,
+    pub font: BannerFont,
+}
+
+impl Default for BannerConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            text: None,
+            font: BannerFont::Block,
+        }
+    }
+}
+
+/// Text or ANSI art shown next to the form, for per-machine branding.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct BackgroundConfig {
+    pub path: Option<PathBuf>,
+    /// Image drawn with terminal graphics, preferred over `path` where supported.
+    pub image: Option<PathBuf>,
+    pub placement: Placement,
+    pub align: Align,
+    /// Animated effect drawn in place of the art when no `path` is set.
+    pub effect: Option<BackdropEffect>,
+    pub fps: u32,
+    pub width: u16,
+    pub height: u16,
+}
+
+impl Default for BackgroundConfig {
+    fn default() -> Self {
+        Self {
+            path: None,
+            image: None,
+            placement: Placement::default(),
+            align: Align::default(),
+            effect: None,
+            fps: 15,
+            width: 32,
+            height: 10,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct GreetingConfig {
+    /// Type the greeting out one character at a time on startup.
+    pub typewriter: bool,
+    pub typewriter_delay_ms: u64,
+    /// Type the greeting out again after a failed login.
+    pub retype_on_failure: bool,
+}
+
+impl Default for GreetingConfig {
+    fn default() -> Self {
+        Self {
+            typewriter: false,
+            typewriter_delay_ms: 35,
+            retype_on_failure: false,
+        }
+    }
+}
+
+/// Focus fades, the error shake and the auth spinner.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct AnimationConfig {
+    pub enabled: bool,
+    pub fps: u32,
+}
+
+impl Default for AnimationConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            fps: 30,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct ThemeConfig {
+    pub preset: ThemePreset,
+    /// `auto` queries the terminal background with OSC 11.
+    pub appearance: AppearancePreference,
+    /// Restyle the UI with the user's own theme once their username is entered, see
+    /// [`crate::theme::load_user`]. Ignored in per-user theme files.
+    pub per_user: bool,
+    /// Individual roles, e.g. `accent = "#87d8cc"`, applied over the preset.
+    #[serde(flatten)]
+    pub overrides: ThemeOverrides,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct ClockConfig {
+    pub enabled: bool,
+    /// strftime style, see `chrono::format::strftime`.
+    pub format: Str,
+    /// Used instead of `format` while the UI is dimmed, so it doesn't tick every second.
+    pub idle_format: Str,
+    pub interval_secs: u64,
+    pub position: ClockPosition,
+}
+
+impl Default for ClockConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            format: "%a, %d %b %Y - %H:%M:%S".into(),
+            idle_format: "%a, %d %b %Y - %H:%M".into(),
+            interval_secs: 1,
+            position: ClockPosition::Top,
+        }
+    }
+}
+
+/// The distro's name next to the greeting, from os-release(5).
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct OsInfoConfig {
+    pub enabled: bool,
+}
+
+/// System uptime in the footer.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct UptimeConfig {
+    pub enabled: bool,
+    pub interval_secs: u64,
+}
+
+impl Default for UptimeConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval_secs: 60,
+        }
+    }
+}
+
+/// Battery charge in the footer, on machines that have one.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct BatteryConfig {
+    pub enabled: bool,
+    pub interval_secs: u64,
+    /// Below this percentage the charge is drawn in the theme's warning color.
+    pub warning: u8,
+    /// Below this percentage the charge is drawn in the theme's error color.
+    pub critical: u8,
+}
+
+impl Default for BatteryConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval_secs: 30,
+            warning: 30,
+            critical: 15,
+        }
+    }
+}
+
+/// A warning in the footer while a filesystem is nearly full, since a full `/` can keep
+/// people from logging in.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct DiskConfig {
+    pub enabled: bool,
+    pub interval_secs: u64,
+    pub path: PathBuf,
+    /// From this percentage in use on, the warning is shown in the theme's warning color.
+    pub warning: u8,
+    /// From this percentage in use on, it's shown in the theme's error color.
+    pub critical: u8,
+}
+
+impl Default for DiskConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval_secs: 300,
+            path: PathBuf::from("/"),
+            warning: 90,
+            critical: 97,
+        }
+    }
+}
+
+/// Link and default route state in the footer.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct NetworkConfig {
+    pub enabled: bool,
+    pub interval_secs: u64,
+}
+
+impl Default for NetworkConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval_secs: 10,
+        }
+    }
+}
+
+/// A list of users to pick from instead of typing a name, like tuigreet's `--user-menu`.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct UserMenuConfig {
+    pub enabled: bool,
+    /// Lowest UID listed, `UID_MIN` from login.defs(5) by default.
+    pub uid_min: Option<u32>,
+    /// Highest UID listed, `UID_MAX` from login.defs(5) by default.
+    pub uid_max: Option<u32>,
+}
+
+/// Shutdown, reboot, suspend and hibernate from the footer, through logind or a shell command.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct PowerConfig {
+    pub enabled: bool,
+    /// Ask before shutting down or rebooting, which ends everyone's sessions, and before
+    /// anything started from its F key. Off for a single-user machine that doesn't need it.
+    pub confirm: bool,
+    /// Commands run with `sh -c` instead of asking logind over D-Bus.
+    pub shutdown: Option<Str>,
+    pub reboot: Option<Str>,
+    pub suspend: Option<Str>,
+    pub hibernate: Option<Str>,
+}
+
+impl Default for PowerConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            confirm: true,
+            shutdown: None,
+            reboot: None,
+            suspend: None,
+            hibernate: None,
+        }
+    }
+}
+
+/// Screen brightness keys, for laptops that boot too dark to read the form.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct BacklightConfig {
+    pub enabled: bool,
+    /// The device under `/sys/class/backlight`, the first one there by default.
+    pub device: Option<Str>,
+    /// How many percent of the range one press changes.
+    pub step: u8,
+}
+
+impl Default for BacklightConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            device: None,
+            step: 10,
+        }
+    }
+}
+
+/// Console keymaps to switch between while typing, for passwords set under another layout.
+/// Names are the ones loadkeys(1) takes, like `de-latin1`.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct ConsoleKeymapsConfig {
+    pub keymaps: Vec<Str>,
+}
+
+/// An on-screen keyboard under the form, for kiosks without a physical keyboard.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct KeyboardConfig {
+    pub enabled: bool,
+}
+
+/// How the text fields edit.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct InputConfig {
+    /// Vi style normal and insert modes in the username field.
+    pub vi_mode: bool,
+    /// Tab completion of the username. Off by default since, like the user menu, it tells
+    /// anyone at the console which accounts exist.
+    pub complete_usernames: bool,
+    pub password_paste: PastePolicy,
+    pub password_mask: PasswordMask,
+    /// Whether Tab on the password field goes back around to the first field, and Shift+Tab
+    /// on the first one to the password field.
+    pub tab_wraps: bool,
+}
+
+impl Default for InputConfig {
+    fn default() -> Self {
+        Self {
+            vi_mode: false,
+            complete_usernames: false,
+            password_paste: PastePolicy::default(),
+            password_mask: PasswordMask::default(),
+            tab_wraps: true,
+        }
+    }
+}
+
+/// What Esc does, depending on where the focus is.
+///
+/// ```toml
+/// [escape]
+/// username = "nothing"
+/// password = "cancel"
+/// session-picker = "power-menu"
+/// ```
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct EscapeConfig {
+    pub username: EscapeAction,
+    pub password: EscapeAction,
+    pub session_picker: EscapeAction,
+}
+
+impl Default for EscapeConfig {
+    fn default() -> Self {
+        Self {
+            username: EscapeAction::Clear,
+            password: EscapeAction::Back,
+            session_picker: EscapeAction::Cancel,
+        }
+    }
+}
+
+/// Remembers who logged in, to bring recent usernames back with Up and Down.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct RememberConfig {
+    pub enabled: bool,
+    pub path: PathBuf,
+    /// How many usernames are kept.
+    pub max: usize,
+}
+
+impl Default for RememberConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            path: PathBuf::from("/var/cache/impolite/usernames"),
+            max: 10,
+        }
+    }
+}
+
+/// Whether the quit key and the palette's Quit command work.
+///
+/// Off by default, since quitting on a real seat leaves the console to greetd, which may or may
+/// not start the greeter again. `--debug` and `impolite preview` can always quit.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct QuitConfig {
+    pub enabled: bool,
+}
+
+/// Where the user menu and session picker go.
+///
+/// ```toml
+/// [layout]
+/// columns = "two"
+/// two-column-min-width = 120
+/// ```
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct LayoutConfig {
+    pub columns: Columns,
+    /// Narrowest terminal that gets two columns, in cells.
+    pub two_column_min_width: u16,
+}
+
+impl Default for LayoutConfig {
+    fn default() -> Self {
+        Self {
+            columns: Columns::One,
+            two_column_min_width: 120,
+        }
+    }
+}
+
+/// The picture or initials of the user about to log in, above the form.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct AvatarConfig {
+    pub enabled: bool,
+}
+
+/// A bar along the bottom edge, with segments on the left, center and right.
+///
+/// ```toml
+/// [status-bar]
+/// enabled = true
+/// left = ["seat"]
+/// right = ["caps-lock", "keymap", "battery", "clock"]
+/// ```
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct StatusBarConfig {
+    pub enabled: bool,
+    pub left: Vec<SegmentKind>,
+    pub center: Vec<SegmentKind>,
+    pub right: Vec<SegmentKind>,
+}
+
+impl StatusBarConfig {
+    /// Whether the bar is on and shows `kind` somewhere.
+    pub fn shows(&self, kind: SegmentKind) -> bool {
+        self.enabled
+            && [&self.left, &self.center, &self.right]
+                .iter()
+                .any(|side| side.contains(&kind))
+    }
+}
+
+impl Default for StatusBarConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            left: vec![SegmentKind::Seat, SegmentKind::ViMode],
+            center: Vec::new(),
+            right: vec![
+                SegmentKind::CapsLock,
+                SegmentKind::Keymap,
+                SegmentKind::Battery,
+                SegmentKind::Clock,
+            ],
+        }
+    }
+}
+
+/// A widget showing the first line a shell command prints, rerun every `interval-secs`.
+///
+/// ```toml
+/// [[widgets]]
+/// name = "weather"
+/// command = "curl -s 'wttr.in/?format=3'"
+/// interval-secs = 900
+/// ```
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct CommandWidgetConfig {
+    pub name: Str,
+    /// Run with `sh -c` as the greeter user.
+    pub command: Str,
+    pub interval_secs: u64,
+    /// The command is killed after this long.
+    pub timeout_secs: u64,
+    /// Output is cut down to this many characters.
+    pub max_len: usize,
+    pub position: WidgetPosition,
+}
+
+impl Default for CommandWidgetConfig {
+    fn default() -> Self {
+        Self {
+            name: "".into(),
+            command: "".into(),
+            interval_secs: 60,
+            timeout_secs: 5,
+            max_len: 40,
+            position: WidgetPosition::Footer,
+        }
+    }
+}
+
+/// Messages of the day shown beneath the form, for maintenance notices and the like.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct MotdConfig {
+    pub enabled: bool,
+    pub paths: Vec<PathBuf>,
+    /// Longer messages scroll with Alt+Up and Alt+Down.
+    pub height: u16,
+}
+
+impl Default for MotdConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            paths: vec!["/etc/motd".into(), "/run/motd.dynamic".into()],
+            height: 8,
+        }
+    }
+}
+
+/// What happens when nobody touches the keyboard for a while.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct IdleConfig {
+    /// Fade the UI out after this many seconds without input, unset to never dim.
+    pub dim_after_secs: Option<u64>,
+    /// Blank the screen entirely after this many seconds, unset to never blank.
+    pub blank_after_secs: Option<u64>,
+    /// Also power down the Linux console with `setterm --blank` while blank.
+    pub blank_console: bool,
+    /// Empty the fields and cancel a login in progress after this many seconds without input,
+    /// in case someone walked away halfway through, unset to leave them.
+    pub clear_after_secs: Option<u64>,
+}
+
+impl Default for IdleConfig {
+    fn default() -> Self {
+        Self {
+            dim_after_secs: Some(60),
+            blank_after_secs: None,
+            blank_console: false,
+            clear_after_secs: None,
+        }
+    }
+}
+
+/// The form's chrome: field markers and the cursor.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct SymbolsConfig {
+    /// Drawn before the focused field's label.
+    pub prompt: Str,
+    /// Drawn before the selected entry of lists like the session picker.
+    pub selection: Str,
+    pub cursor: CursorShape,
+    /// Drawn before errors, so they don't rely on color alone.
+    pub error: Str,
+    /// Drawn before success messages.
+    pub success: Str,
+    /// The terminal font has Nerd Fonts glyphs, like distro logos.
+    pub nerd_fonts: bool,
+}
+
+impl Default for SymbolsConfig {
+    fn default() -> Self {
+        Self {
+            prompt: "| ".into(),
+            selection: "> ".into(),
+            cursor: CursorShape::Block,
+            error: "✗ ".into(),
+            success: "✓ ".into(),
+            nerd_fonts: false,
+        }
+    }
+}
+
+impl Config {
+    /// Loads the config from `path`, or from [`DEFAULT_CONFIG_PATH`] if it exists.
+    ///
+    /// A missing default config is not an error, a missing explicit one is.
+    #[instrument(err)]
+    pub fn load(path: Option<&Path>) -> Result<Self> {
+        let (path, explicit) = match path {
+            Some(path) => (path, true),
+            None => (Path::new(DEFAULT_CONFIG_PATH), false),
+        };
+        let contents = match std::fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(err) if !explicit && err.kind() == std::io::ErrorKind::NotFound => {
+                return Ok(Self::default());
+            }
+            Err(err) => {
+                return Err(err)
+                    .wrap_err_with(|| format!("failed to read config at {}", path.display()));
+            }
+        };
+        Self::parse(&contents)
+            .wrap_err_with(|| format!("invalid config at {}", path.display()))
+            .suggestion("Check the config against the documented keys and types.")
+    }
+
+    pub fn parse(contents: &str) -> Result<Self> {
+        let config = toml::from_str(contents)?;
+        Ok(config)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::banner::BannerFont;
+    use crate::config::Config;
+
+    #[test]
+    fn parse_empty_config() -> color_eyre::Result<()> {
+        let config = Config::parse("")?;
+
+        assert!(!config.banner.enabled);
+        assert_eq!(config.banner.font, BannerFont::Block);
+
+        Ok(())
+    }
+
+    #[test]
+    fn parse_banner_section() -> color_eyre::Result<()> {
+        let config = Config::parse(
+            r#"
+            [banner]
+            enabled = true
+            text = "bingus"
+            font = "slim"
+            "#,
+        )?;
+
+        assert!(config.banner.enabled);
+        assert_eq!(config.banner.text.as_deref(), Some("bingus"));
+        assert_eq!(config.banner.font, BannerFont::Slim);
+
+        Ok(())
+    }
+
+    #[test]
+    fn reject_unknown_font() {
+        assert!(Config::parse("banner.font = \"comic-sans\"").is_err());
+    }
+}
@@ -0,0 +1,54 @@
+use std::process::Stdio;
+
+use color_eyre::{Result, eyre::Context, eyre::eyre};
+use tokio::process::Command;
+use tracing::instrument;
+
+use crate::Str;
+
+/// The keymap after `current` in `keymaps`, wrapping around, or the first one if `current`
+/// isn't in the list.
+pub fn next<'a>(keymaps: &'a [Str], current: Option<&str>) -> Option<&'a str> {
+    let position = keymaps.iter().position(|keymap| Some(&**keymap) == current);
+    let next = position.map_or(0, |position| (position + 1) % keymaps.len());
+    keymaps.get(next).map(|keymap| &**keymap)
+}
+
+/// Loads `keymap` into the console with loadkeys(1). This changes it for every virtual
+/// console, and the session started after logging in keeps it.
+#[instrument(err)]
+pub async fn load(keymap: &str) -> Result<()> {
+    let output = Command::new("loadkeys")
+        .arg("--quiet")
+        .arg(keymap)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .output()
+        .await
+        .wrap_err("failed to run loadkeys")?;
+    match output.status.success() {
+        true => Ok(()),
+        false => {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            Err(eyre!("loadkeys {keymap}: {}", stderr.trim()))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Str;
+    use crate::console_keymap::next;
+
+    #[test]
+    fn cycles_through_the_list() {
+        let keymaps: Vec<Str> = vec!["us".into(), "de".into(), "fr".into()];
+
+        assert_eq!(next(&keymaps, Some("us")), Some("de"));
+        assert_eq!(next(&keymaps, Some("fr")), Some("us"));
+        assert_eq!(next(&keymaps, Some("dvorak")), Some("us"));
+        assert_eq!(next(&keymaps, None), Some("us"));
+        assert_eq!(next(&[], Some("us")), None);
+    }
+}
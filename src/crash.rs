@@ -0,0 +1,82 @@
+use std::fs::OpenOptions;
+use std::io::{self, Write};
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::{Path, PathBuf};
+
+/// Where crash reports go, the first of these the greeter user can write to, and after them the
+/// temporary directory.
+pub const DIRS: [&str; 2] = ["/var/log", "/var/cache/impolite"];
+
+/// Only for whoever the greeter runs as: a crash can happen halfway through a login.
+const FILE_MODE: u32 = 0o600;
+
+/// [`DIRS`], then the temporary directory.
+pub fn dirs() -> Vec<PathBuf> {
+    DIRS.iter()
+        .map(PathBuf::from)
+        .chain([std::env::temp_dir()])
+        .collect()
+}
+
+/// Writes `report` to a new file named after the time of the crash, without the colors it was
+/// printed with, in the first of `dirs` that works, and returns where it went.
+pub fn write(dirs: &[PathBuf], report: &str) -> io::Result<PathBuf> {
+    let name = format!(
+        "impolite-crash-{}.log",
+        chrono::Local::now().format("%Y%m%d-%H%M%S")
+    );
+    let report = strip_ansi(report);
+    let mut last_err = io::ErrorKind::NotFound.into();
+    for dir in dirs {
+        let path = dir.join(&name);
+        match write_new(&path, &report) {
+            Ok(()) => return Ok(path),
+            Err(err) => last_err = err,
+        }
+    }
+    Err(last_err)
+}
+
+fn write_new(path: &Path, report: &str) -> io::Result<()> {
+    let mut file = OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .mode(FILE_MODE)
+        .open(path)?;
+    file.write_all(report.as_bytes())
+}
+
+/// `text` without its CSI escapes, which color-eyre colors the report with.
+fn strip_ansi(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\x1b' {
+            out.push(c);
+            continue;
+        }
+        if chars.next() == Some('[') {
+            // Parameters and intermediates up to the final byte, a letter or one of @[\]^_`{|}~.
+            for c in chars.by_ref() {
+                if ('@'..='~').contains(&c) {
+                    break;
+                }
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::crash::strip_ansi;
+
+    #[test]
+    fn strips_colors() {
+        assert_eq!(
+            strip_ansi("\x1b[31mThe application panicked\x1b[0m: \x1b[1;33moops\x1b[0m"),
+            "The application panicked: oops"
+        );
+        assert_eq!(strip_ansi("no colors"), "no colors");
+    }
+}
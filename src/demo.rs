@@ -0,0 +1,127 @@
+use std::time::Duration;
+
+use color_eyre::Result;
+use flume::{Receiver, Sender};
+use freedesktop_desktop_entry::DesktopEntry;
+
+use crate::greetd::{AuthMessageType, ErrorType, Request, Response};
+use crate::{Msg, queue};
+
+/// The password the demo backend accepts, for any username.
+pub const PASSWORD: &str = "demo";
+
+/// This account's password is right, but PAM turns it away afterwards, to show that error.
+pub const EXPIRED_USER: &str = "expired";
+
+/// Shown in place of the machine's own name with `--demo`.
+pub const HOSTNAME: &str = "impolite-demo";
+
+/// The sessions `--demo` offers, as an id, name and command.
+const SESSIONS: [(&str, &str, &str); 3] = [
+    ("sway", "Sway", "sway"),
+    ("hyprland", "Hyprland", "Hyprland"),
+    ("gnome", "GNOME", "gnome-session"),
+];
+
+/// How long the demo backend takes to answer, so the authenticating state is visible.
+const LATENCY: Duration = Duration::from_millis(600);
+
+/// Answers requests the way greetd would for a single password prompt, without touching PAM.
+pub async fn run(req_rx: Receiver<Request>, tx: Sender<Msg>) -> Result<()> {
+    let mut username = None;
+    while let Ok(req) = req_rx.recv_async().await {
+        tokio::time::sleep(LATENCY).await;
+        let res = answer(&mut username, req);
+        queue::send(&tx, Msg::GreetdRes(res)).await?;
+    }
+    Ok(())
+}
+
+/// What greetd says to `req`, in the words PAM fails with. `username` is who's logging in.
+pub fn answer(username: &mut Option<String>, req: Request) -> Response {
+    match req {
+        Request::CreateSession { username: name } => {
+            *username = Some(name.to_string());
+            Response::AuthMessage {
+                auth_message_type: AuthMessageType::Secret,
+                auth_message: "Password: ".into(),
+            }
+        }
+        Request::PostAuthMessageResponse { response } if response.as_deref() != Some(PASSWORD) => {
+            Response::Error {
+                error_type: ErrorType::AuthError,
+                description: "pam_authenticate: AUTH_ERR".into(),
+            }
+        }
+        Request::PostAuthMessageResponse { .. } if username.as_deref() == Some(EXPIRED_USER) => {
+            Response::Error {
+                error_type: ErrorType::Error,
+                description: "pam_acct_mgmt: ACCT_EXPIRED".into(),
+            }
+        }
+        Request::PostAuthMessageResponse { .. } => Response::Success,
+        Request::CancelSession => {
+            *username = None;
+            Response::Success
+        }
+        Request::StartSession { .. } => Response::Success,
+    }
+}
+
+/// Made-up Wayland sessions, so the picker looks the same on any machine.
+pub fn sessions() -> Vec<DesktopEntry> {
+    SESSIONS
+        .iter()
+        .filter_map(|(id, name, exec)| {
+            let path = format!("/usr/share/wayland-sessions/{id}.desktop");
+            let contents = format!("[Desktop Entry]\nType=Application\nName={name}\nExec={exec}\n");
+            DesktopEntry::from_str(&path, &contents, None::<&[&str]>).ok()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::demo::{EXPIRED_USER, PASSWORD, answer, sessions};
+    use crate::greetd::{ErrorType, Request, Response};
+
+    fn log_in(username: &str, password: &str) -> Response {
+        let mut state = None;
+        answer(
+            &mut state,
+            Request::CreateSession {
+                username: username.into(),
+            },
+        );
+        answer(
+            &mut state,
+            Request::PostAuthMessageResponse {
+                response: Some(password.into()),
+            },
+        )
+    }
+
+    #[test]
+    fn accepts_only_the_demo_password() {
+        assert!(matches!(log_in("alice", PASSWORD), Response::Success));
+        assert!(matches!(
+            log_in("alice", "hunter2"),
+            Response::Error {
+                error_type: ErrorType::AuthError,
+                ..
+            }
+        ));
+        assert!(matches!(
+            log_in(EXPIRED_USER, PASSWORD),
+            Response::Error {
+                error_type: ErrorType::Error,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn offers_every_session() {
+        assert_eq!(sessions().len(), 3);
+    }
+}
@@ -0,0 +1,56 @@
+use std::ffi::CString;
+use std::mem::MaybeUninit;
+use std::os::unix::ffi::OsStrExt;
+use std::path::Path;
+
+/// How full a filesystem is, in bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Usage {
+    pub used: u64,
+    /// What's left for unprivileged users, without the blocks reserved for root.
+    pub available: u64,
+}
+
+impl Usage {
+    /// Percentage in use, rounded up and leaving out the reserved blocks like df(1) does.
+    pub fn used_percent(&self) -> u8 {
+        let total = self.used + self.available;
+        match total {
+            0 => 0,
+            total => (self.used * 100).div_ceil(total).min(100) as u8,
+        }
+    }
+}
+
+/// Usage of the filesystem `path` is on, from statvfs(3).
+// The statvfs fields are narrower than u64 on some targets.
+#[allow(clippy::unnecessary_cast)]
+pub fn read(path: &Path) -> Option<Usage> {
+    let path = CString::new(path.as_os_str().as_bytes()).ok()?;
+    let mut stat = MaybeUninit::<libc::statvfs>::uninit();
+    // SAFETY: `path` is NUL terminated and statvfs fills in `stat` when it returns 0.
+    if unsafe { libc::statvfs(path.as_ptr(), stat.as_mut_ptr()) } != 0 {
+        return None;
+    }
+    let stat = unsafe { stat.assume_init() };
+    let block = stat.f_frsize as u64;
+    Some(Usage {
+        used: (stat.f_blocks as u64).saturating_sub(stat.f_bfree as u64) * block,
+        available: stat.f_bavail as u64 * block,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::disk::Usage;
+
+    #[test]
+    fn used_percent_rounds_up() {
+        let usage = |used, available| Usage { used, available }.used_percent();
+
+        assert_eq!(usage(0, 0), 0);
+        assert_eq!(usage(50, 50), 50);
+        assert_eq!(usage(901, 99), 91);
+        assert_eq!(usage(100, 0), 100);
+    }
+}
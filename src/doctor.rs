@@ -0,0 +1,326 @@
+use std::ffi::CString;
+use std::fmt::Write;
+use std::io::IsTerminal;
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::fs::FileTypeExt;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use ratatui::crossterm::terminal;
+
+use crate::config::Config;
+use crate::layout;
+use crate::storage;
+use crate::term::ColorDepth;
+
+/// Where display managers look for sessions to start.
+const SESSION_DIRS: [&str; 2] = ["/usr/share/wayland-sessions", "/usr/share/xsessions"];
+
+/// How long connecting to greetd may take before it counts as not answering.
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    Pass,
+    Warn,
+    Fail,
+}
+
+/// The outcome of one check, with how to fix it when it didn't pass.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Check {
+    pub what: &'static str,
+    pub status: Status,
+    pub detail: String,
+    pub fix: Option<String>,
+}
+
+impl Check {
+    fn pass(what: &'static str, detail: impl Into<String>) -> Self {
+        Self {
+            what,
+            status: Status::Pass,
+            detail: detail.into(),
+            fix: None,
+        }
+    }
+
+    fn warn(what: &'static str, detail: impl Into<String>, fix: impl Into<String>) -> Self {
+        Self {
+            what,
+            status: Status::Warn,
+            detail: detail.into(),
+            fix: Some(fix.into()),
+        }
+    }
+
+    fn fail(what: &'static str, detail: impl Into<String>, fix: impl Into<String>) -> Self {
+        Self {
+            what,
+            status: Status::Fail,
+            detail: detail.into(),
+            fix: Some(fix.into()),
+        }
+    }
+}
+
+/// Looks at everything the greeter needs from the machine it runs on, with the config at
+/// `config_path`.
+pub async fn run(config_path: Option<&Path>) -> Vec<Check> {
+    let mut checks = Vec::new();
+    let config = match Config::load(config_path) {
+        Ok(config) => {
+            checks.push(Check::pass("config", "loaded"));
+            Some(config)
+        }
+        Err(err) => {
+            let detail = err
+                .chain()
+                .map(|err| err.to_string())
+                .collect::<Vec<_>>()
+                .join(": ");
+            checks.push(Check::fail(
+                "config",
+                detail,
+                "Check it against the documented keys and types.",
+            ));
+            None
+        }
+    };
+    match std::env::var_os("GREETD_SOCK") {
+        None => checks.push(Check::fail(
+            "greetd socket",
+            "GREETD_SOCK isn't set",
+            "Run impolite as the greeter command in greetd's config.toml, which sets it.",
+        )),
+        Some(path) => {
+            let path = PathBuf::from(path);
+            let socket = socket(&path);
+            let usable = socket.status == Status::Pass;
+            checks.push(socket);
+            if usable {
+                checks.push(connect(&path).await);
+            }
+        }
+    }
+    checks.push(sessions());
+    checks.push(terminal());
+    let cache = match &config {
+        Some(config) => config.remember.path.clone(),
+        None => Config::default().remember.path,
+    };
+    if let Some(dir) = cache.parent() {
+        checks.push(cache_dir(dir));
+    }
+    checks
+}
+
+fn socket(path: &Path) -> Check {
+    const WHAT: &str = "greetd socket";
+    let meta = match path.metadata() {
+        Ok(meta) => meta,
+        Err(err) => {
+            return Check::fail(
+                WHAT,
+                format!("{}: {err}", path.display()),
+                "Check that greetd is running, with `systemctl status greetd`.",
+            );
+        }
+    };
+    if !meta.file_type().is_socket() {
+        return Check::fail(
+            WHAT,
+            format!("{} isn't a socket", path.display()),
+            "GREETD_SOCK should point at the socket greetd made for this greeter.",
+        );
+    }
+    if !writable(path) {
+        return Check::fail(
+            WHAT,
+            format!("{} can't be written to", path.display()),
+            "Run impolite as the user set in greetd's [default_session].",
+        );
+    }
+    Check::pass(WHAT, path.display().to_string())
+}
+
+async fn connect(path: &Path) -> Check {
+    const WHAT: &str = "greetd connection";
+    match tokio::time::timeout(CONNECT_TIMEOUT, tokio::net::UnixStream::connect(path)).await {
+        Ok(Ok(_)) => Check::pass(WHAT, "connected"),
+        Ok(Err(err)) => Check::fail(
+            WHAT,
+            err.to_string(),
+            "Restart greetd, it may have stopped listening.",
+        ),
+        Err(_) => Check::fail(
+            WHAT,
+            "timed out",
+            "greetd is there but busy, another greeter may be connected.",
+        ),
+    }
+}
+
+fn sessions() -> Check {
+    const WHAT: &str = "sessions";
+    let counts = SESSION_DIRS
+        .iter()
+        .map(|dir| {
+            let count = std::fs::read_dir(dir)
+                .map(|entries| {
+                    entries
+                        .flatten()
+                        .filter(|entry| {
+                            entry.path().extension().is_some_and(|ext| ext == "desktop")
+                        })
+                        .count()
+                })
+                .unwrap_or(0);
+            (count, dir)
+        })
+        .collect::<Vec<_>>();
+    let detail = counts
+        .iter()
+        .map(|(count, dir)| format!("{count} in {dir}"))
+        .collect::<Vec<_>>()
+        .join(", ");
+    match counts.iter().any(|(count, _)| *count > 0) {
+        true => Check::pass(WHAT, detail),
+        false => Check::warn(
+            WHAT,
+            detail,
+            "Install a desktop or window manager, or log in to a shell from the session picker.",
+        ),
+    }
+}
+
+fn terminal() -> Check {
+    const WHAT: &str = "terminal";
+    if !std::io::stdout().is_terminal() {
+        return Check::warn(
+            WHAT,
+            "stdout isn't a terminal",
+            "Run doctor on the VT greetd uses to see what the greeter will get.",
+        );
+    }
+    let term = std::env::var("TERM").unwrap_or_default();
+    let depth = match ColorDepth::detect() {
+        ColorDepth::Ansi16 => "16 colors",
+        ColorDepth::Ansi256 => "256 colors",
+        ColorDepth::TrueColor => "true color",
+    };
+    let (cols, rows) = terminal::size().unwrap_or((0, 0));
+    let detail = format!("TERM={term}, {depth}, {cols}x{rows}");
+    if layout::too_small(cols, rows) {
+        return Check::fail(
+            WHAT,
+            detail,
+            format!(
+                "The greeter needs at least {}x{}, pick a smaller console font.",
+                layout::MIN_COLS,
+                layout::MIN_ROWS
+            ),
+        );
+    }
+    if term.is_empty() {
+        return Check::warn(
+            WHAT,
+            detail,
+            "Set TERM in greetd's command, like `env TERM=linux impolite`.",
+        );
+    }
+    Check::pass(WHAT, detail)
+}
+
+fn cache_dir(dir: &Path) -> Check {
+    const WHAT: &str = "cache directory";
+    if !dir.exists() {
+        let parent = dir.parent().unwrap_or(Path::new("/"));
+        return match writable(parent) {
+            true => Check::pass(WHAT, format!("{} will be created", dir.display())),
+            false => Check::warn(
+                WHAT,
+                format!("{} is missing and can't be created", dir.display()),
+                format!(
+                    "Create it for the greeter user: `install -d -m 700 -o greeter {}`.",
+                    dir.display()
+                ),
+            ),
+        };
+    }
+    match storage::check_dir(dir) {
+        Ok(()) => Check::pass(WHAT, dir.display().to_string()),
+        Err(err) => Check::warn(
+            WHAT,
+            err.to_string(),
+            format!(
+                "Remembered usernames aren't kept until it belongs to the greeter user with mode \
+                 700: `chown greeter {0} && chmod 700 {0}`.",
+                dir.display()
+            ),
+        ),
+    }
+}
+
+/// Whether this process may write to `path`, by access(2).
+fn writable(path: &Path) -> bool {
+    let Ok(path) = CString::new(path.as_os_str().as_bytes()) else {
+        return false;
+    };
+    // SAFETY: `path` is NUL terminated and outlives the call.
+    unsafe { libc::access(path.as_ptr(), libc::W_OK) == 0 }
+}
+
+/// One line per check, with the fix under each one that didn't pass.
+pub fn report(checks: &[Check]) -> String {
+    let width = checks
+        .iter()
+        .map(|check| check.what.len())
+        .max()
+        .unwrap_or(0);
+    let mut out = String::new();
+    for check in checks {
+        let mark = match check.status {
+            Status::Pass => "ok  ",
+            Status::Warn => "warn",
+            Status::Fail => "FAIL",
+        };
+        _ = writeln!(out, "[{mark}] {:width$}  {}", check.what, check.detail);
+        if let Some(fix) = &check.fix {
+            _ = writeln!(out, "       {:width$}  fix: {fix}", "");
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use crate::doctor::{Check, Status, report, socket};
+
+    #[test]
+    fn reports_fixes_for_failures() {
+        let checks = [
+            Check::pass("config", "loaded"),
+            Check::fail(
+                "greetd socket",
+                "GREETD_SOCK isn't set",
+                "Run it from greetd.",
+            ),
+        ];
+
+        assert_eq!(
+            report(&checks),
+            "[ok  ] config         loaded\n\
+             [FAIL] greetd socket  GREETD_SOCK isn't set\n\
+             \x20                     fix: Run it from greetd.\n"
+        );
+    }
+
+    #[test]
+    fn a_directory_is_not_a_socket() {
+        let check = socket(Path::new("/"));
+        assert_eq!(check.status, Status::Fail);
+    }
+}
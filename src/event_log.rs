@@ -0,0 +1,139 @@
+use chrono::{DateTime, Local};
+
+use crate::Str;
+use crate::greetd::{Request, Response};
+
+/// Something that happened between the greeter and greetd.
+#[derive(Debug, Clone)]
+pub struct Entry {
+    pub at: DateTime<Local>,
+    pub text: Str,
+}
+
+/// The latest protocol and auth events, oldest first.
+#[derive(Debug, Default)]
+pub struct EventLog {
+    entries: Vec<Entry>,
+    /// Entries pushed so far, including dropped ones.
+    pushed: u64,
+}
+
+impl EventLog {
+    /// Older entries are dropped past this many.
+    pub const MAX: usize = 200;
+
+    pub fn push(&mut self, text: impl Into<Str>) {
+        if self.entries.len() == Self::MAX {
+            self.entries.remove(0);
+        }
+        self.entries.push(Entry {
+            at: Local::now(),
+            text: text.into(),
+        });
+        self.pushed += 1;
+    }
+
+    pub fn pushed(&self) -> u64 {
+        self.pushed
+    }
+
+    /// The entries pushed after the first `pushed`, as many of them as are still kept.
+    pub fn since(&self, pushed: u64) -> &[Entry] {
+        let new = (self.pushed - pushed).min(self.entries.len() as u64) as usize;
+        &self.entries[self.entries.len() - new..]
+    }
+
+    pub fn request(&mut self, req: &Request) {
+        self.push(format!("→ {}", describe_request(req)));
+    }
+
+    pub fn response(&mut self, res: &Response) {
+        self.push(format!("← {}", describe_response(res)));
+    }
+
+    /// The entries of a `height` tall window, scrolled up from the newest by `scroll`.
+    pub fn tail(&self, scroll: usize, height: usize) -> &[Entry] {
+        let end = self.entries.len() - scroll.min(self.max_scroll(height));
+        &self.entries[end.saturating_sub(height)..end]
+    }
+
+    /// The largest useful scroll offset.
+    pub fn max_scroll(&self, height: usize) -> usize {
+        self.entries.len().saturating_sub(height)
+    }
+}
+
+/// `req` for the log, without anything the user typed into a secret prompt.
+///
+/// Auth responses are always redacted since only the prompt knew whether they were secret,
+/// and session environments are counted rather than shown.
+pub fn describe_request(req: &Request) -> String {
+    match req {
+        Request::CreateSession { username } => format!("create_session {username}"),
+        Request::PostAuthMessageResponse { response: Some(_) } => {
+            "post_auth_message_response <redacted>".to_string()
+        }
+        Request::PostAuthMessageResponse { response: None } => {
+            "post_auth_message_response (none)".to_string()
+        }
+        Request::StartSession { cmd, env } => {
+            format!("start_session {} ({} env vars)", cmd.join(" "), env.len())
+        }
+        Request::CancelSession => "cancel_session".to_string(),
+    }
+}
+
+pub fn describe_response(res: &Response) -> String {
+    match res {
+        Response::Success => "success".to_string(),
+        Response::Error {
+            error_type,
+            description,
+        } => format!("error ({error_type:?}): {description}"),
+        Response::AuthMessage {
+            auth_message_type,
+            auth_message,
+        } => format!("auth_message ({auth_message_type:?}): {auth_message}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::event_log::{Entry, EventLog, describe_request};
+    use crate::greetd::Request;
+
+    #[test]
+    fn redacts_secrets() {
+        let text = describe_request(&Request::PostAuthMessageResponse {
+            response: Some("hunter2".into()),
+        });
+        assert!(!text.contains("hunter2"));
+
+        let text = describe_request(&Request::StartSession {
+            cmd: ["sway".into()].into(),
+            env: ["TOKEN=hunter2".into()].into(),
+        });
+        assert!(!text.contains("hunter2"));
+    }
+
+    #[test]
+    fn tail_scrolls_from_newest() {
+        let mut log = EventLog::default();
+        for n in 0..EventLog::MAX + 5 {
+            log.push(n.to_string());
+        }
+        assert_eq!(log.tail(0, usize::MAX).len(), EventLog::MAX);
+
+        let text = |entries: &[Entry]| {
+            entries
+                .iter()
+                .map(|entry| entry.text.to_string())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(text(log.tail(0, 2)), ["203", "204"]);
+        assert_eq!(text(log.tail(1, 2)), ["202", "203"]);
+        assert_eq!(text(log.tail(500, 2)), ["5", "6"]);
+        assert_eq!(text(log.since(log.pushed() - 2)), ["203", "204"]);
+        assert_eq!(log.since(0).len(), EventLog::MAX);
+    }
+}
@@ -0,0 +1,153 @@
+use std::time::Duration;
+
+use color_eyre::{Result, eyre::Context, eyre::eyre};
+use flume::{Receiver, Sender};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::UnixStream;
+
+use crate::Msg;
+use crate::demo;
+use crate::greetd::{Request, Response};
+
+/// How long a test waits for an answer before it fails instead of hanging.
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+/// A greetd on the other end of a socket pair, answering like the demo backend, with the
+/// greeter's real relay on this end. Tests send requests the way the UI does and get back the
+/// messages the UI would.
+pub struct FakeGreetd {
+    req_tx: Sender<Request>,
+    rx: Receiver<Msg>,
+    seen: Receiver<Request>,
+}
+
+impl FakeGreetd {
+    /// Has to be called inside a Tokio runtime, which runs both ends.
+    pub fn start() -> Result<Self> {
+        let (greeter, greetd) = UnixStream::pair().wrap_err("failed to create a socket pair")?;
+        let (req_tx, req_rx) = flume::unbounded();
+        let (tx, rx) = flume::unbounded();
+        let (seen_tx, seen) = flume::unbounded();
+        tokio::spawn(crate::relay(Some(greeter), req_rx, tx));
+        tokio::spawn(serve(greetd, seen_tx));
+        Ok(Self { req_tx, rx, seen })
+    }
+
+    /// Sends `req` and waits for greetd's answer to reach the UI.
+    pub async fn send(&self, req: Request) -> Result<Response> {
+        self.req_tx.send_async(req).await?;
+        let msg = tokio::time::timeout(TIMEOUT, self.rx.recv_async())
+            .await
+            .wrap_err("greetd didn't answer")??;
+        match msg {
+            Msg::GreetdRes(res) => Ok(res),
+            _ => Err(eyre!("expected a response from greetd")),
+        }
+    }
+
+    /// The next request greetd read off the socket, as it arrived.
+    pub async fn received(&self) -> Result<Request> {
+        let req = tokio::time::timeout(TIMEOUT, self.seen.recv_async())
+            .await
+            .wrap_err("greetd got nothing")??;
+        Ok(req)
+    }
+}
+
+/// Reads requests framed the way greetd's protocol has them, a native-endian length and then
+/// JSON, and answers each one.
+async fn serve(mut stream: UnixStream, seen: Sender<Request>) -> Result<()> {
+    let mut username = None;
+    loop {
+        let mut len = [0u8; 4];
+        stream.read_exact(&mut len).await?;
+        let mut buf = vec![0u8; u32::from_ne_bytes(len) as usize];
+        stream.read_exact(&mut buf).await?;
+        let req: Request = serde_json::from_slice(&buf)?;
+        seen.send_async(req.clone()).await?;
+        let json = serde_json::to_vec(&demo::answer(&mut username, req))?;
+        stream.write_all(&(json.len() as u32).to_ne_bytes()).await?;
+        stream.write_all(&json).await?;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::demo;
+    use crate::fake_greetd::FakeGreetd;
+    use crate::greetd::{AuthMessageType, ErrorType, Request, Response};
+
+    #[tokio::test]
+    async fn logs_in_end_to_end() -> color_eyre::Result<()> {
+        let greetd = FakeGreetd::start()?;
+
+        let res = greetd
+            .send(Request::CreateSession {
+                username: "alice".into(),
+            })
+            .await?;
+        assert!(matches!(
+            res,
+            Response::AuthMessage {
+                auth_message_type: AuthMessageType::Secret,
+                ..
+            }
+        ));
+
+        let res = greetd
+            .send(Request::PostAuthMessageResponse {
+                response: Some(demo::PASSWORD.into()),
+            })
+            .await?;
+        assert!(matches!(res, Response::Success));
+
+        let res = greetd
+            .send(Request::StartSession {
+                cmd: ["sway".into()].into(),
+                env: [].into(),
+            })
+            .await?;
+        assert!(matches!(res, Response::Success));
+
+        assert!(matches!(
+            greetd.received().await?,
+            Request::CreateSession { username } if &*username == "alice"
+        ));
+        assert!(matches!(
+            greetd.received().await?,
+            Request::PostAuthMessageResponse { response: Some(password) }
+                if &*password == demo::PASSWORD
+        ));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn wrong_password_then_cancel() -> color_eyre::Result<()> {
+        let greetd = FakeGreetd::start()?;
+
+        greetd
+            .send(Request::CreateSession {
+                username: "alice".into(),
+            })
+            .await?;
+        let res = greetd
+            .send(Request::PostAuthMessageResponse {
+                response: Some("hunter2".into()),
+            })
+            .await?;
+        assert!(matches!(
+            res,
+            Response::Error {
+                error_type: ErrorType::AuthError,
+                ..
+            }
+        ));
+        assert!(matches!(
+            greetd.send(Request::CancelSession).await?,
+            Response::Success
+        ));
+
+        Ok(())
+    }
+}
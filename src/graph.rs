@@ -0,0 +1,96 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+use crate::greetd::{AuthMessageType, ErrorType, Response};
+use crate::{FormEffect, FormState};
+
+/// One of each state, with made-up contents where it has any.
+fn states() -> [FormState; 4] {
+    [
+        FormState::Idle,
+        FormState::CreatedSession,
+        FormState::LoginFailed(ErrorType::AuthError, "Authentication failed".into()),
+        FormState::PickingDesktop,
+    ]
+}
+
+/// One of each kind of response greetd sends, named for the graph.
+fn responses() -> [(&'static str, Response); 6] {
+    let message = |auth_message_type| Response::AuthMessage {
+        auth_message_type,
+        auth_message: "".into(),
+    };
+    [
+        ("success", Response::Success),
+        (
+            "error",
+            Response::Error {
+                error_type: ErrorType::AuthError,
+                description: "".into(),
+            },
+        ),
+        ("visible prompt", message(AuthMessageType::Visible)),
+        ("secret prompt", message(AuthMessageType::Secret)),
+        ("info", message(AuthMessageType::Info)),
+        ("error message", message(AuthMessageType::Error)),
+    ]
+}
+
+fn effect_name(effect: &FormEffect) -> Option<&'static str> {
+    match effect {
+        FormEffect::None => None,
+        FormEffect::Answer => Some("answer"),
+        FormEffect::Acknowledge => Some("acknowledge"),
+        FormEffect::FocusDesktopPicker => Some("focus desktop picker"),
+    }
+}
+
+/// How the login form moves between states on greetd's responses, in Graphviz's DOT language.
+///
+/// Made by putting every kind of response to [`FormState::update`] in every state, so it shows
+/// what the code does rather than what someone wrote down. Responses that lead to the same
+/// state with the same effect share an edge.
+pub fn form_state() -> String {
+    let mut edges = BTreeMap::<(&str, &str, Option<&str>), Vec<&str>>::new();
+    for state in states() {
+        for (response, res) in responses() {
+            let (next, effect) = state.clone().update(res);
+            edges
+                .entry((state.name(), next.name(), effect_name(&effect)))
+                .or_default()
+                .push(response);
+        }
+    }
+    let mut dot = String::from("digraph form_state {\n    rankdir=LR;\n");
+    for state in states() {
+        _ = writeln!(dot, "    {};", state.name());
+    }
+    for ((from, to, effect), responses) in edges {
+        let mut label = responses.join("\\n");
+        if let Some(effect) = effect {
+            _ = write!(label, "\\n/ {effect}");
+        }
+        _ = writeln!(dot, "    {from} -> {to} [label=\"{label}\"];");
+    }
+    dot.push_str("}\n");
+    dot
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::graph::form_state;
+
+    #[test]
+    fn follows_the_real_transitions() {
+        let dot = form_state();
+
+        assert!(dot.starts_with("digraph form_state {"));
+        assert!(dot.contains(
+            "created_session -> picking_desktop [label=\"success\\n/ focus desktop picker\"];"
+        ));
+        assert!(dot.contains("created_session -> login_failed [label=\"error\"];"));
+        assert!(dot.contains(
+            "created_session -> created_session [label=\"visible prompt\\nsecret prompt\\n/ answer\"];"
+        ));
+    }
+}
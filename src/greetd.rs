@@ -1,3 +1,5 @@
+use std::fmt;
+use std::io;
 use std::{path::PathBuf, sync::Arc};
 
 use color_eyre::{Result, Section, eyre::Context};
@@ -14,6 +16,7 @@ use tokio::net::UnixStream;
 use tracing::instrument;
 
 use crate::Str;
+use crate::secret::{self, Secret};
 
 pub fn get_desktops() -> Vec<DesktopEntry> {
     let locales = get_languages_from_env();
@@ -21,15 +24,38 @@ pub fn get_desktops() -> Vec<DesktopEntry> {
     desktop_entries(&locales)
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Clone)]
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum Request {
     CreateSession { username: Str },
-    PostAuthMessageResponse { response: Option<Str> },
+    PostAuthMessageResponse { response: Option<Secret> },
     StartSession { cmd: Arc<[Str]>, env: Arc<[Str]> },
     CancelSession,
 }
 
+/// Written out so an answer to PAM, usually the password, never shows up in a log or on screen,
+/// and neither does the session environment, which can hold tokens.
+impl fmt::Debug for Request {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Request::CreateSession { username } => f
+                .debug_struct("CreateSession")
+                .field("username", username)
+                .finish(),
+            Request::PostAuthMessageResponse { response } => f
+                .debug_struct("PostAuthMessageResponse")
+                .field("response", response)
+                .finish(),
+            Request::StartSession { cmd, env } => f
+                .debug_struct("StartSession")
+                .field("cmd", cmd)
+                .field("env", &format_args!("{} vars", env.len()))
+                .finish(),
+            Request::CancelSession => f.write_str("CancelSession"),
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum Response {
@@ -106,23 +132,37 @@ impl<W> GreetdWrite for W
 where
     W: AsyncWrite + Unpin,
 {
+    /// The length prefix and JSON go out in one write from a buffer sized up front, so it's
+    /// never outgrown and copied, and is wiped afterwards since it has the password in it.
     #[instrument(skip_all, err)]
     async fn greetd_write(&mut self, msg: Request) -> Result<()> {
-        let msg = serde_json::to_string(&msg).wrap_err("failed to serialize msg")?;
-        {
-            let msg = msg.as_bytes();
-            let len = msg.len();
-            self.write_all(&u32::to_ne_bytes(len as u32))
-                .await
-                .wrap_err("failed to write length prefix over greetd socket")?;
-            self.write_all(msg)
-                .await
-                .wrap_err("failed to write over greetd socket")?;
-        }
+        let mut counted = Counted(0);
+        serde_json::to_writer(&mut counted, &msg).wrap_err("failed to serialize msg")?;
+        let mut frame = Vec::with_capacity(4 + counted.0);
+        frame.extend_from_slice(&u32::to_ne_bytes(counted.0 as u32));
+        serde_json::to_writer(&mut frame, &msg).wrap_err("failed to serialize msg")?;
+        let written = self.write_all(&frame).await;
+        secret::wipe_bytes(&mut frame);
+        written.wrap_err("failed to write over greetd socket")?;
         self.flush()
             .await
             .wrap_err("failed to flush greetd socket")?;
-        tracing::info!("WROTE {msg}");
+        // Not the JSON, which has the password in it.
+        tracing::info!("WROTE {msg:?}");
+        Ok(())
+    }
+}
+
+/// Counts what's written to it, to size a buffer before writing anything secret into it.
+struct Counted(usize);
+
+impl io::Write for Counted {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0 += buf.len();
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
         Ok(())
     }
 }
@@ -168,6 +208,23 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn debug_redacts_secrets() {
+        let msg = Request::PostAuthMessageResponse {
+            response: Some("hunter2".into()),
+        };
+        assert_eq!(
+            format!("{msg:?}"),
+            "PostAuthMessageResponse { response: Some(<redacted>) }"
+        );
+
+        let msg = Request::StartSession {
+            cmd: ["sway".into()].into(),
+            env: ["TOKEN=hunter2".into()].into(),
+        };
+        assert!(!format!("{msg:?}").contains("hunter2"));
+    }
+
     #[test]
     fn serialize_auth_message_response() -> color_eyre::Result<()> {
         let msg = Response::AuthMessage {
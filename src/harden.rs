@@ -0,0 +1,66 @@
+use std::io;
+
+/// Keeps the password out of swap and core dumps: locks the greeter's memory into RAM, turns
+/// core dumps off and stops other processes of the same user from attaching to it.
+///
+/// A step that fails is logged and the others still go ahead, since a partly hardened greeter
+/// beats one that doesn't start.
+pub fn harden() {
+    warn("lock memory", lock_memory());
+    warn("disable core dumps", disable_core_dumps());
+    warn("make the process undumpable", undumpable());
+}
+
+fn warn(what: &str, result: io::Result<()>) {
+    if let Err(err) = result {
+        tracing::warn!("failed to {what}, the password could end up on disk: {err}");
+    }
+}
+
+fn check(ret: libc::c_int) -> io::Result<()> {
+    match ret {
+        0 => Ok(()),
+        _ => Err(io::Error::last_os_error()),
+    }
+}
+
+/// Locks everything mapped now and later with mlockall(2).
+///
+/// Once `MCL_FUTURE` is on, any allocation past `RLIMIT_MEMLOCK` fails, so this only goes ahead
+/// when the limit can't be hit: running as root, or with the limit raised to unlimited.
+fn lock_memory() -> io::Result<()> {
+    let mut limit = libc::rlimit {
+        rlim_cur: 0,
+        rlim_max: 0,
+    };
+    // SAFETY: getrlimit fills in `limit`, which outlives the call.
+    check(unsafe { libc::getrlimit(libc::RLIMIT_MEMLOCK, &mut limit) })?;
+    limit.rlim_cur = limit.rlim_max;
+    // SAFETY: as above, and raising the soft limit up to the hard one is always allowed.
+    check(unsafe { libc::setrlimit(libc::RLIMIT_MEMLOCK, &limit) })?;
+    // SAFETY: geteuid can't fail.
+    let root = unsafe { libc::geteuid() } == 0;
+    if !root && limit.rlim_cur != libc::RLIM_INFINITY {
+        return Err(io::Error::other(format!(
+            "RLIMIT_MEMLOCK is {} bytes, run as root or raise it to unlimited",
+            limit.rlim_cur
+        )));
+    }
+    // SAFETY: takes no pointers.
+    check(unsafe { libc::mlockall(libc::MCL_CURRENT | libc::MCL_FUTURE) })
+}
+
+fn disable_core_dumps() -> io::Result<()> {
+    let limit = libc::rlimit {
+        rlim_cur: 0,
+        rlim_max: 0,
+    };
+    // SAFETY: `limit` outlives the call.
+    check(unsafe { libc::setrlimit(libc::RLIMIT_CORE, &limit) })
+}
+
+/// Also keeps ptrace and /proc/self/mem away from anyone but root.
+fn undumpable() -> io::Result<()> {
+    // SAFETY: PR_SET_DUMPABLE takes no pointers.
+    check(unsafe { libc::prctl(libc::PR_SET_DUMPABLE, 0) })
+}
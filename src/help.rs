@@ -0,0 +1,132 @@
+use crate::keymap::{Action, KeyMap, Scope};
+
+/// A line of the help overlay: an action and the keys bound to it.
+pub struct Binding {
+    pub keys: String,
+    pub action: &'static str,
+}
+
+/// The readline keys of the text fields, which aren't in the keymap.
+const EDITING: &[(&str, &str)] = &[
+    ("Ctrl+A / Ctrl+E", "start or end of the field"),
+    ("Alt+B / Alt+F", "previous or next word"),
+    ("Ctrl+W / Alt+D", "delete the word before or after"),
+    ("Ctrl+U", "delete up to the cursor"),
+];
+
+/// The first key of each of `actions` with a short label, for the hints in the footer.
+/// Actions the config left without keys are left out.
+pub fn hints(keys: &KeyMap, actions: &[(Action, &'static str)]) -> Vec<Binding> {
+    actions
+        .iter()
+        .filter_map(|&(action, label)| {
+            let chord = keys.keys(action).first()?;
+            Some(Binding {
+                keys: chord.to_string(),
+                action: label,
+            })
+        })
+        .collect()
+}
+
+/// [`hints`] on one line, like `Alt+↑ older • Alt+L hide`, for the dim hints under panels.
+pub fn footer(keys: &KeyMap, actions: &[(Action, &'static str)]) -> String {
+    hints(keys, actions)
+        .iter()
+        .map(|hint| format!("{} {}", hint.keys, hint.action))
+        .collect::<Vec<_>>()
+        .join(" • ")
+}
+
+/// The bindings in `keys` grouped by scope, leaving out the actions that aren't in use and
+/// those the config left without keys.
+pub fn sections(keys: &KeyMap, in_use: impl Fn(Action) -> bool) -> Vec<(Scope, Vec<Binding>)> {
+    let mut sections: Vec<(Scope, Vec<Binding>)> = Vec::new();
+    let actions = Action::ALL
+        .iter()
+        .filter(|action| in_use(**action) && !keys.keys(**action).is_empty());
+    for &action in actions {
+        let binding = Binding {
+            keys: keys.describe(action),
+            action: action.description(),
+        };
+        match sections.last_mut() {
+            Some((scope, bindings)) if *scope == action.scope() => bindings.push(binding),
+            _ => sections.push((action.scope(), vec![binding])),
+        }
+    }
+    if let Some((_, bindings)) = sections.iter_mut().find(|(scope, _)| *scope == Scope::Form) {
+        bindings.extend(EDITING.iter().map(|(keys, action)| Binding {
+            keys: keys.to_string(),
+            action,
+        }));
+    }
+    sections
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::help::{footer, hints, sections};
+    use crate::keymap::{Action, KeyMap, Scope};
+
+    #[test]
+    fn groups_bindings_in_use() -> color_eyre::Result<()> {
+        let keys: KeyMap = toml::from_str("debug = []")?;
+        let sections = sections(&keys, |action| {
+            !matches!(action.scope(), Scope::Preview | Scope::UserMenu) && action != Action::Quit
+        });
+        let scopes = sections.iter().map(|(scope, _)| *scope).collect::<Vec<_>>();
+
+        assert_eq!(
+            scopes,
+            [
+                Scope::Global,
+                Scope::Form,
+                Scope::SessionPicker,
+                Scope::PowerMenu,
+                Scope::Keyboard,
+                Scope::Palette,
+                Scope::Dialog
+            ]
+        );
+        assert!(sections.iter().all(|(_, bindings)| !bindings.is_empty()));
+        assert_eq!(sections[0].1[0].keys, "F1 / ?");
+        assert!(
+            sections[0]
+                .1
+                .iter()
+                .all(|binding| binding.action != "show or hide debug info"
+                    && binding.action != "quit")
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn hints_use_the_first_key() -> color_eyre::Result<()> {
+        let keys: KeyMap = toml::from_str("palette = []")?;
+        let hints = hints(
+            &keys,
+            &[
+                (Action::Submit, "log in"),
+                (Action::Palette, "commands"),
+                (Action::Help, "help"),
+            ],
+        );
+        let hints = hints
+            .iter()
+            .map(|hint| (hint.keys.as_str(), hint.action))
+            .collect::<Vec<_>>();
+
+        assert_eq!(hints, [("Enter", "log in"), ("F1", "help")]);
+        assert_eq!(
+            footer(
+                &keys,
+                &[(Action::ScrollUp, "older"), (Action::ToggleLog, "hide")]
+            ),
+            "Alt+↑ older • Alt+L hide"
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,141 @@
+use std::path::Path;
+
+use color_eyre::Result;
+use tracing::instrument;
+
+use crate::{Str, storage};
+
+/// Usernames that logged in before, browsed from the username field like shell history.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct History {
+    /// Newest first.
+    entries: Vec<Str>,
+    /// The entry in the field, `None` while showing what was typed.
+    position: Option<usize>,
+    /// What was typed before browsing, to come back to.
+    draft: String,
+}
+
+impl History {
+    pub fn new(entries: Vec<Str>) -> Self {
+        Self {
+            entries,
+            ..Default::default()
+        }
+    }
+
+    /// Whether an entry is in the field instead of what was typed.
+    pub fn is_browsing(&self) -> bool {
+        self.position.is_some()
+    }
+
+    /// Goes back to typing, after the field was edited.
+    pub fn stop_browsing(&mut self) {
+        self.position = None;
+        self.draft.clear();
+    }
+
+    /// The next older username, given what's in the field now. `None` past the oldest one.
+    pub fn older(&self, current: &str) -> Option<(History, String)> {
+        let position = self.position.map_or(0, |position| position + 1);
+        let entry = self.entries.get(position)?.to_string();
+        let draft = match self.position {
+            Some(_) => self.draft.clone(),
+            None => current.to_string(),
+        };
+        let history = History {
+            entries: self.entries.clone(),
+            position: Some(position),
+            draft,
+        };
+        Some((history, entry))
+    }
+
+    /// The next newer username, or what was typed after the newest one. `None` when not
+    /// browsing.
+    pub fn newer(&self) -> Option<(History, String)> {
+        let mut history = self.clone();
+        let value = match self.position? {
+            0 => {
+                history.position = None;
+                std::mem::take(&mut history.draft)
+            }
+            position => {
+                history.position = Some(position - 1);
+                self.entries[position - 1].to_string()
+            }
+        };
+        Some((history, value))
+    }
+}
+
+/// `entries` with `username` moved to the front, keeping at most `max`.
+pub fn recorded(entries: &[Str], username: &str, max: usize) -> Vec<Str> {
+    std::iter::once(Str::from(username))
+        .chain(
+            entries
+                .iter()
+                .filter(|&entry| &**entry != username)
+                .cloned(),
+        )
+        .take(max)
+        .collect()
+}
+
+/// The remembered usernames, none if the file isn't there yet.
+#[instrument(err)]
+pub fn load(path: &Path) -> Result<Vec<Str>> {
+    let entries = storage::read(path)?
+        .unwrap_or_default()
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(Str::from)
+        .collect();
+    Ok(entries)
+}
+
+#[instrument(err, skip(entries))]
+pub fn save(path: &Path, entries: &[Str]) -> Result<()> {
+    let mut contents = entries.join("\n");
+    contents.push('\n');
+    storage::write(path, contents.as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Str;
+    use crate::history::{History, recorded};
+
+    #[test]
+    fn browse_like_shell_history() {
+        let history = History::new(["bob", "alice"].map(Str::from).to_vec());
+        assert_eq!(history.newer(), None);
+
+        let (history, value) = history.older("ca").unwrap();
+        assert_eq!(value, "bob");
+        let (history, value) = history.older(&value).unwrap();
+        assert_eq!(value, "alice");
+        assert_eq!(history.older(&value), None);
+
+        let (history, value) = history.newer().unwrap();
+        assert_eq!(value, "bob");
+        let (history, value) = history.newer().unwrap();
+        assert_eq!(value, "ca");
+        assert!(!history.is_browsing());
+    }
+
+    #[test]
+    fn recording_moves_to_the_front() {
+        let entries = ["bob", "alice", "carol"].map(Str::from);
+
+        assert_eq!(
+            recorded(&entries, "alice", 10),
+            ["alice", "bob", "carol"].map(Str::from)
+        );
+        assert_eq!(
+            recorded(&entries, "dave", 2),
+            ["dave", "bob"].map(Str::from)
+        );
+    }
+}
@@ -0,0 +1,82 @@
+use std::time::Duration;
+
+use zbus::proxy;
+
+use crate::Str;
+use crate::os_release::OsRelease;
+
+/// Where machine-info(5) keeps the pretty hostname when hostnamed isn't running.
+const MACHINE_INFO: &str = "/etc/machine-info";
+
+/// hostnamed is started on demand, which shouldn't hold up the greeter for long.
+const HOSTNAMED_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// The part of systemd-hostnamed the greeting uses.
+#[proxy(
+    interface = "org.freedesktop.hostname1",
+    default_service = "org.freedesktop.hostname1",
+    default_path = "/org/freedesktop/hostname1"
+)]
+trait Hostname1 {
+    #[zbus(property)]
+    fn pretty_hostname(&self) -> zbus::Result<String>;
+}
+
+/// The name shown in the greeting: the pretty hostname, like "Alice's Laptop", if one is set,
+/// or else the kernel's.
+pub async fn load() -> Str {
+    if let Some(pretty) = pretty().await {
+        return pretty.into();
+    }
+    std::net::hostname()
+        .map(|host| host.to_string_lossy().into())
+        .unwrap_or_else(|_| "machine".into())
+}
+
+/// From hostnamed, or straight from machine-info(5) without it.
+async fn pretty() -> Option<String> {
+    let hostnamed = async {
+        let bus = zbus::Connection::system().await?;
+        Hostname1Proxy::new(&bus).await?.pretty_hostname().await
+    };
+    let pretty = match tokio::time::timeout(HOSTNAMED_TIMEOUT, hostnamed).await {
+        Ok(Ok(pretty)) => Some(pretty),
+        Ok(Err(err)) => {
+            tracing::debug!("failed to ask hostnamed for the pretty hostname: {err}");
+            None
+        }
+        Err(_) => {
+            tracing::debug!("hostnamed didn't answer in time");
+            None
+        }
+    };
+    let pretty = pretty.or_else(|| {
+        let contents = std::fs::read_to_string(MACHINE_INFO).ok()?;
+        from_machine_info(&contents)
+    })?;
+    let pretty = pretty.trim();
+    (!pretty.is_empty()).then(|| pretty.to_string())
+}
+
+/// machine-info(5) uses the same `KEY=value` format as os-release(5).
+fn from_machine_info(contents: &str) -> Option<String> {
+    OsRelease::parse(contents)
+        .get("PRETTY_HOSTNAME")
+        .map(str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::hostname::from_machine_info;
+
+    #[test]
+    fn reads_machine_info() {
+        let contents = "CHASSIS=laptop\nPRETTY_HOSTNAME=\"Alice's Laptop\"\n";
+
+        assert_eq!(
+            from_machine_info(contents).as_deref(),
+            Some("Alice's Laptop")
+        );
+        assert_eq!(from_machine_info("CHASSIS=laptop"), None);
+    }
+}
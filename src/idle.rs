@@ -0,0 +1,118 @@
+use std::process::{Command, Stdio};
+use std::time::Duration;
+
+use crate::config::IdleConfig;
+
+/// How long the dim takes to fade in. Waking up is instant.
+pub const DIM_FADE: Duration = Duration::from_millis(800);
+/// How far colors are blended towards the theme's `dim` role once dimmed.
+pub const DIM_AMOUNT: f32 = 0.6;
+
+/// How far the UI has gone to sleep after a stretch without input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub enum IdleLevel {
+    #[default]
+    Active,
+    Dimmed,
+    /// Nothing drawn at all, to spare always-on screens from burn-in.
+    Blank,
+}
+
+impl IdleLevel {
+    pub fn after(idle_for: Duration, config: &IdleConfig) -> Self {
+        if reached(idle_for, config.blank_after_secs) {
+            IdleLevel::Blank
+        } else if reached(idle_for, config.dim_after_secs) {
+            IdleLevel::Dimmed
+        } else {
+            IdleLevel::Active
+        }
+    }
+
+    pub fn is_active(self) -> bool {
+        self == IdleLevel::Active
+    }
+}
+
+/// Whether the form should be cleared after `idle_for` without input.
+pub fn clears_after(idle_for: Duration, config: &IdleConfig) -> bool {
+    reached(idle_for, config.clear_after_secs)
+}
+
+fn reached(idle_for: Duration, secs: Option<u64>) -> bool {
+    secs.is_some_and(|secs| idle_for >= Duration::from_secs(secs))
+}
+
+/// Blanks or unblanks the Linux console itself with `setterm`, powering down the display
+/// where the driver supports it. Does nothing outside the console.
+pub fn blank_console(blank: bool) {
+    if !std::env::var("TERM").is_ok_and(|term| term == "linux") {
+        return;
+    }
+    let mode = match blank {
+        true => "force",
+        false => "poke",
+    };
+    let status = Command::new("setterm")
+        .args(["--blank", mode])
+        .stdin(Stdio::null())
+        .status();
+    match status {
+        Ok(status) if status.success() => {}
+        Ok(status) => tracing::warn!("setterm --blank {mode} exited with {status}"),
+        Err(err) => tracing::warn!("failed to run setterm: {err}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::config::IdleConfig;
+    use crate::idle::{IdleLevel, clears_after};
+
+    #[test]
+    fn dims_after_timeout() {
+        let config = IdleConfig {
+            dim_after_secs: Some(30),
+            blank_after_secs: Some(300),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            IdleLevel::after(Duration::from_secs(29), &config),
+            IdleLevel::Active
+        );
+        assert_eq!(
+            IdleLevel::after(Duration::from_secs(30), &config),
+            IdleLevel::Dimmed
+        );
+        assert_eq!(
+            IdleLevel::after(Duration::from_secs(300), &config),
+            IdleLevel::Blank
+        );
+        assert_eq!(
+            IdleLevel::after(
+                Duration::MAX,
+                &IdleConfig {
+                    dim_after_secs: None,
+                    blank_after_secs: None,
+                    ..Default::default()
+                }
+            ),
+            IdleLevel::Active
+        );
+    }
+
+    #[test]
+    fn clears_only_when_configured() {
+        let config = IdleConfig {
+            clear_after_secs: Some(120),
+            ..Default::default()
+        };
+
+        assert!(!clears_after(Duration::from_secs(119), &config));
+        assert!(clears_after(Duration::from_secs(120), &config));
+        assert!(!clears_after(Duration::MAX, &IdleConfig::default()));
+    }
+}
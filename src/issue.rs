@@ -0,0 +1,157 @@
+use std::ffi::CStr;
+use std::mem::MaybeUninit;
+use std::path::Path;
+
+use ansi_to_tui::IntoText;
+use chrono::{DateTime, Local};
+use color_eyre::{Result, eyre::Context};
+use ratatui::text::Line;
+use tracing::instrument;
+
+use crate::os_release::OsRelease;
+use crate::term;
+
+pub const ISSUE_PATH: &str = "/etc/issue";
+/// Drop-ins appended to the issue file, like agetty does.
+const ISSUE_DIR: &str = "/etc/issue.d";
+
+/// What the agetty escapes in an issue file expand to.
+#[derive(Debug, Clone, Default)]
+pub struct IssueContext {
+    pub sysname: String,
+    pub nodename: String,
+    pub release: String,
+    pub version: String,
+    pub machine: String,
+    pub domainname: String,
+    pub tty: String,
+    pub os: OsRelease,
+}
+
+impl IssueContext {
+    pub fn detect() -> Self {
+        let mut context = Self {
+            os: OsRelease::load().unwrap_or_default(),
+            ..Default::default()
+        };
+        let mut uts = MaybeUninit::<libc::utsname>::uninit();
+        // SAFETY: uname fills in the whole struct when it returns 0.
+        if unsafe { libc::uname(uts.as_mut_ptr()) } == 0 {
+            let uts = unsafe { uts.assume_init() };
+            let field = |chars: &[libc::c_char]| {
+                let bytes = chars.iter().map(|&c| c as u8).collect::<Vec<_>>();
+                CStr::from_bytes_until_nul(&bytes)
+                    .map(|field| field.to_string_lossy().into_owned())
+                    .unwrap_or_default()
+            };
+            context.sysname = field(&uts.sysname);
+            context.nodename = field(&uts.nodename);
+            context.release = field(&uts.release);
+            context.version = field(&uts.version);
+            context.machine = field(&uts.machine);
+            context.domainname = field(&uts.domainname);
+        }
+        context.tty = term::tty_name().unwrap_or_default();
+        context
+    }
+}
+
+/// Reads the issue file at `path` and its drop-ins, expanding agetty escapes.
+#[instrument(err, skip(context))]
+pub fn load(path: &Path, context: &IssueContext) -> Result<Vec<Line<'static>>> {
+    let mut contents = std::fs::read_to_string(path)
+        .wrap_err_with(|| format!("failed to read issue file at {}", path.display()))?;
+    if path == Path::new(ISSUE_PATH)
+        && let Ok(entries) = std::fs::read_dir(ISSUE_DIR)
+    {
+        let mut drop_ins = entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == "issue"))
+            .collect::<Vec<_>>();
+        drop_ins.sort();
+        for drop_in in drop_ins {
+            contents.extend(std::fs::read_to_string(drop_in).ok());
+        }
+    }
+    let text = expand(&contents, context, Local::now())
+        .into_bytes()
+        .into_text()
+        .wrap_err("failed to parse ANSI escapes in issue file")?;
+    let mut lines = text.lines;
+    while lines.last().is_some_and(|line| line.width() == 0) {
+        lines.pop();
+    }
+    Ok(lines)
+}
+
+/// Expands the agetty(8) escapes in `text`. Unknown escapes are left as they are.
+pub fn expand(text: &str, context: &IssueContext, now: DateTime<Local>) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        let Some(escape) = chars.next() else {
+            out.push('\\');
+            break;
+        };
+        match escape {
+            'n' => out.push_str(&context.nodename),
+            'o' => out.push_str(&context.domainname),
+            'l' => out.push_str(&context.tty),
+            's' => out.push_str(&context.sysname),
+            'r' => out.push_str(&context.release),
+            'v' => out.push_str(&context.version),
+            'm' => out.push_str(&context.machine),
+            'd' => out.push_str(&now.format("%a %b %e %Y").to_string()),
+            't' => out.push_str(&now.format("%H:%M:%S").to_string()),
+            'e' => out.push('\x1b'),
+            '\\' => out.push('\\'),
+            'S' if chars.peek() == Some(&'{') => {
+                chars.next();
+                let key = chars.by_ref().take_while(|&c| c != '}').collect::<String>();
+                out.push_str(context.os.get(&key).unwrap_or_default());
+            }
+            'S' => out.push_str(context.os.pretty_name()),
+            other => {
+                out.push('\\');
+                out.push(other);
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Local, TimeZone};
+
+    use crate::issue::{IssueContext, expand};
+    use crate::os_release::OsRelease;
+
+    #[test]
+    fn expand_agetty_escapes() {
+        let context = IssueContext {
+            sysname: "Linux".into(),
+            nodename: "bingus".into(),
+            release: "6.18.0".into(),
+            machine: "x86_64".into(),
+            tty: "tty1".into(),
+            os: OsRelease::parse("PRETTY_NAME=\"Arch Linux\"\nID=arch"),
+            ..Default::default()
+        };
+        let now = Local.with_ymd_and_hms(2026, 3, 1, 9, 5, 0).unwrap();
+
+        assert_eq!(
+            expand(r"\S (\s \r \m) - \n on \l", &context, now),
+            "Arch Linux (Linux 6.18.0 x86_64) - bingus on tty1"
+        );
+        assert_eq!(
+            expand(r"\S{ID} \t \\ \q", &context, now),
+            r"arch 09:05:00 \ \q"
+        );
+        assert_eq!(expand(r"\e[1mhi", &context, now), "\x1b[1mhi");
+    }
+}
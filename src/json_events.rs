@@ -0,0 +1,177 @@
+use std::fs::File;
+use std::io::{self, Write};
+use std::os::fd::{FromRawFd, RawFd};
+use std::sync::{Arc, Mutex};
+
+use chrono::{Local, SecondsFormat};
+use serde::Serialize;
+
+use crate::toast::ToastLevel;
+use crate::{Focus, Model};
+
+/// Mirrors what happens in the greeter to a file descriptor as JSON lines, for test harnesses
+/// and monitoring that would otherwise have to read the screen.
+#[derive(Clone)]
+pub struct JsonEvents(Arc<Mutex<File>>);
+
+/// One line of the stream. Nothing typed into the form is in it, requests and responses come
+/// through as the event log shows them.
+#[derive(Serialize, Debug, PartialEq)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum Event<'a> {
+    /// The login form moved on, by [`crate::FormState::name`].
+    State {
+        from: &'static str,
+        to: &'static str,
+    },
+    Focus {
+        from: Focus,
+        to: Focus,
+    },
+    /// A line of the event log.
+    Log {
+        text: &'a str,
+    },
+    Toast {
+        text: &'a str,
+        level: ToastLevel,
+    },
+    Modal {
+        title: &'a str,
+    },
+}
+
+#[derive(Serialize)]
+struct Line<'a> {
+    at: String,
+    #[serde(flatten)]
+    event: Event<'a>,
+}
+
+/// What the stream compares before and after each message to see what changed.
+pub struct Observed {
+    state: &'static str,
+    focus: Focus,
+    logged: u64,
+    next_toast: u64,
+    modal: bool,
+}
+
+impl Observed {
+    pub fn of(model: &Model) -> Self {
+        Self {
+            state: model.form_state.name(),
+            focus: model.focus.clone(),
+            logged: model.event_log.pushed(),
+            next_toast: model.toasts.next_id(),
+            modal: model.modal.is_some(),
+        }
+    }
+}
+
+impl JsonEvents {
+    /// Takes over `fd`, which whoever started the greeter opened for it. It's closed on exec so
+    /// sessions don't inherit it.
+    pub fn open(fd: RawFd) -> io::Result<Self> {
+        // SAFETY: fcntl only looks at the descriptor, which may not be open.
+        let flags = unsafe { libc::fcntl(fd, libc::F_GETFD) };
+        if flags == -1 {
+            return Err(io::Error::last_os_error());
+        }
+        // SAFETY: as above, and `fd` is open.
+        if unsafe { libc::fcntl(fd, libc::F_SETFD, flags | libc::FD_CLOEXEC) } == -1 {
+            return Err(io::Error::last_os_error());
+        }
+        // SAFETY: `fd` is open, and nothing else in the greeter owns it.
+        let file = unsafe { File::from_raw_fd(fd) };
+        Ok(Self(Arc::new(Mutex::new(file))))
+    }
+
+    /// Writes an event for everything that changed in `model` since `before`.
+    pub fn changes(&self, before: &Observed, model: &Model) {
+        let state = model.form_state.name();
+        if state != before.state {
+            self.write(Event::State {
+                from: before.state,
+                to: state,
+            });
+        }
+        if model.focus != before.focus {
+            self.write(Event::Focus {
+                from: before.focus.clone(),
+                to: model.focus.clone(),
+            });
+        }
+        for entry in model.event_log.since(before.logged) {
+            self.write(Event::Log { text: &entry.text });
+        }
+        let toasts = model.toasts.iter();
+        for toast in toasts.filter(|toast| toast.id >= before.next_toast) {
+            self.write(Event::Toast {
+                text: &toast.text,
+                level: toast.level,
+            });
+        }
+        if let Some(modal) = &model.modal
+            && !before.modal
+        {
+            self.write(Event::Modal {
+                title: &modal.title,
+            });
+        }
+    }
+
+    /// Written straight away, so whatever reads it sees events as they happen.
+    pub fn write(&self, event: Event) {
+        let line = Line {
+            at: Local::now().to_rfc3339_opts(SecondsFormat::Millis, false),
+            event,
+        };
+        let result = serde_json::to_string(&line)
+            .map_err(io::Error::other)
+            .and_then(|json| {
+                let mut file = self.0.lock().unwrap();
+                writeln!(file, "{json}")
+            });
+        if let Err(err) = result {
+            tracing::debug!("failed to write {:?} to --json-events: {err}", line.event);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Focus;
+    use crate::json_events::Event;
+    use crate::toast::ToastLevel;
+
+    #[test]
+    fn events_are_tagged() -> color_eyre::Result<()> {
+        let state = Event::State {
+            from: "idle",
+            to: "created_session",
+        };
+        assert_eq!(
+            serde_json::to_string(&state)?,
+            r#"{"event":"state","from":"idle","to":"created_session"}"#
+        );
+        let focus = Event::Focus {
+            from: Focus::UsernameField,
+            to: Focus::PasswordField,
+        };
+        assert_eq!(
+            serde_json::to_string(&focus)?,
+            r#"{"event":"focus","from":"username_field","to":"password_field"}"#
+        );
+        let toast = Event::Toast {
+            text: "caps lock is on",
+            level: ToastLevel::Warning,
+        };
+        assert_eq!(
+            serde_json::to_string(&toast)?,
+            r#"{"event":"toast","text":"caps lock is on","level":"warning"}"#
+        );
+
+        Ok(())
+    }
+}
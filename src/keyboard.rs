@@ -0,0 +1,119 @@
+/// The character rows of the on-screen keyboard, above a row of [`Key::SPECIAL`] keys.
+const ROWS: [&str; 4] = ["1234567890", "qwertyuiop", "asdfghjkl-", "zxcvbnm._@"];
+
+/// A key of the on-screen keyboard.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Key {
+    Char(char),
+    Shift,
+    Space,
+    Backspace,
+    Enter,
+}
+
+impl Key {
+    const SPECIAL: [Key; 4] = [Key::Shift, Key::Space, Key::Backspace, Key::Enter];
+
+    pub fn label(self, shift: bool) -> String {
+        match self {
+            Key::Char(c) if shift => c.to_uppercase().collect(),
+            Key::Char(c) => c.to_string(),
+            Key::Shift => "shift".into(),
+            Key::Space => "space".into(),
+            Key::Backspace => "del".into(),
+            Key::Enter => "enter".into(),
+        }
+    }
+}
+
+/// An on-screen keyboard for kiosks without a physical one, driven by the arrow keys and
+/// Enter of a remote or d-pad.
+#[derive(Debug, Clone, Default)]
+pub struct Keyboard {
+    row: usize,
+    col: usize,
+    shift: bool,
+}
+
+impl Keyboard {
+    pub fn rows() -> impl Iterator<Item = Vec<Key>> {
+        ROWS.iter()
+            .map(|row| row.chars().map(Key::Char).collect())
+            .chain([Key::SPECIAL.to_vec()])
+    }
+
+    pub fn shift(&self) -> bool {
+        self.shift
+    }
+
+    /// The row and column of the selected key.
+    pub fn selected(&self) -> (usize, usize) {
+        (self.row, self.col)
+    }
+
+    /// Moves the selection, wrapping around within a row.
+    ///
+    /// Returns `false` without moving if that would go past the top or bottom row, so the
+    /// focus can move on to what's above or below the keyboard.
+    pub fn move_by(&mut self, rows: isize, cols: isize) -> bool {
+        let Some(row) = self
+            .row
+            .checked_add_signed(rows)
+            .filter(|row| *row <= ROWS.len())
+        else {
+            return false;
+        };
+        let len = Self::row_len(row) as isize;
+        // Keep roughly the same horizontal position between rows of different lengths.
+        let col = self.col * Self::row_len(row) / Self::row_len(self.row);
+        self.row = row;
+        self.col = (col as isize + cols).rem_euclid(len) as usize;
+        true
+    }
+
+    /// Presses the selected key. Shift only applies to the next character, so it is handled
+    /// here and never returned.
+    pub fn press(&mut self) -> Option<Key> {
+        let key = Self::rows().nth(self.row)?.get(self.col).copied()?;
+        match key {
+            Key::Shift => {
+                self.shift = !self.shift;
+                None
+            }
+            Key::Char(c) if self.shift => {
+                self.shift = false;
+                Some(Key::Char(c.to_ascii_uppercase()))
+            }
+            key => Some(key),
+        }
+    }
+
+    fn row_len(row: usize) -> usize {
+        ROWS.get(row).map_or(Key::SPECIAL.len(), |row| row.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::keyboard::{Key, Keyboard};
+
+    #[test]
+    fn navigate_and_type() {
+        let mut keyboard = Keyboard::default();
+        assert!(!keyboard.move_by(-1, 0));
+        assert!(keyboard.move_by(1, -1));
+        assert_eq!(keyboard.press(), Some(Key::Char('p')));
+
+        assert!(keyboard.move_by(3, 0));
+        assert_eq!(keyboard.selected(), (4, 3));
+        assert_eq!(keyboard.press(), Some(Key::Enter));
+        assert!(!keyboard.move_by(1, 0));
+
+        keyboard.move_by(0, 1);
+        assert_eq!(keyboard.press(), None);
+        assert!(keyboard.shift());
+        keyboard.move_by(-1, 0);
+        assert_eq!(keyboard.press(), Some(Key::Char('Z')));
+        assert!(!keyboard.shift());
+    }
+}
@@ -0,0 +1,630 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+use color_eyre::Result;
+use color_eyre::eyre::{Report, eyre};
+use ratatui::crossterm::event::{Event, KeyCode, KeyEventKind, KeyModifiers};
+use serde::{Deserialize, Serialize};
+
+use crate::power::PowerAction;
+
+/// Where a group of key bindings applies.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Scope {
+    Global,
+    Form,
+    UserMenu,
+    SessionPicker,
+    PowerMenu,
+    Keyboard,
+    Palette,
+    Dialog,
+    Preview,
+}
+
+impl Scope {
+    pub fn title(self) -> &'static str {
+        match self {
+            Scope::Global => "Anywhere",
+            Scope::Form => "Login form",
+            Scope::UserMenu => "User menu",
+            Scope::SessionPicker => "Session picker",
+            Scope::PowerMenu => "Power menu",
+            Scope::Keyboard => "On-screen keyboard",
+            Scope::Palette => "Command palette",
+            Scope::Dialog => "Dialogs",
+            Scope::Preview => "Preview",
+        }
+    }
+}
+
+/// Declares every [`Action`] once with its scope, default keys and what the help says it
+/// does.
+macro_rules! actions {
+    ($($action:ident: $scope:ident [$($key:literal),*] $description:literal,)*) => {
+        /// Something a key binding does, named in the config's `[keys]` table.
+        #[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        #[serde(rename_all = "kebab-case")]
+        pub enum Action {
+            $($action,)*
+        }
+
+        impl Action {
+            /// Every action, in the order the help lists them.
+            pub const ALL: &[Action] = &[$(Action::$action,)*];
+
+            pub fn scope(self) -> Scope {
+                match self {
+                    $(Action::$action => Scope::$scope,)*
+                }
+            }
+
+            pub fn description(self) -> &'static str {
+                match self {
+                    $(Action::$action => $description,)*
+                }
+            }
+
+            fn default_keys(self) -> &'static [&'static str] {
+                match self {
+                    $(Action::$action => &[$($key),*],)*
+                }
+            }
+        }
+    };
+}
+
+actions! {
+    Help: Global ["f1", "?"] "show or hide this help",
+    Quit: Global ["ctrl+c"] "quit",
+    Palette: Global ["ctrl+p"] "command palette",
+    ToggleLog: Global ["alt+l"] "show or hide the event log",
+    ScrollUp: Global ["alt+up"] "scroll the event log or message of the day up",
+    ScrollDown: Global ["alt+down"] "scroll them down",
+    ErrorDetails: Global ["alt+e", "d"] "details of a failed login",
+    Debug: Global ["f12"] "show or hide debug info",
+    BrightnessUp: Global ["alt+="] "brighten the screen",
+    BrightnessDown: Global ["alt+-"] "dim the screen",
+    NextKeymap: Global ["alt+k"] "switch to the next console keymap",
+    GoForm: Global ["g f"] "the username field",
+    GoUsers: Global ["g u"] "the user menu",
+    GoSessions: Global ["g s"] "the session picker",
+    GoPower: Global ["g p"] "the power menu",
+    GoKeyboard: Global ["g k"] "the on-screen keyboard",
+    NextField: Form ["down", "ctrl+j"] "next field, or a more recent username",
+    PrevField: Form ["up", "ctrl+k"] "previous field, or an older username",
+    SwitchField: Form ["tab"] "complete the username, or the next field",
+    SwitchBack: Form ["shift+tab"] "the field before",
+    Complete: Form ["ctrl+n"] "complete the username",
+    Submit: Form ["enter"] "next field, log in from the password",
+    Escape: Form ["esc"] "what the escape config says",
+    UserDown: UserMenu ["down", "j"] "next user",
+    UserUp: UserMenu ["up", "k"] "previous user",
+    PickUser: UserMenu ["enter"] "log in as the user",
+    TypeUsername: UserMenu ["tab"] "type a username instead",
+    UserMenuBack: UserMenu ["shift+tab"] "the password field, if tab wraps",
+    SessionDown: SessionPicker ["down", "j", "tab"] "next session",
+    SessionUp: SessionPicker ["up", "k", "shift+tab"] "previous session",
+    StartShell: SessionPicker ["b"] "start a shell instead",
+    LeavePicker: SessionPicker ["esc"] "what the escape config says",
+    Shutdown: PowerMenu ["f2"] "shut down after confirming, from anywhere",
+    Reboot: PowerMenu ["f3"] "reboot after confirming, from anywhere",
+    Suspend: PowerMenu ["f4"] "suspend after confirming, from anywhere",
+    Hibernate: PowerMenu ["f5"] "hibernate after confirming, from anywhere",
+    PowerNext: PowerMenu ["right", "l"] "next action",
+    PowerPrev: PowerMenu ["left", "h"] "previous action",
+    PowerRun: PowerMenu ["enter"] "run it",
+    PowerBack: PowerMenu ["up", "tab", "esc"] "back to the form",
+    KeyboardUp: Keyboard ["up"] "key above",
+    KeyboardDown: Keyboard ["down"] "key below",
+    KeyboardLeft: Keyboard ["left"] "key to the left",
+    KeyboardRight: Keyboard ["right"] "key to the right",
+    KeyboardPress: Keyboard ["enter"] "press it",
+    KeyboardBack: Keyboard ["tab", "esc"] "back to the field",
+    PaletteNext: Palette ["down", "tab"] "next command",
+    PalettePrev: Palette ["up", "shift+tab"] "previous command",
+    PaletteRun: Palette ["enter"] "run it",
+    PaletteClose: Palette ["esc"] "close",
+    DialogNext: Dialog ["tab", "right", "l"] "next button",
+    DialogPrev: Dialog ["left", "h", "shift+tab"] "previous button",
+    DialogPress: Dialog ["enter"] "press it",
+    DialogClose: Dialog ["esc", "q"] "close",
+    NextState: Preview ["pagedown"] "next form state",
+    PrevState: Preview ["pageup"] "previous form state",
+}
+
+impl Action {
+    /// The power action this runs from anywhere, for [`Action::Shutdown`] and friends.
+    pub fn power(self) -> Option<PowerAction> {
+        match self {
+            Action::Shutdown => Some(PowerAction::Shutdown),
+            Action::Reboot => Some(PowerAction::Reboot),
+            Action::Suspend => Some(PowerAction::Suspend),
+            Action::Hibernate => Some(PowerAction::Hibernate),
+            _ => None,
+        }
+    }
+}
+
+impl From<PowerAction> for Action {
+    fn from(action: PowerAction) -> Self {
+        match action {
+            PowerAction::Shutdown => Action::Shutdown,
+            PowerAction::Reboot => Action::Reboot,
+            PowerAction::Suspend => Action::Suspend,
+            PowerAction::Hibernate => Action::Hibernate,
+        }
+    }
+}
+
+/// What Esc does, chosen per context in the config's `[escape]` table.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum EscapeAction {
+    Nothing,
+    /// Empty the focused field.
+    Clear,
+    /// Go back a step: the password to the username, the username to the user menu and the
+    /// session picker to the form.
+    Back,
+    /// Cancel the login in progress and start over.
+    Cancel,
+    PowerMenu,
+}
+
+/// A key with the modifiers held down with it, written like `ctrl+shift+j` in the config.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(try_from = "String", into = "String")]
+pub struct KeyCombo {
+    code: KeyCode,
+    modifiers: KeyModifiers,
+}
+
+impl KeyCombo {
+    /// Terminals disagree on whether symbols like `?` come with shift held and report
+    /// Shift+Tab as BackTab, so combos are kept in one form: letters lowercase with shift as
+    /// a modifier, symbols without it.
+    pub fn new(code: KeyCode, modifiers: KeyModifiers) -> Self {
+        let (code, modifiers) = match code {
+            KeyCode::Char(c) if c.is_uppercase() => (
+                KeyCode::Char(c.to_lowercase().next().unwrap_or(c)),
+                modifiers | KeyModifiers::SHIFT,
+            ),
+            KeyCode::Char(c) if !c.is_alphabetic() => (code, modifiers - KeyModifiers::SHIFT),
+            KeyCode::BackTab => (KeyCode::Tab, modifiers | KeyModifiers::SHIFT),
+            code => (code, modifiers),
+        };
+        Self { code, modifiers }
+    }
+
+    pub fn matches(&self, event: &Event) -> bool {
+        match event {
+            Event::Key(key) if key.kind != KeyEventKind::Release => {
+                *self == KeyCombo::new(key.code, key.modifiers)
+            }
+            _ => false,
+        }
+    }
+
+    /// The combo in the config's syntax, or the help's when `pretty`.
+    fn format(&self, pretty: bool) -> String {
+        let modifiers = [
+            (KeyModifiers::CONTROL, "ctrl", "Ctrl"),
+            (KeyModifiers::ALT, "alt", "Alt"),
+            (KeyModifiers::SHIFT, "shift", "Shift"),
+            (KeyModifiers::SUPER, "super", "Super"),
+        ];
+        let mut out = String::new();
+        for (modifier, name, pretty_name) in modifiers {
+            if self.modifiers.contains(modifier) {
+                out.push_str(if pretty { pretty_name } else { name });
+                out.push('+');
+            }
+        }
+        let key = match (self.code, pretty) {
+            (KeyCode::Char(' '), _) => "space".to_string(),
+            (KeyCode::Char(c), true) if !self.modifiers.is_empty() => c.to_uppercase().collect(),
+            (KeyCode::Char(c), _) => c.to_string(),
+            (KeyCode::F(n), _) => format!("f{n}"),
+            (KeyCode::Up, true) => "↑".to_string(),
+            (KeyCode::Down, true) => "↓".to_string(),
+            (KeyCode::Left, true) => "←".to_string(),
+            (KeyCode::Right, true) => "→".to_string(),
+            (KeyCode::PageUp, true) => "PgUp".to_string(),
+            (KeyCode::PageDown, true) => "PgDn".to_string(),
+            (code, _) => code.to_string().to_lowercase().replace(' ', ""),
+        };
+        match pretty {
+            true => capitalize(&key, &out),
+            false => out + &key,
+        }
+    }
+}
+
+/// Capitalizes named keys like `enter` or `f1` for the help, after the modifiers in `prefix`.
+fn capitalize(key: &str, prefix: &str) -> String {
+    let mut chars = key.chars();
+    match (chars.next(), key.chars().count()) {
+        (Some(first), 2..) => format!("{prefix}{}{}", first.to_uppercase(), chars.as_str()),
+        _ => format!("{prefix}{key}"),
+    }
+}
+
+impl fmt::Display for KeyCombo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.format(true))
+    }
+}
+
+impl FromStr for KeyCombo {
+    type Err = Report;
+
+    fn from_str(combo: &str) -> Result<Self> {
+        let combo = combo.trim();
+        // A trailing `+` is the plus key itself, like in `ctrl++`.
+        let (modifiers, key) = match combo.strip_suffix("++") {
+            Some(modifiers) => (modifiers, "+"),
+            None if combo == "+" => ("", "+"),
+            None => combo.rsplit_once('+').unwrap_or(("", combo)),
+        };
+        let mut parsed = KeyModifiers::NONE;
+        for modifier in modifiers.split('+').filter(|modifier| !modifier.is_empty()) {
+            parsed |= match modifier.to_lowercase().as_str() {
+                "ctrl" | "control" => KeyModifiers::CONTROL,
+                "alt" | "meta" => KeyModifiers::ALT,
+                "shift" => KeyModifiers::SHIFT,
+                "super" => KeyModifiers::SUPER,
+                _ => return Err(eyre!("unknown modifier `{modifier}` in `{combo}`")),
+            };
+        }
+        let function = key
+            .strip_prefix(['f', 'F'])
+            .and_then(|n| n.parse::<u8>().ok());
+        let code = match key.to_lowercase().as_str() {
+            _ if function.is_some() => KeyCode::F(function.unwrap_or_default()),
+            "enter" | "return" => KeyCode::Enter,
+            "esc" | "escape" => KeyCode::Esc,
+            "tab" => KeyCode::Tab,
+            "backtab" => KeyCode::BackTab,
+            "space" => KeyCode::Char(' '),
+            "backspace" => KeyCode::Backspace,
+            "delete" | "del" => KeyCode::Delete,
+            "insert" | "ins" => KeyCode::Insert,
+            "home" => KeyCode::Home,
+            "end" => KeyCode::End,
+            "pageup" | "pgup" => KeyCode::PageUp,
+            "pagedown" | "pgdn" => KeyCode::PageDown,
+            "up" => KeyCode::Up,
+            "down" => KeyCode::Down,
+            "left" => KeyCode::Left,
+            "right" => KeyCode::Right,
+            _ => {
+                let mut chars = key.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) => KeyCode::Char(c),
+                    _ => return Err(eyre!("unknown key `{key}` in `{combo}`")),
+                }
+            }
+        };
+        Ok(KeyCombo::new(code, parsed))
+    }
+}
+
+impl TryFrom<String> for KeyCombo {
+    type Error = Report;
+
+    fn try_from(combo: String) -> Result<Self> {
+        combo.parse()
+    }
+}
+
+impl From<KeyCombo> for String {
+    fn from(combo: KeyCombo) -> Self {
+        combo.format(false)
+    }
+}
+
+/// A combo, or two pressed one after the other like `g p`.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(try_from = "String", into = "String")]
+pub struct Chord {
+    /// The combo waiting for the second one, in a two step chord.
+    leader: Option<KeyCombo>,
+    key: KeyCombo,
+}
+
+impl Chord {
+    /// Whether `event` is this whole chord, which only a single step one can be.
+    pub fn matches(&self, event: &Event) -> bool {
+        self.leader.is_none() && self.key.matches(event)
+    }
+
+    pub fn leader(&self) -> Option<KeyCombo> {
+        self.leader
+    }
+
+    pub fn key(&self) -> KeyCombo {
+        self.key
+    }
+}
+
+impl fmt::Display for Chord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.leader {
+            Some(leader) => write!(f, "{leader} {}", self.key),
+            None => write!(f, "{}", self.key),
+        }
+    }
+}
+
+impl FromStr for Chord {
+    type Err = Report;
+
+    fn from_str(chord: &str) -> Result<Self> {
+        let steps = chord
+            .split_whitespace()
+            .map(str::parse)
+            .collect::<Result<Vec<KeyCombo>>>()?;
+        match steps.as_slice() {
+            [key] => Ok(Chord {
+                leader: None,
+                key: *key,
+            }),
+            [leader, key] => Ok(Chord {
+                leader: Some(*leader),
+                key: *key,
+            }),
+            _ => Err(eyre!("`{chord}` isn't one or two keys")),
+        }
+    }
+}
+
+impl TryFrom<String> for Chord {
+    type Error = Report;
+
+    fn try_from(chord: String) -> Result<Self> {
+        chord.parse()
+    }
+}
+
+impl From<Chord> for String {
+    fn from(chord: Chord) -> Self {
+        match chord.leader {
+            Some(leader) => format!("{} {}", String::from(leader), String::from(chord.key)),
+            None => chord.key.into(),
+        }
+    }
+}
+
+/// Whether `event` is a key being pressed, as opposed to released or not a key at all.
+pub fn is_press(event: &Event) -> bool {
+    matches!(event, Event::Key(key) if key.kind != KeyEventKind::Release)
+}
+
+/// Whether `event` is a key that types something, which text fields get before any
+/// binding does.
+pub fn is_text(event: &Event) -> bool {
+    match event {
+        Event::Key(key) => {
+            matches!(key.code, KeyCode::Char(_))
+                && !key
+                    .modifiers
+                    .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT | KeyModifiers::SUPER)
+        }
+        _ => false,
+    }
+}
+
+/// The keys of every action: the defaults, with the ones the config's `[keys]` table sets
+/// replacing them action by action.
+///
+/// Two step chords like `g p` only work for the actions available anywhere, and not while
+/// typing into a field if they start with a character.
+///
+/// ```toml
+/// [keys]
+/// help = ["f1"]
+/// palette = ["ctrl+space"]
+/// go-power = ["space p"]
+/// ```
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(
+    from = "HashMap<Action, Vec<Chord>>",
+    into = "HashMap<Action, Vec<Chord>>"
+)]
+pub struct KeyMap(HashMap<Action, Vec<Chord>>);
+
+impl KeyMap {
+    pub fn keys(&self, action: Action) -> &[Chord] {
+        self.0.get(&action).map_or(&[], Vec::as_slice)
+    }
+
+    /// The first action in `scope` bound to `event`.
+    pub fn action(&self, scope: Scope, event: &Event) -> Option<Action> {
+        self.find(scope, |chord| chord.matches(event))
+    }
+
+    /// The combo `event` is, if it starts a chord of an action in `scope`.
+    pub fn leader(&self, scope: Scope, event: &Event) -> Option<KeyCombo> {
+        Action::ALL
+            .iter()
+            .filter(|action| action.scope() == scope)
+            .flat_map(|action| self.keys(*action))
+            .find_map(|chord| chord.leader().filter(|leader| leader.matches(event)))
+    }
+
+    /// The action in `scope` whose chord `event` finishes after `leader`.
+    pub fn chord(&self, scope: Scope, leader: KeyCombo, event: &Event) -> Option<Action> {
+        self.find(scope, |chord| {
+            chord.leader() == Some(leader) && chord.key().matches(event)
+        })
+    }
+
+    /// The actions in `scope` with chords starting with `leader`, and the key finishing each.
+    pub fn after(&self, scope: Scope, leader: KeyCombo) -> Vec<(KeyCombo, Action)> {
+        Action::ALL
+            .iter()
+            .filter(|action| action.scope() == scope)
+            .flat_map(|action| {
+                self.keys(*action)
+                    .iter()
+                    .filter(|chord| chord.leader() == Some(leader))
+                    .map(|chord| (chord.key(), *action))
+            })
+            .collect()
+    }
+
+    fn find(&self, scope: Scope, matches: impl Fn(&Chord) -> bool) -> Option<Action> {
+        Action::ALL
+            .iter()
+            .copied()
+            .filter(|action| action.scope() == scope)
+            .find(|action| self.keys(*action).iter().any(&matches))
+    }
+
+    /// The keys of `action` the way the help shows them, like `↓ / Ctrl+J`.
+    pub fn describe(&self, action: Action) -> String {
+        self.keys(action)
+            .iter()
+            .map(Chord::to_string)
+            .collect::<Vec<_>>()
+            .join(" / ")
+    }
+}
+
+impl Default for KeyMap {
+    fn default() -> Self {
+        let bindings = Action::ALL.iter().map(|action| {
+            let keys = action
+                .default_keys()
+                .iter()
+                .filter_map(|key| key.parse().ok())
+                .collect();
+            (*action, keys)
+        });
+        Self(bindings.collect())
+    }
+}
+
+impl From<HashMap<Action, Vec<Chord>>> for KeyMap {
+    fn from(overrides: HashMap<Action, Vec<Chord>>) -> Self {
+        let mut keys = Self::default();
+        keys.0.extend(overrides);
+        keys
+    }
+}
+
+impl From<KeyMap> for HashMap<Action, Vec<Chord>> {
+    fn from(keys: KeyMap) -> Self {
+        keys.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+
+    use crate::keymap::{Action, Chord, KeyCombo, KeyMap, Scope};
+
+    fn press(code: KeyCode, modifiers: KeyModifiers) -> Event {
+        Event::Key(KeyEvent::new(code, modifiers))
+    }
+
+    #[test]
+    fn parse_and_format_combos() -> color_eyre::Result<()> {
+        let combo: KeyCombo = "ctrl+shift+j".parse()?;
+        assert_eq!(
+            combo,
+            KeyCombo::new(KeyCode::Char('J'), KeyModifiers::CONTROL)
+        );
+        assert_eq!(combo.to_string(), "Ctrl+Shift+J");
+        assert_eq!(String::from(combo), "ctrl+shift+j");
+
+        for combo in ["f12", "alt+up", "?", "ctrl++", "pagedown", "space", "esc"] {
+            assert_eq!(String::from(combo.parse::<KeyCombo>()?), combo);
+        }
+        assert_eq!("alt+down".parse::<KeyCombo>()?.to_string(), "Alt+↓");
+        assert_eq!("enter".parse::<KeyCombo>()?.to_string(), "Enter");
+        assert!("hyper+j".parse::<KeyCombo>().is_err());
+        assert!("jk".parse::<KeyCombo>().is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn match_events_the_way_terminals_send_them() -> color_eyre::Result<()> {
+        let question: KeyCombo = "?".parse()?;
+        assert!(question.matches(&press(KeyCode::Char('?'), KeyModifiers::SHIFT)));
+        assert!(question.matches(&press(KeyCode::Char('?'), KeyModifiers::NONE)));
+
+        let back: KeyCombo = "shift+tab".parse()?;
+        assert!(back.matches(&press(KeyCode::BackTab, KeyModifiers::SHIFT)));
+        assert_eq!(
+            KeyMap::default().action(Scope::Form, &press(KeyCode::BackTab, KeyModifiers::NONE)),
+            Some(Action::SwitchBack)
+        );
+
+        let j: KeyCombo = "j".parse()?;
+        assert!(!j.matches(&press(KeyCode::Char('J'), KeyModifiers::SHIFT)));
+
+        Ok(())
+    }
+
+    #[test]
+    fn config_replaces_defaults_per_action() -> color_eyre::Result<()> {
+        let keys: KeyMap = toml::from_str(r#"help = ["ctrl+h"]"#)?;
+        let f1 = press(KeyCode::F(1), KeyModifiers::NONE);
+        let ctrl_h = press(KeyCode::Char('h'), KeyModifiers::CONTROL);
+
+        assert_eq!(keys.action(Scope::Global, &f1), None);
+        assert_eq!(keys.action(Scope::Global, &ctrl_h), Some(Action::Help));
+        assert_eq!(
+            KeyMap::default().action(Scope::PowerMenu, &press(KeyCode::F(4), KeyModifiers::NONE)),
+            Some(Action::Suspend)
+        );
+        assert_eq!(keys.describe(Action::NextField), "↓ / Ctrl+J");
+
+        Ok(())
+    }
+
+    #[test]
+    fn two_step_chords() -> color_eyre::Result<()> {
+        let chord: Chord = "g shift+p".parse()?;
+        assert_eq!(chord.to_string(), "g Shift+P");
+        assert_eq!(String::from(chord), "g shift+p");
+        assert!("g p q".parse::<Chord>().is_err());
+
+        let keys: KeyMap = toml::from_str(r#"go-power = ["space p", "alt+p"]"#)?;
+        let (space, p) = (
+            press(KeyCode::Char(' '), KeyModifiers::NONE),
+            press(KeyCode::Char('p'), KeyModifiers::NONE),
+        );
+        let leader = keys.leader(Scope::Global, &space).unwrap();
+        assert_eq!(keys.action(Scope::Global, &space), None);
+        assert_eq!(keys.action(Scope::Global, &p), None);
+        assert_eq!(keys.chord(Scope::Global, leader, &p), Some(Action::GoPower));
+        assert_eq!(
+            keys.action(Scope::Global, &press(KeyCode::Char('p'), KeyModifiers::ALT)),
+            Some(Action::GoPower)
+        );
+        assert_eq!(
+            keys.after(Scope::Global, leader),
+            [("p".parse()?, Action::GoPower)]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn default_keys_parse() {
+        let keys = KeyMap::default();
+        for action in Action::ALL {
+            assert_eq!(
+                keys.keys(*action).len(),
+                action.default_keys().len(),
+                "{action:?}"
+            );
+        }
+    }
+}
@@ -0,0 +1,99 @@
+use serde::{Deserialize, Serialize};
+
+use crate::config::LayoutConfig;
+
+/// Smallest terminal the form fits on at all, in cells.
+pub const MIN_COLS: u16 = 30;
+pub const MIN_ROWS: u16 = 10;
+
+/// Whether the terminal is too small to draw anything but a note asking for more room.
+pub fn too_small(cols: u16, rows: u16) -> bool {
+    cols < MIN_COLS || rows < MIN_ROWS
+}
+
+/// Whether the user menu and session picker go below the form or in a column to its right.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum Columns {
+    #[default]
+    One,
+    Two,
+}
+
+/// How much room the terminal leaves the greeter, which decides what gets drawn.
+///
+/// An 80x24 console only fits the form with the banner, but a VT with a big font or a serial
+/// console can be a lot smaller than that.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Breakpoint {
+    Tiny,
+    Compact,
+    Full,
+}
+
+impl Breakpoint {
+    pub fn new(cols: u16, rows: u16) -> Self {
+        match (cols, rows) {
+            (..50, _) | (_, ..20) => Breakpoint::Tiny,
+            (..80, _) | (_, ..30) => Breakpoint::Compact,
+            _ => Breakpoint::Full,
+        }
+    }
+
+    /// Background art, the issue, avatar and message of the day.
+    pub fn decorations(self) -> bool {
+        self == Breakpoint::Full
+    }
+
+    pub fn banner(self) -> bool {
+        self >= Breakpoint::Compact
+    }
+
+    /// Shorter field labels, the greeting stacked over the hostname and no widgets.
+    pub fn tiny(self) -> bool {
+        self == Breakpoint::Tiny
+    }
+}
+
+/// Whether the lists get a column of their own, which needs a wide terminal that's tall
+/// enough for everything else too.
+pub fn side_column(config: &LayoutConfig, breakpoint: Breakpoint, cols: u16) -> bool {
+    config.columns == Columns::Two
+        && breakpoint == Breakpoint::Full
+        && cols >= config.two_column_min_width
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::LayoutConfig;
+    use crate::layout::{Breakpoint, Columns, side_column, too_small};
+
+    #[test]
+    fn breakpoints() {
+        assert_eq!(Breakpoint::new(200, 60), Breakpoint::Full);
+        assert_eq!(Breakpoint::new(80, 24), Breakpoint::Compact);
+        assert_eq!(Breakpoint::new(53, 20), Breakpoint::Compact);
+        assert_eq!(Breakpoint::new(40, 30), Breakpoint::Tiny);
+        assert_eq!(Breakpoint::new(80, 15), Breakpoint::Tiny);
+        assert!(too_small(29, 24));
+        assert!(too_small(80, 9));
+        assert!(!too_small(30, 10));
+    }
+
+    #[test]
+    fn side_column_needs_room() {
+        let two = LayoutConfig {
+            columns: Columns::Two,
+            ..Default::default()
+        };
+
+        assert!(side_column(&two, Breakpoint::new(160, 40), 160));
+        assert!(!side_column(&two, Breakpoint::new(100, 40), 100));
+        assert!(!side_column(&two, Breakpoint::new(160, 24), 160));
+        assert!(!side_column(
+            &LayoutConfig::default(),
+            Breakpoint::new(160, 40),
+            160
+        ));
+    }
+}
@@ -0,0 +1,143 @@
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::{Path, PathBuf};
+
+use tracing_appender::non_blocking::{NonBlocking, WorkerGuard};
+
+/// Where the log goes without `--log-file`, the first of these the greeter user can write to.
+pub const DEFAULT_PATHS: [&str; 2] = ["/var/log/impolite.log", "/var/cache/impolite/impolite.log"];
+
+/// A log grows to this many bytes before it is rotated.
+const MAX_LEN: u64 = 1024 * 1024;
+/// Rotated logs kept next to the current one, as `impolite.log.1` and so on.
+const KEEP: usize = 3;
+/// Readable by the group, where admins usually are, but not by everyone: logins are in it.
+const FILE_MODE: u32 = 0o640;
+
+/// A log file that is moved aside to `<path>.1` once it reaches [`MAX_LEN`], pushing the older
+/// ones up and dropping the last.
+pub struct RotatingFile {
+    path: PathBuf,
+    file: File,
+    len: u64,
+    max_len: u64,
+}
+
+impl RotatingFile {
+    pub fn open(path: &Path) -> io::Result<Self> {
+        Self::with_max_len(path, MAX_LEN)
+    }
+
+    fn with_max_len(path: &Path, max_len: u64) -> io::Result<Self> {
+        let file = open(path)?;
+        let len = file.metadata()?.len();
+        Ok(Self {
+            path: path.to_path_buf(),
+            file,
+            len,
+            max_len,
+        })
+    }
+
+    fn rotate(&mut self) -> io::Result<()> {
+        for n in (1..KEEP).rev() {
+            let from = rotated(&self.path, n);
+            if from.exists() {
+                std::fs::rename(&from, rotated(&self.path, n + 1))?;
+            }
+        }
+        std::fs::rename(&self.path, rotated(&self.path, 1))?;
+        self.file = open(&self.path)?;
+        self.len = 0;
+        Ok(())
+    }
+}
+
+impl Write for RotatingFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.len > 0 && self.len + buf.len() as u64 > self.max_len {
+            self.rotate()?;
+        }
+        let written = self.file.write(buf)?;
+        self.len += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+fn open(path: &Path) -> io::Result<File> {
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .mode(FILE_MODE)
+        .custom_flags(libc::O_NOFOLLOW)
+        .open(path)
+}
+
+fn rotated(path: &Path, n: usize) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(format!(".{n}"));
+    PathBuf::from(name)
+}
+
+/// Opens `path`, or the first of [`DEFAULT_PATHS`] that can be written, behind a writer that
+/// hands lines to a background thread so logging never blocks the UI. The guard flushes what's
+/// left when it's dropped, so it has to live until the greeter exits.
+pub fn open_non_blocking(path: Option<&Path>) -> io::Result<(NonBlocking, WorkerGuard)> {
+    let file = match path {
+        Some(path) => RotatingFile::open(path)?,
+        None => {
+            let mut last_err = None;
+            let file = DEFAULT_PATHS.iter().find_map(|path| {
+                RotatingFile::open(Path::new(path))
+                    .map_err(|err| last_err = Some(err))
+                    .ok()
+            });
+            match file {
+                Some(file) => file,
+                None => return Err(last_err.unwrap_or_else(|| io::ErrorKind::NotFound.into())),
+            }
+        }
+    };
+    Ok(tracing_appender::non_blocking(file))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use crate::log_file::{RotatingFile, rotated};
+
+    #[test]
+    fn rotates_by_size() -> std::io::Result<()> {
+        let dir = std::env::temp_dir().join(format!("impolite-log-{}", std::process::id()));
+        std::fs::create_dir_all(&dir)?;
+        let path = dir.join("impolite.log");
+
+        let mut file = RotatingFile::with_max_len(&path, 10)?;
+        for line in ["first\n", "second\n", "third\n", "fourth\n", "fifth\n"] {
+            file.write_all(line.as_bytes())?;
+        }
+        let read = |path| std::fs::read_to_string(path).unwrap_or_default();
+        let (current, one, two, three, four) = (
+            read(path.clone()),
+            read(rotated(&path, 1)),
+            read(rotated(&path, 2)),
+            read(rotated(&path, 3)),
+            read(rotated(&path, 4)),
+        );
+        std::fs::remove_dir_all(&dir)?;
+
+        assert_eq!(current, "fifth\n");
+        assert_eq!(one, "fourth\n");
+        assert_eq!(two, "third\n");
+        assert_eq!(three, "second\n");
+        assert_eq!(four, "");
+
+        Ok(())
+    }
+}
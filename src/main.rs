@@ -13,112 +13,997 @@ use freedesktop_desktop_entry::DesktopEntry;
 use mana_tui::mana_tui_potion::Effect;
 use mana_tui::mana_tui_potion::Message;
 use mana_tui::mana_tui_potion::focus::handlers::On;
-use mana_tui::mana_tui_utils::key;
-use ratatui::crossterm::event::KeyModifiers;
+use ratatui::text::Line;
 use ratatui::text::Span;
-use std::borrow::Cow;
-use std::net::hostname;
+use ratatui::text::Text;
+#[cfg(feature = "images")]
+use ratatui_image::StatefulImage;
+use serde::Serialize;
+use std::path::Path;
+use std::path::PathBuf;
 use std::pin::Pin;
 use std::sync::Arc;
 use std::sync::Mutex;
+use std::sync::MutexGuard;
+use std::sync::PoisonError;
+use std::time::Duration;
+use std::time::Instant;
+use std::time::SystemTime;
 use tokio::io::AsyncRead;
 use tokio::io::BufReader;
-use tokio::io::BufWriter;
 use tokio::net::unix;
 use tokio::select;
 use tui_input::Input;
-use tui_input::backend::crossterm::EventHandler;
+use tui_input::InputRequest;
 
 use tracing_error::ErrorLayer;
+use tracing_subscriber::EnvFilter;
+use tracing_subscriber::filter::LevelFilter;
 use tracing_subscriber::prelude::*;
 
 use ratatui::crossterm::event;
+use ratatui::crossterm::terminal;
 
 use mana_tui::mana_tui_potion;
 use mana_tui::prelude::*;
 
+use crate::animation::Easing;
+use crate::animation::Subscription;
+use crate::animation::Ticker;
+use crate::animation::Tween;
+use crate::auth::AuthProgress;
+use crate::avatar::Avatar;
+use crate::backdrop::Backdrop;
+use crate::background::Placement;
+use crate::backoff::Backoff;
+use crate::clock::ClockPosition;
+use crate::complete::Completion;
+use crate::config::Config;
+use crate::config::PowerConfig;
+use crate::config::SymbolsConfig;
+use crate::config::ThemeConfig;
+use crate::event_log::EventLog;
 use crate::greetd::ErrorType;
 use crate::greetd::GreetdWrite;
 use crate::greetd::greetd_connect;
 use crate::greetd::greetd_decode;
-use crate::lipgloss_colors::LIPGLOSS;
+use crate::help::Binding;
+use crate::history::History;
+use crate::idle::IdleLevel;
+use crate::json_events::JsonEvents;
+use crate::json_events::Observed;
+use crate::keyboard::Keyboard;
+use crate::keymap::Action;
+use crate::keymap::EscapeAction;
+use crate::keymap::KeyCombo;
+use crate::keymap::KeyMap;
+use crate::keymap::Scope;
+use crate::layout::Breakpoint;
+use crate::metrics::Metrics;
+use crate::modal::Modal;
+use crate::network::Connectivity;
+use crate::os_release::OsRelease;
+use crate::palette::Command as PaletteCommand;
+use crate::palette::Palette;
+use crate::power::PowerAction;
+use crate::power::Scheduled;
+use crate::readline::PastePolicy;
+use crate::secret::SecretInput;
+use crate::status_bar::SegmentContext;
+use crate::status_bar::SegmentKind;
+use crate::styling::GradientTarget;
+use crate::term::Appearance;
+use crate::term::AppearancePreference;
+use crate::term::ColorDepth;
+use crate::theme::Theme;
+use crate::theme::ThemePreset;
+use crate::throttle::Throttle;
+use crate::toast::ToastLevel;
+use crate::toast::Toasts;
+use crate::typewriter::Typewriter;
+use crate::users::InvalidUsername;
+use crate::users::User;
+use crate::vi::Vi;
+use crate::widgets::WidgetPosition;
 
+pub mod accounts;
+pub mod animation;
+pub mod audit;
+pub mod auth;
+pub mod avatar;
+pub mod backdrop;
+pub mod background;
+pub mod backlight;
+pub mod backoff;
+pub mod banner;
+pub mod battery;
+pub mod clock;
+pub mod color;
+pub mod complete;
+pub mod config;
+pub mod console_keymap;
+pub mod crash;
+pub mod demo;
+pub mod disk;
+pub mod doctor;
+pub mod event_log;
+#[cfg(any(test, feature = "test-util"))]
+pub mod fake_greetd;
+pub mod graph;
 pub mod greetd;
+pub mod harden;
+pub mod help;
+pub mod history;
+pub mod hostname;
+pub mod idle;
+pub mod issue;
+pub mod json_events;
+pub mod keyboard;
+pub mod keymap;
+pub mod layout;
 #[path = "lipgloss-colors.rs"]
 pub mod lipgloss_colors;
+pub mod log_file;
+pub mod metrics;
+pub mod modal;
+pub mod motd;
+pub mod network;
+pub mod nologin;
+pub mod os_release;
+pub mod palette;
+pub mod power;
+pub mod queue;
+pub mod readline;
+pub mod replay;
+pub mod sandbox;
+pub mod script;
+pub mod secret;
+pub mod snapshot;
+pub mod status_bar;
+pub mod storage;
+pub mod styling;
+pub mod term;
+pub mod theme;
+pub mod throttle;
+pub mod toast;
+pub mod typewriter;
+pub mod uptime;
+pub mod users;
+pub mod vi;
+#[cfg(feature = "images")]
+pub mod wallpaper;
+pub mod widgets;
 
 pub type Str = Arc<str>;
 
 #[derive(clap::Parser)]
 struct CliArgs {
+    /// Allow quitting, keep running without greetd if it can't be reached, and leave core
+    /// dumps and swapping of the greeter's memory on
     #[arg(short, long)]
     debug: bool,
+    /// Path to the config file [default: /etc/impolite/config.toml]
+    #[arg(short, long)]
+    config: Option<PathBuf>,
+    /// Try every screen without a login manager: a fake greetd, machine name and session list.
+    /// Any username logs in with the password `demo`, except `expired`, whose account is
+    /// turned away
+    #[arg(long)]
+    demo: bool,
+    /// Show /etc/issue above the form with its agetty escapes expanded
+    #[arg(long)]
+    issue: bool,
+    /// Where to write the log, rotated once it reaches 1 MiB [default: /var/log/impolite.log,
+    /// or /var/cache/impolite/impolite.log if that can't be written]
+    #[arg(long, global = true)]
+    log_file: Option<PathBuf>,
+    /// How each line of the log is written
+    #[arg(long, value_enum, default_value_t, global = true)]
+    log_format: LogFormat,
+    /// Log more: debug with -v, everything with -vv. RUST_LOG directives take precedence
+    #[arg(short, long, action = clap::ArgAction::Count, global = true, conflicts_with = "quiet")]
+    verbose: u8,
+    /// Log less: warnings with -q, only errors with -qq
+    #[arg(short, long, action = clap::ArgAction::Count, global = true)]
+    quiet: u8,
+    /// Draw the UI once without a terminal, print it and exit, for screenshots of themes and
+    /// diffing changes to how it looks
+    #[arg(long)]
+    render_once: bool,
+    /// The state of the form to draw with --render-once
+    #[arg(long, value_enum, default_value_t, requires = "render_once")]
+    state: SnapshotState,
+    /// Keep the colors of --render-once as ANSI escapes
+    #[arg(long, requires = "render_once")]
+    ansi: bool,
+    /// Columns of the screen --render-once draws
+    #[arg(long, default_value_t = 100, requires = "render_once")]
+    width: u16,
+    /// Rows of the screen --render-once draws
+    #[arg(long, default_value_t = 30, requires = "render_once")]
+    height: u16,
+    /// Write every key, paste and answer from greetd to this file, with what's typed into the
+    /// password field masked, to attach to a bug report
+    #[arg(long, conflicts_with = "replay")]
+    record: Option<PathBuf>,
+    /// Play back a file written by --record in place of the keyboard and greetd
+    #[arg(long)]
+    replay: Option<PathBuf>,
+    /// Run the steps in this file against the form with a clock of its own, checking what it
+    /// expects along the way, and exit with an error at the first thing that doesn't hold
+    #[arg(long, conflicts_with_all = ["record", "replay", "render_once"])]
+    script: Option<PathBuf>,
+    /// Write every change of state and focus, event log line, toast and dialog to this file
+    /// descriptor as JSON lines, for test harnesses and monitoring
+    #[arg(long, value_name = "FD")]
+    json_events: Option<i32>,
+    /// Write how long the first frame, building each frame and logins took to this file as
+    /// JSON on exit
+    #[arg(long)]
+    metrics_file: Option<PathBuf>,
+    /// Theme preset, overriding the config
+    #[arg(long, global = true)]
+    preset: Option<ThemePreset>,
+    /// Light or dark variant of the theme, overriding the config
+    #[arg(long, global = true)]
+    appearance: Option<AppearancePreference>,
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Debug, Clone, Copy, Default, clap::ValueEnum)]
+enum SnapshotState {
+    #[default]
+    Idle,
+    Authenticating,
+    Error,
+    Picker,
+}
+
+impl SnapshotState {
+    fn form_state(self) -> FormState {
+        match self {
+            SnapshotState::Idle => FormState::Idle,
+            SnapshotState::Authenticating => FormState::CreatedSession,
+            SnapshotState::Error => {
+                FormState::LoginFailed(ErrorType::AuthError, "Authentication failed".into())
+            }
+            SnapshotState::Picker => FormState::PickingDesktop,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, clap::ValueEnum)]
+enum LogFormat {
+    /// For people reading the file
+    #[default]
+    Text,
+    /// One JSON object per event with the fields of its spans, for log collectors
+    Json,
+}
+
+#[derive(clap::Subcommand)]
+enum Command {
+    /// Inspect the configured theme
+    Theme {
+        #[command(subcommand)]
+        command: ThemeCommand,
+    },
+    /// Check that greetd, the config, sessions and the terminal are set up for the greeter,
+    /// and say how to fix what isn't
+    Doctor,
+    /// Print how the login form moves between states on greetd's responses, as a Graphviz
+    /// graph
+    #[command(hide = true)]
+    Graph,
+    /// Run the UI against a fake greetd, to design themes without a login manager
+    ///
+    /// Any username logs in with the password `demo`. PageUp and PageDown step through
+    /// the idle, authenticating, error and session picker states.
+    Preview {
+        /// Theme file to preview, in the same format as a per-user theme
+        #[arg(long)]
+        theme: Option<PathBuf>,
+    },
+}
+
+impl CliArgs {
+    /// Running against the fake greetd, with `preview` or `--demo`.
+    fn is_preview(&self) -> bool {
+        self.demo || matches!(self.command, Some(Command::Preview { .. }))
+    }
+
+    /// Whether a fake greetd answers, so logins aren't real and nothing about them is kept.
+    fn is_pretend(&self) -> bool {
+        self.is_preview() || self.replay.is_some() || self.script.is_some()
+    }
+
+    /// What gets logged: the level the flags pick, refined per target by `RUST_LOG`.
+    fn log_filter(&self) -> EnvFilter {
+        let level = match self.verbose as i16 - self.quiet as i16 {
+            ..=-2 => LevelFilter::ERROR,
+            -1 => LevelFilter::WARN,
+            0 => LevelFilter::INFO,
+            1 => LevelFilter::DEBUG,
+            2.. => LevelFilter::TRACE,
+        };
+        EnvFilter::builder()
+            .with_default_directive(level.into())
+            .from_env_lossy()
+    }
+}
+
+#[derive(clap::Subcommand)]
+enum ThemeCommand {
+    /// Write the fully resolved theme as TOML, as a starting point for customizing it
+    Export {
+        /// Where to write the theme [default: stdout]
+        output: Option<PathBuf>,
+    },
+}
+
+/// Loads the config with the command line's overrides applied.
+fn load_config(cli_args: &CliArgs) -> Result<Config> {
+    let mut config = Config::load(cli_args.config.as_deref())?;
+    if let Some(Command::Preview { theme: Some(path) }) = &cli_args.command {
+        config.theme = theme::load_file(path)?;
+    }
+    if let Some(preset) = cli_args.preset {
+        config.theme.preset = preset;
+    }
+    if let Some(appearance) = cli_args.appearance {
+        config.theme.appearance = appearance;
+    }
+    Ok(config)
 }
 
 #[tokio::main(flavor = "current_thread")]
 async fn main() -> Result<()> {
-    color_eyre::install()?;
-    let cli_args = Box::leak(Box::new(CliArgs::parse())) as &'static _;
+    let metrics = Metrics::new(Instant::now());
+    let (panic_hook, eyre_hook) = color_eyre::config::HookBuilder::default().into_hooks();
+    eyre_hook.install()?;
+    term::install_panic_hook(panic_hook);
+    let cli_args = Box::leak(Box::new(CliArgs::parse())) as &'static CliArgs;
+    if let Some(Command::Graph) = &cli_args.command {
+        print!("{}", graph::form_state());
+        return Ok(());
+    }
+    // Before the config is loaded, since a broken one is among what it reports.
+    if let Some(Command::Doctor) = &cli_args.command {
+        let checks = doctor::run(cli_args.config.as_deref()).await;
+        print!("{}", doctor::report(&checks));
+        if checks
+            .iter()
+            .any(|check| check.status == doctor::Status::Fail)
+        {
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+    let config = Box::leak(Box::new(load_config(cli_args)?)) as &'static Config;
+    // Has to happen before the UI owns stdin.
+    let appearance = config.theme.appearance.resolve();
+    if let Some(Command::Theme {
+        command: ThemeCommand::Export { output },
+    }) = &cli_args.command
+    {
+        return theme::export(&config.theme, appearance, output.as_deref());
+    }
+    // While this is the only thread: the log's writer thread and any started after would keep
+    // the capabilities otherwise. Only logged once there is a log to write it to.
+    let dropped = match !cli_args.debug && !cli_args.is_pretend() {
+        true => sandbox::drop_capabilities(),
+        false => Ok(()),
+    };
+    // A log that can't be opened isn't worth refusing to show a login screen over. The guard
+    // is kept until the end, so the log's last lines are flushed.
+    let (log_writer, _log_guard) = log_file::open_non_blocking(cli_args.log_file.as_deref())
+        .ok()
+        .unzip();
+    let log_layer = log_writer.map(|writer| {
+        let layer = tracing_subscriber::fmt::layer()
+            .with_writer(writer)
+            .with_ansi(false);
+        let layer = match cli_args.log_format {
+            LogFormat::Text => layer.boxed(),
+            LogFormat::Json => layer.json().with_span_list(true).boxed(),
+        };
+        layer.with_filter(cli_args.log_filter())
+    });
     let subscriber = tracing_subscriber::Registry::default()
-        // any number of other subscriber layers may be added before or
-        // after the `ErrorLayer`...
+        .with(log_layer)
         .with(ErrorLayer::default());
-
-    // set the subscriber as the default for the application
     tracing::subscriber::set_global_default(subscriber)?;
+    if let Err(err) = dropped {
+        tracing::warn!("failed to drop capabilities: {err}");
+    }
 
-    mana_tui_potion::run()
-        .init(|| init(cli_args))
+    if cli_args.render_once {
+        return render_once(cli_args, config, appearance).await;
+    }
+    let json_events = match cli_args.json_events {
+        Some(fd) => Some(
+            JsonEvents::open(fd).wrap_err_with(|| format!("--json-events can't use fd {fd}"))?,
+        ),
+        None => None,
+    };
+    if let Some(path) = &cli_args.script {
+        let metrics = Metrics::new(Instant::now());
+        let (model, _) = init(cli_args, config, appearance, None, json_events, metrics).await;
+        let steps = script::run(path, model).await?;
+        println!("{}: {steps} steps ok", path.display());
+        return Ok(());
+    }
+    // Preview never sees a real password, and debugging wants core dumps.
+    if !cli_args.debug && !cli_args.is_preview() {
+        harden::harden();
+    }
+    if let Some(path) = &cli_args.replay {
+        replay::attach(path)?;
+    }
+    // Started as late as it can be, so times in the trace line up with a replay's.
+    let recorder = match &cli_args.record {
+        Some(path) => Some(
+            replay::Recorder::create(path)
+                .wrap_err_with(|| format!("failed to create {}", path.display()))?,
+        ),
+        None => None,
+    };
+    term::bracketed_paste(true);
+    let model_metrics = metrics.clone();
+    let result = mana_tui_potion::run()
+        .init(move || {
+            init(
+                cli_args,
+                config,
+                appearance,
+                recorder.clone(),
+                json_events.clone(),
+                model_metrics.clone(),
+            )
+        })
         .view(view)
         .quit_signal(|_, msg| matches!(msg, Msg::Quit))
         .update(update)
         .run()
-        .await?;
+        .await;
+    term::restore();
+    if let Some(path) = &cli_args.metrics_file
+        && let Err(err) = metrics.write(path)
+    {
+        tracing::warn!("{err}");
+    }
+    if let Err(report) = result {
+        eprintln!("Error: {}", secret::scrub(&format!("{report:?}")));
+        std::process::exit(1);
+    }
 
     Ok(())
 }
 
+/// Draws the UI in the state `--state` asks for and prints it, without touching the terminal
+/// or greetd.
+async fn render_once(
+    cli_args: &'static CliArgs,
+    config: &'static Config,
+    appearance: Appearance,
+) -> Result<()> {
+    let metrics = Metrics::new(Instant::now());
+    let (model, _) = init(cli_args, config, appearance, None, None, metrics).await;
+    let (mut model, _) = update(model, Msg::PreviewState(cli_args.state.form_state())).await;
+    // The whole greeting, as it looks once it's typed out.
+    model.typewriter = None;
+    model.size = Some((cli_args.width, cli_args.height));
+    let buf = snapshot::render(view(&model).await, cli_args.width, cli_args.height)?;
+    print!("{}", snapshot::dump(&buf, cli_args.ansi));
+    Ok(())
+}
+
 #[derive(Debug, Clone)]
 enum Msg {
     Quit,
+    /// The greeter got this signal telling it to exit.
+    Terminate(&'static str),
     Error(Arc<color_eyre::Report>),
+    /// Restart greetd after an error.
+    Retry,
+    /// greetd's task failed and is restarted after the delay.
+    GreetdRestarting(Arc<color_eyre::Report>, Duration),
     GreetdRes(greetd::Response),
-    FieldUpdate(Field, Input),
+    UsernameUpdate(Input),
+    PasswordUpdate(SecretInput),
+    /// A vi mode key changed the username field or its mode.
+    ViUpdate(Vi, Input),
+    /// The username was completed.
+    Complete(Completion, Input),
+    /// Give up on the login in progress and go back to the username.
+    CancelSession,
+    /// A remembered username was brought back into the username field.
+    BrowseHistory(History, Input),
+    /// The first key of a chord was pressed, or `None` once the second one was.
+    Chord(Option<KeyCombo>),
     FocusOn(Focus),
     SubmitLogin,
+    /// Answer the question PAM is asking with what was typed in the password field.
+    AnswerPrompt,
+    /// Caps lock was found to be on or off while typing.
+    CapsLock(bool),
+    Toast(Str, ToastLevel),
+    DismissToast(u64),
+    OpenModal(Modal),
+    /// Move the focus between the modal's buttons.
+    ModalFocus(isize),
+    /// Press the modal's focused button.
+    ModalPress,
+    CloseModal,
+    /// Move the user menu's selection.
+    UserMenuMove(isize),
+    /// Fill in this username and go on to the password.
+    PickUser(Str),
+    /// Move the session picker's selection.
+    PickerMove(isize),
+    Power(PowerAction),
+    /// The power actions that can run, once logind was asked.
+    PowerActions(Vec<PowerAction>),
+    /// Move the power menu's selection.
+    PowerMenuMove(isize),
+    /// A shutdown someone scheduled, looked up again every so often.
+    Scheduled(Option<Scheduled>),
+    /// NetworkManager's connectivity changed.
+    Connectivity(Connectivity),
+    /// Change the screen's brightness by this many percent.
+    Brightness(i8),
+    /// Load the next console keymap from the config.
+    NextKeymap,
+    /// This console keymap was loaded.
+    KeymapLoaded(Str),
+    /// Move the on-screen keyboard's selection by rows and columns.
+    KeyboardMove(isize, isize),
+    KeyboardPress,
+    /// New output of the command widget at this index in the config.
+    WidgetOutput(usize, Str),
+    /// Scroll the message of the day by this many lines.
+    ScrollMotd(isize),
+    ToggleLog,
+    ToggleHelp,
+    ToggleDebug,
+    TogglePalette,
+    /// The command palette's query was edited.
+    PaletteQuery(Input),
+    /// Move the command palette's selection.
+    PaletteMove(isize),
+    /// Run the selected command and close the palette.
+    PaletteRun,
+    ReloadConfig,
+    /// Scroll the event log up by this many entries.
+    ScrollLog(isize),
+    /// Jump straight to a form state, in `impolite preview`.
+    PreviewState(FormState),
+    /// The theme of the user being logged in, `None` to go back to the system theme.
+    UserTheme(Option<ThemeConfig>),
 
     Nothing,
     StartShell,
+    Tick,
+    /// A key was pressed while the UI was idle.
+    Wake,
+}
+
+impl Msg {
+    /// Whether this message comes from the user doing something, which resets the idle timer.
+    fn is_input(&self) -> bool {
+        matches!(
+            self,
+            Msg::UsernameUpdate(_)
+                | Msg::PasswordUpdate(_)
+                | Msg::ViUpdate(..)
+                | Msg::Complete(..)
+                | Msg::BrowseHistory(..)
+                | Msg::Chord(_)
+                | Msg::CancelSession
+                | Msg::FocusOn(_)
+                | Msg::SubmitLogin
+                | Msg::AnswerPrompt
+                | Msg::StartShell
+                | Msg::Wake
+                | Msg::OpenModal(_)
+                | Msg::ModalFocus(_)
+                | Msg::ModalPress
+                | Msg::CloseModal
+                | Msg::UserMenuMove(_)
+                | Msg::PickUser(_)
+                | Msg::PickerMove(_)
+                | Msg::Power(_)
+                | Msg::PowerMenuMove(_)
+                | Msg::Brightness(_)
+                | Msg::NextKeymap
+                | Msg::KeyboardMove(..)
+                | Msg::KeyboardPress
+                | Msg::TogglePalette
+                | Msg::PaletteQuery(_)
+                | Msg::PaletteMove(_)
+                | Msg::PaletteRun
+        )
+    }
 }
 
 #[derive(Debug, Clone)]
-#[repr(usize)]
 enum Field {
     Username,
     Password,
 }
 
+impl Field {
+    fn focus(&self) -> Focus {
+        match self {
+            Field::Username => Focus::UsernameField,
+            Field::Password => Focus::PasswordField,
+        }
+    }
+}
+
 impl Message for Msg {
     type Model = Model;
 }
 
 struct Model {
     cli_args: &'static CliArgs,
+    config: &'static Config,
+    hostname: Str,
+    appearance: Appearance,
+    theme: Theme,
     req_tx: Sender<greetd::Request>,
-    fields: [tui_input::Input; 2],
+    /// Kept to hand to a restarted greetd task.
+    req_rx: Receiver<greetd::Request>,
+    /// A background task failed; the error screen is up until the user retries.
+    error: Option<Arc<color_eyre::Report>>,
+    username: Input,
+    password: SecretInput,
     focus: Focus,
     form_state: FormState,
-    last_response: Option<greetd::Response>,
+    auth: AuthProgress,
+    /// The on-screen keyboard, when enabled.
+    keyboard: Option<Keyboard>,
+    /// The field the on-screen keyboard types into.
+    keyboard_target: Field,
+    /// Vi mode of the username field, when enabled.
+    vi: Option<Vi>,
+    /// Accounts the username completes to, empty without completion.
+    usernames: Vec<Str>,
+    completion: Completion,
+    history: History,
+    /// The first key of a chord, while waiting for the second.
+    chord: Option<KeyCombo>,
+    login_throttle: Throttle,
+    /// Why the last login wasn't sent, until the username is edited.
+    username_hint: Option<InvalidUsername>,
+    event_log: EventLog,
+    /// Where events and greetd's answers go with `--record`.
+    recorder: Option<replay::Recorder>,
+    /// Where changes are mirrored with `--json-events`.
+    json_events: Option<JsonEvents>,
+    metrics: Metrics,
+    /// The script's clock with `--script`, `None` for the real one.
+    clock: Option<Instant>,
+    /// The size drawn at with `--render-once`, `None` for the terminal's.
+    size: Option<(u16, u16)>,
+    /// Told to exit by a signal, and only waiting for greetd to answer the cancel.
+    terminating: bool,
+    help_open: bool,
+    debug_open: bool,
+    palette: Option<Palette>,
+    log_open: bool,
+    /// How far the log is scrolled up from its newest entry.
+    log_scroll: usize,
     desktops: Vec<DesktopEntry>,
-    dekstop_picker_state: Arc<Mutex<ListState>>,
+    /// Index into `desktops` of the session picker's selection.
+    desktop_selected: usize,
+    /// Index into `desktops` of the first session the picker shows.
+    desktop_offset: usize,
+    background: Option<Text<'static>>,
+    motd: Vec<Line<'static>>,
+    issue: Vec<Line<'static>>,
+    os_release: Option<OsRelease>,
+    toasts: Toasts,
+    modal: Option<Modal>,
+    /// Users to pick from, empty without the user menu.
+    users: Vec<User>,
+    user_menu_selected: usize,
+    /// The power actions that can run, none until logind answers.
+    power_actions: Vec<PowerAction>,
+    /// Index into `power_actions` of the power menu's selection.
+    power_selected: usize,
+    /// Shown above the form until it is cancelled or happens.
+    scheduled: Option<Scheduled>,
+    /// Why logins are disabled, read at startup and again with every login.
+    nologin: Option<String>,
+    /// Whose avatar is shown, and the avatar itself.
+    avatar: Option<(Str, Avatar)>,
+    /// Detected once for the status bar, then whichever was switched to.
+    keymap: Option<String>,
+    seat: Option<String>,
+    /// Kept up to date by NetworkManager, `None` without it.
+    connectivity: Option<Connectivity>,
+    caps_lock: bool,
+    /// Latest output of each command widget, `None` until it first ran.
+    widget_outputs: Vec<Option<Str>>,
+    motd_scroll: usize,
+    backdrop: Option<Backdrop>,
+    typewriter: Option<Typewriter>,
+    /// The one source of ticks, see [`Ticker`].
+    ticker: Ticker,
+    /// Keeps the backdrop's ticks going.
+    backdrop_ticks: Option<Subscription>,
+    focus_fade: Tween<f32>,
+    shake: Option<Tween<f32>>,
+    /// When the pending login was submitted, and the ticks for its spinner, `None` with
+    /// animations off.
+    spinner: Option<(Instant, Option<Subscription>)>,
+    last_input: Instant,
+    idle: IdleLevel,
+    /// The form was cleared for being left alone, see [`config::IdleConfig::clear_after_secs`].
+    idle_cleared: bool,
+    /// Fading out after going idle, `None` while active.
+    dim: Option<Tween<f32>>,
+    /// Whether the last message changed anything on screen, see [`view`].
+    dirty: bool,
+    /// The view built last and the terminal size it was built for.
+    last_view: Mutex<Option<((u16, u16), View)>>,
+    /// Keeps the once a second idle check going.
+    idle_ticks: Option<Subscription>,
+    /// Keeps the clock and the status segments that change with time ticking on the minute,
+    /// or whatever interval they're set to.
+    clock_ticks: Vec<Subscription>,
+    #[cfg(feature = "images")]
+    wallpaper: Option<wallpaper::Wallpaper>,
 }
 
 impl Model {
-    fn field(&self, field: Field) -> &tui_input::Input {
-        &self.fields[field as usize]
+    fn greeting_len(&self) -> usize {
+        greeting_len(&self.hostname)
+    }
+
+    /// The time, which with `--script` only moves when the script waits.
+    fn now(&self) -> Instant {
+        self.clock.unwrap_or_else(Instant::now)
+    }
+
+    /// Whether the user may quit the greeter, see [`config::QuitConfig`].
+    fn can_quit(&self) -> bool {
+        self.config.quit.enabled || self.cli_args.debug || self.cli_args.is_preview()
+    }
+
+    /// Wipes the answers and prompts of the login once its session is started, along with a
+    /// modal that could still be holding a paste into the password field.
+    fn forget_secrets(&mut self) {
+        self.password.clear();
+        self.auth.clear();
+        self.modal = None;
+    }
+
+    /// The focus `step` places along from the current one in the order Tab goes through:
+    /// the user menu, if there is one, then the username and the password. `None` past either
+    /// end, unless `input.tab-wraps` is on.
+    fn tab_focus(&self, step: isize) -> Option<Focus> {
+        let order = [Focus::UserMenu, Focus::UsernameField, Focus::PasswordField];
+        let order = match self.users.is_empty() {
+            true => &order[1..],
+            false => &order[..],
+        };
+        let at = order.iter().position(|focus| *focus == self.focus)? as isize + step;
+        let len = order.len() as isize;
+        if !(0..len).contains(&at) && !self.config.input.tab_wraps {
+            return None;
+        }
+        Some(order[at.rem_euclid(len) as usize].clone())
+    }
+
+    /// Puts whoever just logged in at the front of the remembered usernames.
+    fn remember_username(&mut self) {
+        let config = &self.config.remember;
+        let username = self.username.value();
+        if !config.enabled || self.cli_args.is_pretend() || username.is_empty() {
+            return;
+        }
+        let entries = match history::load(&config.path) {
+            Ok(entries) => history::recorded(&entries, username, config.max),
+            // Left for someone to look at rather than written over.
+            Err(err) => {
+                self.event_log
+                    .push(format!("couldn't read the remembered usernames: {err}"));
+                return;
+            }
+        };
+        if let Err(err) = history::save(&config.path, &entries) {
+            self.event_log
+                .push(format!("couldn't remember the username: {err}"));
+        }
+    }
+
+    /// Loads the avatar of whoever is about to log in, if that changed.
+    fn refresh_avatar(&mut self) {
+        if !self.config.avatar.enabled {
+            return;
+        }
+        let user = match self.focus {
+            Focus::UserMenu => self.users.get(self.user_menu_selected),
+            _ => None,
+        };
+        let name = match user {
+            Some(user) => user.name.clone(),
+            None => Str::from(self.username.value()),
+        };
+        if self
+            .avatar
+            .as_ref()
+            .is_some_and(|(shown, _)| *shown == name)
+        {
+            return;
+        }
+        self.avatar = (!name.is_empty()).then(|| {
+            let user = self.users.iter().find(|user| user.name == name);
+            let real_name = user.and_then(|user| user.real_name.as_deref());
+            let icon = user.and_then(|user| user.icon.as_deref());
+            let avatar = Avatar::load(&name, real_name, icon, ColorDepth::detect());
+            (name, avatar)
+        });
+    }
+
+    /// Columns and rows the UI is laid out in.
+    fn size(&self) -> (u16, u16) {
+        self.size
+            .unwrap_or_else(|| terminal::size().unwrap_or((80, 24)))
+    }
+
+    /// Whether the power menu is shown, which takes an action that can run.
+    fn has_power_menu(&self) -> bool {
+        self.config.power.enabled && !self.power_actions.is_empty()
+    }
+
+    /// Back to the screen the greeter starts on, cancelling the login in progress.
+    fn reset(&mut self) {
+        if matches!(
+            self.form_state,
+            FormState::CreatedSession | FormState::PickingDesktop
+        ) {
+            self.send(greetd::Request::CancelSession);
+            self.event_log.push("login cancelled after inactivity");
+            self.audit(audit::Outcome::Cancelled, Some("inactivity"));
+        }
+        self.username = Input::default();
+        self.password.clear();
+        self.form_state = FormState::Idle;
+        self.auth.clear();
+        self.spinner = None;
+        self.completion = Completion::default();
+        self.history.stop_browsing();
+        self.username_hint = None;
+        self.chord = None;
+        self.modal = None;
+        self.palette = None;
+        self.help_open = false;
+        self.user_menu_selected = 0;
+        self.focus = match self.users.is_empty() {
+            true => Focus::UsernameField,
+            false => Focus::UserMenu,
+        };
+        self.refresh_avatar();
+    }
+
+    fn last_view(&self) -> MutexGuard<'_, Option<((u16, u16), View)>> {
+        // Only ever replaced whole, so it holds up after a panic.
+        self.last_view
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Ticks for the spinner, unless animations are off.
+    fn spinner_ticks(&self) -> Option<Subscription> {
+        self.config
+            .animation
+            .enabled
+            .then(|| self.ticker.every(self.config.animation.fps))
+    }
+
+    /// Notes what happened to the login in the audit log, unless it's only pretend.
+    fn audit(&self, outcome: audit::Outcome, reason: Option<&str>) {
+        if !self.cli_args.is_pretend() {
+            audit::log(self.username.value(), outcome, reason);
+        }
+    }
+
+    /// Adds `event` to the `--record` trace, if there is one.
+    fn record(&self, event: &event::Event) {
+        let Some(recorder) = &self.recorder else {
+            return;
+        };
+        // Where the on-screen keyboard moves to gives away what it types.
+        if self.focus.is_keyboard() && matches!(self.keyboard_target, Field::Password) {
+            return;
+        }
+        recorder.event(event, self.focus.is_password_field());
+    }
+
+    /// Sends `req` to greetd, noting it in the event log. Past [`REQUEST_CAPACITY`] waiting
+    /// requests greetd isn't answering, and rather than holding up the UI the login fails and is
+    /// cancelled, with the cancel waiting for room in the background.
+    fn send(&mut self, req: greetd::Request) {
+        self.event_log.request(&req);
+        if let greetd::Request::CreateSession { .. } = req {
+            self.metrics.login_sent();
+        }
+        let req = match self.req_tx.try_send(req) {
+            Ok(()) => return,
+            Err(flume::TrySendError::Full(req)) => req,
+            // The model holds on to the receiving end, so this only happens on the way out.
+            Err(flume::TrySendError::Disconnected(_)) => {
+                tracing::warn!("greetd's channel is closed");
+                return;
+            }
+        };
+        tracing::warn!("greetd isn't keeping up with requests");
+        if !matches!(req, greetd::Request::CancelSession) {
+            self.event_log.push("login failed, greetd isn't answering");
+            self.audit(audit::Outcome::Failure, Some("greetd isn't answering"));
+            self.form_state = FormState::Idle;
+            self.auth.clear();
+            self.spinner = None;
+            self.password.clear();
+            self.modal = Some(
+                Modal::new(
+                    "Login failed",
+                    "greetd isn't answering, so the login was cancelled.",
+                )
+                .button("Close", Msg::Nothing),
+            );
+            self.event_log.request(&greetd::Request::CancelSession);
+        }
+        // Dropping the cancel would leave greetd halfway through a login the form has given up
+        // on, and the next one would fail.
+        let req_tx = self.req_tx.clone();
+        tokio::spawn(async move {
+            _ = req_tx.send_async(greetd::Request::CancelSession).await;
+        });
     }
 }
 
+/// How many lines of a login error are shown before it's cut off.
+const ERROR_LINES: usize = 3;
+/// How many users the user menu shows at once.
+const USER_MENU_HEIGHT: usize = 6;
+/// How many sessions the session picker shows at once.
+const SESSION_PICKER_HEIGHT: usize = 8;
+/// How many of the latest protocol frames the debug panel shows.
+const DEBUG_FRAMES: usize = 4;
+/// How many entries of the event log are shown at once.
+const LOG_HEIGHT: usize = 8;
+
+const GREETING: &str = "Logging into ";
+const FOCUS_FADE: Duration = Duration::from_millis(150);
+const SHAKE: Duration = Duration::from_millis(400);
+/// How many logins go out at once, after which it's one every [`LOGIN_INTERVAL`], so a key
+/// stuck on Enter doesn't keep greetd and PAM busy.
+const LOGIN_BURST: u32 = 3;
+const LOGIN_INTERVAL: Duration = Duration::from_secs(1);
+/// Requests that can wait for greetd. A login has one out at a time, so more than this means
+/// greetd stopped reading.
+const REQUEST_CAPACITY: usize = 16;
+/// How long greetd's task waits before its first restart, doubling up to [`RESTART_MAX`] while
+/// it keeps failing.
+const RESTART_MIN: Duration = Duration::from_millis(500);
+const RESTART_MAX: Duration = Duration::from_secs(30);
+/// A task that ran this long before failing was healthy, and the backoff starts over.
+const RESTART_HEALTHY: Duration = Duration::from_secs(60);
+/// Failures in a row after which the error is shown and restarting is left to the user.
+const RESTART_ATTEMPTS: u32 = 5;
+/// How long a signal to exit waits for greetd to answer the cancel of the login in progress.
+const TERMINATE_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Length in characters of the greeting line, including the padded hostname badge.
+fn greeting_len(hostname: &str) -> usize {
+    GREETING.chars().count() + hostname.chars().count() + 2
+}
+
 #[derive(Debug, Clone)]
 enum FormState {
     Idle,
@@ -129,11 +1014,45 @@ enum FormState {
 
 enum FormEffect {
     None,
-    SendPassword,
+    /// PAM asked a question, answered by the password field or the user.
+    Answer,
+    /// PAM sent something to show, which greetd still wants a reply to.
+    Acknowledge,
     FocusDesktopPicker,
 }
 
 impl FormState {
+    /// What the state is called in `impolite graph` and `--script`.
+    fn name(&self) -> &'static str {
+        match self {
+            FormState::Idle => "idle",
+            FormState::CreatedSession => "created_session",
+            FormState::LoginFailed(..) => "login_failed",
+            FormState::PickingDesktop => "picking_desktop",
+        }
+    }
+
+    /// The state after this one in `impolite preview`, going round all of them.
+    fn preview_cycle(&self, forward: bool) -> Self {
+        let states = [
+            FormState::Idle,
+            FormState::CreatedSession,
+            FormState::LoginFailed(ErrorType::AuthError, "Authentication failed".into()),
+            FormState::PickingDesktop,
+        ];
+        let current = match self {
+            FormState::Idle => 0,
+            FormState::CreatedSession => 1,
+            FormState::LoginFailed(..) => 2,
+            FormState::PickingDesktop => 3,
+        };
+        let next = match forward {
+            true => (current + 1) % states.len(),
+            false => (current + states.len() - 1) % states.len(),
+        };
+        states[next].clone()
+    }
+
     fn update(self, res: greetd::Response) -> (Self, FormEffect) {
         match (self, res) {
             (FormState::Idle, _) => (FormState::Idle, FormEffect::None),
@@ -150,17 +1069,19 @@ impl FormState {
             (
                 FormState::CreatedSession,
                 greetd::Response::AuthMessage {
-                    auth_message_type: greetd::AuthMessageType::Secret,
+                    auth_message_type:
+                        greetd::AuthMessageType::Visible | greetd::AuthMessageType::Secret,
                     auth_message: _,
                 },
-            ) => (Self::CreatedSession, FormEffect::SendPassword),
+            ) => (Self::CreatedSession, FormEffect::Answer),
             (FormState::CreatedSession, greetd::Response::AuthMessage { .. }) => {
-                (Self::CreatedSession, FormEffect::None)
+                (Self::CreatedSession, FormEffect::Acknowledge)
             }
             (FormState::LoginFailed(_, _), greetd::Response::Success) => {
                 (FormState::PickingDesktop, FormEffect::None)
             }
-            (FormState::LoginFailed(_, _), _) => todo!(),
+            // greetd has nothing more to say about a login that failed.
+            (state @ FormState::LoginFailed(..), _) => (state, FormEffect::None),
             (
                 _,
                 greetd::Response::Error {
@@ -173,14 +1094,38 @@ impl FormState {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
 enum Focus {
+    UserMenu,
     UsernameField,
     PasswordField,
     DesktopPicker,
+    PowerMenu,
+    Keyboard,
 }
 
 impl Focus {
+    #[must_use]
+    fn is_user_menu(&self) -> bool {
+        matches!(self, Self::UserMenu)
+    }
+
+    #[must_use]
+    fn is_power_menu(&self) -> bool {
+        matches!(self, Self::PowerMenu)
+    }
+
+    #[must_use]
+    fn is_desktop_picker(&self) -> bool {
+        matches!(self, Self::DesktopPicker)
+    }
+
+    #[must_use]
+    fn is_keyboard(&self) -> bool {
+        matches!(self, Self::Keyboard)
+    }
+
     /// Returns `true` if the focus is [`UsernameField`].
     ///
     /// [`UsernameField`]: Focus::UsernameField
@@ -198,50 +1143,347 @@ impl Focus {
     }
 }
 
-async fn init(cli_args: &'static CliArgs) -> (Model, Effect<Msg>) {
-    let (req_tx, req_rx) = flume::unbounded();
-    (
-        Model {
-            req_tx: req_tx.clone(),
-            cli_args,
-            focus: Focus::UsernameField,
-            fields: Default::default(),
-            form_state: FormState::Idle,
-            last_response: None,
-            desktops: greetd::get_desktops(),
-            dekstop_picker_state: Arc::new(Mutex::new(ListState::default())),
+async fn init(
+    cli_args: &'static CliArgs,
+    config: &'static Config,
+    appearance: Appearance,
+    recorder: Option<replay::Recorder>,
+    json_events: Option<JsonEvents>,
+    metrics: Metrics,
+) -> (Model, Effect<Msg>) {
+    let (req_tx, req_rx) = flume::bounded(REQUEST_CAPACITY);
+    let theme = Theme::resolve(&config.theme, appearance);
+    let mut warnings: Vec<Str> = Vec::new();
+    let uids = || {
+        let defaults = users::uid_range();
+        let min = config.user_menu.uid_min.unwrap_or(*defaults.start());
+        let max = config.user_menu.uid_max.unwrap_or(*defaults.end());
+        min..=max
+    };
+    let mut users = match config.user_menu.enabled {
+        true => users::list(uids()),
+        false => Vec::new(),
+    };
+    if !users.is_empty() {
+        accounts::enrich(&mut users).await;
+    }
+    let usernames = match (config.input.complete_usernames, config.user_menu.enabled) {
+        (false, _) => Vec::new(),
+        (true, true) => users.iter().map(|user| user.name.clone()).collect(),
+        (true, false) => users::list(uids())
+            .into_iter()
+            .map(|user| user.name)
+            .collect(),
+    };
+    let background =
+        config
+            .background
+            .path
+            .as_deref()
+            .and_then(|path| match background::load(path) {
+                Ok(text) => Some(text),
+                Err(_) => {
+                    warnings.push("couldn't load background art".into());
+                    None
+                }
+            });
+    #[cfg(feature = "images")]
+    let wallpaper =
+        config
+            .background
+            .image
+            .as_deref()
+            .and_then(|path| match wallpaper::load(path) {
+                Ok(wallpaper) => wallpaper,
+                Err(_) => {
+                    warnings.push("couldn't load the wallpaper".into());
+                    None
+                }
+            });
+    let motd = match config.motd.enabled {
+        true => motd::load(&config.motd.paths).unwrap_or_else(|_| {
+            warnings.push("couldn't read the message of the day".into());
+            Vec::new()
+        }),
+        false => Vec::new(),
+    };
+    let issue = match cli_args.issue {
+        true => issue::load(Path::new(issue::ISSUE_PATH), &issue::IssueContext::detect())
+            .unwrap_or_default(),
+        false => Vec::new(),
+    };
+    let backdrop = match (&background, config.background.effect) {
+        (None, Some(effect)) if ColorDepth::detect() >= ColorDepth::Ansi256 => Some(Backdrop::new(
+            effect,
+            config.background.width,
+            config.background.height,
+            config.background.fps,
+            &theme,
+        )),
+        _ => None,
+    };
+    let ticker = Ticker::new();
+    let backdrop_ticks = backdrop
+        .as_ref()
+        .map(|_| ticker.every(config.background.fps));
+    let hostname = match cli_args.demo {
+        true => demo::HOSTNAME.into(),
+        false => hostname::load().await,
+    };
+    let typewriter = config.greeting.typewriter.then(|| {
+        Typewriter::start(
+            greeting_len(&hostname),
+            Duration::from_millis(config.greeting.typewriter_delay_ms),
+        )
+    });
+    if let Some(typewriter) = typewriter {
+        ticker.play(typewriter.duration(), config.animation.fps);
+    }
+    let idle_ticks = (config.idle.dim_after_secs.is_some()
+        || config.idle.blank_after_secs.is_some()
+        || config.idle.clear_after_secs.is_some())
+    .then(|| ticker.every(1));
+    let shown = |enabled: bool, kind: SegmentKind| enabled || config.status_bar.shows(kind);
+    let clock_interval = shown(config.clock.enabled, SegmentKind::Clock)
+        .then(|| Duration::from_secs(config.clock.interval_secs));
+    let battery_interval = shown(config.battery.enabled, SegmentKind::Battery)
+        .then(|| Duration::from_secs(config.battery.interval_secs));
+    let network_interval = shown(config.network.enabled, SegmentKind::Network)
+        .then(|| Duration::from_secs(config.network.interval_secs));
+    let disk_interval = shown(config.disk.enabled, SegmentKind::Disk)
+        .then(|| Duration::from_secs(config.disk.interval_secs));
+    let uptime_interval = shown(config.uptime.enabled, SegmentKind::Uptime)
+        .then(|| Duration::from_secs(config.uptime.interval_secs));
+    let clock_ticks = [
+        clock_interval,
+        battery_interval,
+        network_interval,
+        disk_interval,
+        uptime_interval,
+    ]
+    .into_iter()
+    .flatten()
+    .map(|interval| ticker.wall(interval))
+    .collect();
+    let mut model = Model {
+        req_tx: req_tx.clone(),
+        req_rx: req_rx.clone(),
+        error: None,
+        cli_args,
+        config,
+        hostname,
+        appearance,
+        theme,
+        focus: match users.is_empty() {
+            true => Focus::UsernameField,
+            false => Focus::UserMenu,
+        },
+        users,
+        user_menu_selected: 0,
+        avatar: None,
+        power_actions: Vec::new(),
+        power_selected: 0,
+        scheduled: None,
+        nologin: nologin::read(),
+        username: Input::default(),
+        password: SecretInput::default(),
+        form_state: FormState::Idle,
+        auth: AuthProgress::default(),
+        keyboard: config.keyboard.enabled.then(Keyboard::default),
+        keyboard_target: Field::Username,
+        vi: config.input.vi_mode.then(Vi::default),
+        usernames,
+        completion: Completion::default(),
+        chord: None,
+        login_throttle: Throttle::new(LOGIN_BURST, LOGIN_INTERVAL),
+        username_hint: None,
+        history: History::new(match config.remember.enabled {
+            true => history::load(&config.remember.path).unwrap_or_default(),
+            false => Vec::new(),
+        }),
+        event_log: EventLog::default(),
+        recorder,
+        json_events,
+        metrics,
+        clock: None,
+        size: None,
+        terminating: false,
+        help_open: false,
+        debug_open: false,
+        palette: None,
+        log_open: false,
+        log_scroll: 0,
+        desktops: match cli_args.demo {
+            true => demo::sessions(),
+            false => greetd::get_desktops(),
         },
+        desktop_selected: 0,
+        desktop_offset: 0,
+        background,
+        motd,
+        motd_scroll: 0,
+        issue,
+        widget_outputs: vec![None; config.widgets.len()],
+        toasts: Toasts::default(),
+        modal: None,
+        keymap: (config.status_bar.shows(SegmentKind::Keymap)
+            || !config.console_keymaps.keymaps.is_empty())
+        .then(status_bar::detect_keymap)
+        .flatten(),
+        seat: config
+            .status_bar
+            .shows(SegmentKind::Seat)
+            .then(status_bar::detect_seat)
+            .flatten(),
+        connectivity: None,
+        caps_lock: false,
+        os_release: config
+            .os_info
+            .enabled
+            .then(|| OsRelease::load().unwrap_or_default()),
+        backdrop,
+        typewriter,
+        ticker: ticker.clone(),
+        backdrop_ticks,
+        focus_fade: Tween::new(1.0, 1.0, Duration::ZERO, Easing::Linear),
+        shake: None,
+        spinner: None,
+        last_input: Instant::now(),
+        idle: IdleLevel::Active,
+        idle_cleared: false,
+        dim: None,
+        idle_ticks,
+        clock_ticks,
+        dirty: true,
+        last_view: Mutex::default(),
+        #[cfg(feature = "images")]
+        wallpaper,
+    };
+    model.refresh_avatar();
+    (
+        model,
         Effect::new(move |tx| {
             let req_rx = req_rx.clone();
+            let warnings = warnings.clone();
+            let ticker = ticker.clone();
             async move {
-                if let Err(err) = greetd_task(cli_args, req_rx, tx.clone()).await {
-                    tx.send(Msg::Error(Arc::new(err)))
-                        .wrap_err("Fatal channel error")
-                        .unwrap();
+                let greetd = run_greetd(cli_args, req_rx, tx.clone());
+                let ticks = ticker.run(tx.clone());
+                let network = async {
+                    if network_interval.is_some() {
+                        network::watch(tx.clone()).await;
+                    }
+                };
+                let widgets = widgets::run_all(tx.clone(), &config.widgets);
+                let power = async {
+                    if config.power.enabled {
+                        // A preview shows the whole menu, whatever this machine can do.
+                        let actions = match cli_args.is_preview() {
+                            true => PowerAction::ALL.to_vec(),
+                            false => power::available(&config.power).await,
+                        };
+                        _ = queue::send(&tx, Msg::PowerActions(actions)).await;
+                    }
+                };
+                let scheduled = async {
+                    if !cli_args.is_preview() {
+                        power::watch_scheduled(tx.clone()).await;
+                    }
+                };
+                let terminated = async {
+                    let signal = term::terminated().await;
+                    _ = queue::send(&tx, Msg::Terminate(signal)).await;
+                };
+                let vt = async {
+                    let Some(mut vt) = term::VtSwitching::take() else {
+                        return;
+                    };
+                    loop {
+                        match vt.next().await {
+                            term::VtSwitch::Release => vt.release(),
+                            term::VtSwitch::Acquire => vt.acquire(),
+                        }
+                    }
+                };
+                for warning in warnings {
+                    _ = queue::send(&tx, Msg::Toast(warning, ToastLevel::Warning)).await;
                 }
+                tokio::join!(
+                    greetd, ticks, network, widgets, power, scheduled, terminated, vt
+                );
             }
         }),
     )
 }
 
+/// Runs [`greetd_task`], restarting it with a backoff when it fails instead of taking the
+/// greeter down. Only after [`RESTART_ATTEMPTS`] failures in a row is the error shown.
+async fn run_greetd(
+    cli_args: &'static CliArgs,
+    req_rx: Receiver<greetd::Request>,
+    tx: Sender<Msg>,
+) {
+    let mut backoff = Backoff::new(RESTART_MIN, RESTART_MAX);
+    let mut failures = 0;
+    loop {
+        let started = Instant::now();
+        let Err(err) = greetd_task(cli_args, req_rx.clone(), tx.clone()).await else {
+            return;
+        };
+        if started.elapsed() >= RESTART_HEALTHY {
+            backoff.reset();
+            failures = 0;
+        }
+        failures += 1;
+        let err = Arc::new(err);
+        if failures >= RESTART_ATTEMPTS {
+            _ = queue::send(&tx, Msg::Error(err)).await;
+            return;
+        }
+        let delay = backoff.delay();
+        // Nothing is left to report to once the UI is gone.
+        if queue::send(&tx, Msg::GreetdRestarting(err, delay))
+            .await
+            .is_err()
+        {
+            return;
+        }
+        tokio::time::sleep(delay).await;
+    }
+}
+
 async fn greetd_task(
     cli_args: &'static CliArgs,
     req_rx: Receiver<greetd::Request>,
     tx: Sender<Msg>,
 ) -> Result<()> {
+    if cli_args.is_preview() {
+        return demo::run(req_rx, tx).await;
+    }
+    if let Some(path) = &cli_args.replay {
+        return replay::answer(path, req_rx, tx).await;
+    }
     let mut greetd = greetd_connect().await;
     let mut greetd = match (greetd, cli_args.debug) {
         (Ok(greetd), _) => Some(greetd),
         (Err(_), true) => None,
         (Err(err), false) => return Err(err),
     };
+    // Everything it needs is open by now, the TTY included.
+    if greetd.is_some() && !cli_args.debug {
+        sandbox::enter();
+    }
+    relay(greetd, req_rx, tx).await
+}
 
-    struct GreetdStream(
-        Option<(
-            BufWriter<unix::OwnedWriteHalf>,
-            BufReader<unix::OwnedReadHalf>,
-        )>,
-    );
+/// Passes requests on to `greetd` and its responses back to the UI. Without a connection,
+/// requests go nowhere and nothing answers them.
+async fn relay(
+    greetd: Option<tokio::net::UnixStream>,
+    req_rx: Receiver<greetd::Request>,
+    tx: Sender<Msg>,
+) -> Result<()> {
+    struct GreetdStream(Option<(unix::OwnedWriteHalf, BufReader<unix::OwnedReadHalf>)>);
 
     impl AsyncRead for GreetdStream {
         fn poll_read(
@@ -260,8 +1502,9 @@ async fn greetd_task(
         Some(greetd) => {
             let (read, write) = greetd.into_split();
             let greetd_read = BufReader::new(read);
-            let greetd_write = BufWriter::new(write);
-            GreetdStream(Some((greetd_write, greetd_read)))
+            // Unbuffered, so no copy of a request with the password in it is left in a buffer.
+            // Each one goes out in a single write anyway.
+            GreetdStream(Some((write, greetd_read)))
         }
         None => GreetdStream(None),
     };
@@ -275,260 +1518,2555 @@ async fn greetd_task(
                         .wrap_err("error writing request to greetd socket")?;
                 }
             }
-            Ok(res) = greetd_decode(&mut stream) => {
-                tx.send_async(Msg::GreetdRes(res)).await?;
+            res = greetd_decode(&mut stream) => {
+                let res = res.wrap_err("error reading from greetd socket")?;
+                queue::send(&tx, Msg::GreetdRes(res)).await?;
             }
         }
     }
 }
 
+/// The screen, built again only when the last message changed the model or the terminal was
+/// resized. The runtime draws after every message, and only what differs from the frame
+/// before reaches the terminal, so drawing the same view again costs no output.
 async fn view(model: &Model) -> View {
-    let hostname = hostname();
-    let hostname = hostname
-        .as_ref()
-        .map(|str| str.to_string_lossy())
-        .unwrap_or_else(|_| Cow::Borrowed("machine"));
-    let last_response = &model.last_response;
-    let form_state = &model.form_state;
-
-    ui! {
-        <Block
-            On::new(|_, event| {
-                match event {
-                    key!(Char('c'), KeyModifiers::CONTROL) => Some((Msg::Quit, Effect::none())),
-                    _ => None
-                }
-            })
-            Center
-            Width::grow()
-            Height::grow()
-        >
-            <Block Gap(1)>
-                <Block Direction::Horizontal>
-                    <Span>"Logging into "</Span>
-                    <Span .style={Style::new().bg(LIPGLOSS[0][13]).fg(Color::Black)}>" {hostname} "</Span>
-                </Block>
-                <FieldInput
-                    .field={Field::Username}
-                    .state={&model.fields[Field::Username as usize]}
-                    .label="Username"
-                    .focused={model.focus.is_username_field()}
-                    On::new(|model: &Model, event| {
-                        if !model.focus.is_username_field() {
-                            return None;
-                        }
-                        match event {
-                            key!(Tab)
-                            | key!(Char('j' | 'J'), KeyModifiers::CONTROL)
-                            | key!(Down)
-                            | key!(Enter) => Some((Msg::FocusOn(Focus::PasswordField), Effect::none())),
-                            _ => None
-                        }
-                    })
-                />
-                <FieldInput
-                    .field={Field::Password}
-                    .state={&model.fields[Field::Password as usize]}
-                    .label="Password"
-                    .focused={model.focus.is_password_field()}
-                    .secret=true
-                    On::new(|model: &Model, event| {
-                        if !model.focus.is_password_field() {
-                            return None;
-                        }
-                        match event {
-                            key!(Enter) => Some((Msg::SubmitLogin, Effect::none())),
-                            key!(Tab)
-                            | key!(Char('k' | 'K'), KeyModifiers::CONTROL)
-                            | key!(Up) => Some((Msg::FocusOn(Focus::UsernameField), Effect::none())),
-                            _ => None
-                        }
-                    })
-                />
-                <Maybe
-                    .cond={matches!(model.form_state, FormState::PickingDesktop)}
-                    .then={ui!{
-                      <DesktopPicker .model={model}/>
-                    }}
-                />
-                <Span>"{last_response:?}:{form_state:?}"</Span>
-                <HelpSection Padding::new(0, 0, 4, 0)/>
-            </Block>
-        </Block>
+    let size = model.size();
+    if !model.dirty
+        && let Some((built_for, view)) = &*model.last_view()
+        && *built_for == size
+    {
+        return view.clone();
     }
+    let started = Instant::now();
+    let view = screen(model).await;
+    model.metrics.frame(started.elapsed());
+    *model.last_view() = Some((size, view.clone()));
+    view
 }
 
-#[subview]
-fn field_input(
-    field: Field,
-    state: &Input,
-    label: &str,
-    focused: bool,
-    #[builder(default)] secret: bool,
-) -> View {
-    let value = match secret {
-        false => Cow::Borrowed(state.value()),
-        true => Cow::Owned("*".repeat(state.value().len())),
+async fn screen(model: &Model) -> View {
+    let hostname = &model.hostname;
+    let theme = match model.dim {
+        Some(dim) => model.theme.fade(model.theme.dim, dim.value()),
+        None => model.theme,
     };
-    let new_state = state.clone();
-    let label_style = match focused {
-        true => Style::new().fg(LIPGLOSS[6][11]),
-        false => Style::new().dim(),
-    };
-    let input_style = match focused {
-        true => Style::new().bold(),
-        false => Style::new().dim().bold(),
-    };
-    let label = match focused {
-        true => format!("| {label}"),
-        false => format!("  {label}"),
+    // Everything behind a modal or the help is dimmed like an idle screen.
+    let modal_theme = theme;
+    let theme = match model.modal.is_some() || model.help_open || model.palette.is_some() {
+        true => theme.fade(theme.dim, idle::DIM_AMOUNT),
+        false => theme,
     };
-    ui! {
-        <Block
-            Direction::Horizontal
-        >
-            <Span .style={label_style}>"{label} "</Span>
-            <Span .style={input_style}
-                On::new(move |_, event| -> Option<(Msg, _)> {
-                    if !focused {
-                        return None;
-                    }
-                    let mut new_state = new_state.clone();
-                    match new_state.handle_event(event) {
-                        Some(_) => Some((Msg::FieldUpdate(field.clone(), new_state), Effect::none())),
+    let (cols, rows) = model.size();
+    // Views are rebuilt on every event, a resize included, so this goes away on its own.
+    if layout::too_small(cols, rows) {
+        let (min_cols, min_rows) = (layout::MIN_COLS, layout::MIN_ROWS);
+        return ui! {
+            <Block
+                Center
+                Width::grow()
+                Height::grow()
+                On::new(|model: &Model, event| {
+                    model.record(event);
+                    match model.config.keys.action(Scope::Global, event) {
+                        Some(Action::Quit) => model.can_quit().then_some((Msg::Quit, Effect::none())),
                         _ => None,
                     }
                 })
             >
-                "{value}"
-            </Span>
-        </Block>
+                <Span .style={Style::new().fg(theme.warning).bold()}>"terminal too small"</Span>
+                <Span .style={Style::new().fg(theme.text)}>"resize to at least {min_cols}x{min_rows}"</Span>
+                <Span .style={Style::new().fg(theme.text_dim)}>"now {cols}x{rows}"</Span>
+            </Block>
+        };
     }
-}
-
-#[subview]
-fn maybe(cond: bool, then: View, r#else: Option<View>) -> View {
-    if cond {
-        then
-    } else {
-        r#else.unwrap_or(ui! { "" })
+    let breakpoint = Breakpoint::new(cols, rows);
+    let banner = match model.config.banner.enabled && breakpoint.banner() {
+        true => {
+            let text = model.config.banner.text.as_deref().unwrap_or(hostname);
+            banner::fit(
+                text,
+                model.config.banner.font,
+                cols.saturating_sub(4) as usize,
+                (rows / 3) as usize,
+            )
+        }
+        false => None,
     }
-}
+    .unwrap_or_default();
+    let banner = banner.into_iter().map(|line| {
+        let spans = styling::gradient(
+            &line,
+            theme.banner_from,
+            theme.banner_to,
+            Style::new(),
+            GradientTarget::Fg,
+        );
+        ui! { <SpanLine .spans={spans}/> }
+    });
+    let visible = match model.typewriter {
+        Some(typewriter) => typewriter.visible(model.greeting_len()),
+        None => model.greeting_len(),
+    };
+    let greeting = GREETING.chars().take(visible).collect::<String>();
+    let hostname = format!(" {hostname} ")
+        .chars()
+        .take(visible.saturating_sub(GREETING.chars().count()))
+        .collect::<String>();
+    let hostname = styling::gradient(
+        &hostname,
+        theme.badge_from,
+        theme.badge_to,
+        Style::new().fg(theme.badge_fg),
+        GradientTarget::Bg,
+    );
+    let os_info = match &model.os_release {
+        _ if breakpoint.tiny() => String::new(),
+        Some(os) if model.config.symbols.nerd_fonts => {
+            format!("  {} {}", os.logo(), os.pretty_name())
+        }
+        Some(os) => format!("  {}", os.pretty_name()),
+        None => String::new(),
+    };
+    let focus_fade = model.focus_fade.value();
+    // Mistakes show up as they're typed, an empty username only once a login was tried.
+    let username_hint = match users::check_username(model.username.value()) {
+        Some(InvalidUsername::Empty) if model.username_hint.is_none() => None,
+        invalid => invalid,
+    };
+    let hints = match model.chord {
+        // Only the chords that would do something right now.
+        Some(leader) => model
+            .config
+            .keys
+            .after(Scope::Global, leader)
+            .into_iter()
+            .filter(|(_, action)| go_to(model, *action).is_some())
+            .map(|(key, action)| Binding {
+                keys: key.to_string(),
+                action: action.description(),
+            })
+            .collect(),
+        None => help::hints(&model.config.keys, &hint_actions(model)),
+    };
+    let question = model.auth.question();
+    let (username_label, password_label) = match breakpoint.tiny() {
+        true => ("User", "Pass"),
+        false => ("Username", "Password"),
+    };
+    let password_label = question.map_or(password_label, |prompt| {
+        prompt.text.trim().trim_end_matches(':')
+    });
+    let password_secret =
+        !question.is_some_and(|prompt| matches!(prompt.kind, greetd::AuthMessageType::Visible));
+    let (password_text, password_cursor) = match password_secret {
+        true => model.config.input.password_mask.apply(&model.password),
+        false => (model.password.value(), model.password.cursor()),
+    };
+    let shake = model
+        .shake
+        .map(|shake| animation::shake(shake.value(), 2.0).unsigned_abs() as u16)
+        .unwrap_or_default();
+    let spinner = model
+        .spinner
+        .as_ref()
+        .map(|(since, _)| animation::spinner_frame(*since))
+        .unwrap_or_default();
+    let art = match (&model.background, &model.backdrop) {
+        (Some(text), _) => Some(background::align(text, model.config.background.align)),
+        (None, Some(backdrop)) => Some(backdrop.render()),
+        (None, None) => None,
+    }
+    .map(|lines| ui! { <AnsiArt .lines={lines}/> });
+    #[cfg(feature = "images")]
+    let art = match &model.wallpaper {
+        Some(wallpaper) => Some(ui! {
+            <StatefulImage
+                {wallpaper.clone()}
+                Width::fixed(model.config.background.width)
+                Height::fixed(model.config.background.height)
+            />
+        }),
+        None => art,
+    };
+    let art = art.filter(|_| breakpoint.decorations());
 
-#[subview]
-fn desktop_picker(model: &Model) -> View {
-    let items = model
-        .desktops
+    let segments = SegmentContext {
+        config: model.config,
+        theme,
+        idle: model.idle,
+        caps_lock: model.caps_lock,
+        keymap: model.keymap.as_deref(),
+        seat: model.seat.as_deref(),
+        connectivity: model.connectivity,
+        vi_mode: model
+            .vi
+            .as_ref()
+            .filter(|_| model.focus.is_username_field())
+            .map(Vi::mode),
+    };
+    let segment = |kind: SegmentKind, enabled: bool| {
+        enabled
+            .then(|| kind.segment().render(&segments))
+            .flatten()
+            .map(|span| ui! { <SpanLine .spans={vec![span]}/> })
+    };
+    let clock = segment(SegmentKind::Clock, model.config.clock.enabled);
+    let uptime = segment(SegmentKind::Uptime, model.config.uptime.enabled);
+    let battery = segment(SegmentKind::Battery, model.config.battery.enabled);
+    let network = segment(SegmentKind::Network, model.config.network.enabled);
+    let disk = segment(SegmentKind::Disk, model.config.disk.enabled);
+    let bar = &model.config.status_bar;
+    let status_bar = (bar.enabled && model.error.is_none()).then(|| {
+        ui! {
+            <SegmentBar
+                .left={status_bar::render(&bar.left, &segments)}
+                .center={status_bar::render(&bar.center, &segments)}
+                .right={status_bar::render(&bar.right, &segments)}
+            />
+        }
+    });
+    let toasts = model
+        .toasts
         .iter()
-        .map(|desktop| desktop.path.to_string_lossy().to_string());
-    let list_state = model.dekstop_picker_state.clone();
-    ui! {
-        <Block>
-            "Pick a session"
-            <List
-                .items={items}
-                {model.dekstop_picker_state.clone()}
-                On::new(move |_, event| match event {
-                    key!(Char('j')) | key!(Tab) | key!(Down) => {
-                        list_state.lock().unwrap().select_next();
-                        None
-                    },
-                    key!(Char('k')) | key!(Up) => {
-                        list_state.lock().unwrap().select_previous();
-                        None
-                    },
-                    key!(Char('b')) => Some((Msg::StartShell, Effect::none())),
-                    _ => None
+        .map(|toast| {
+            let (symbol, color) = match toast.level {
+                ToastLevel::Info => ("", theme.text_dim),
+                ToastLevel::Warning => ("! ", theme.warning),
+                ToastLevel::Error => (&*model.config.symbols.error, theme.error),
+            };
+            let text = &toast.text;
+            ui! { <Span .style={Style::new().fg(color)}>"{symbol}{text} "</Span> }
+        })
+        .collect::<Vec<_>>();
+    let (mut header, mut footer) = (Vec::new(), Vec::new());
+    match model.config.clock.position {
+        ClockPosition::Top => header.extend(clock),
+        ClockPosition::Bottom => footer.extend(clock),
+    }
+    footer.extend(uptime);
+    footer.extend(battery);
+    footer.extend(network);
+    footer.extend(disk);
+    for (widget, output) in model.config.widgets.iter().zip(&model.widget_outputs) {
+        let Some(output) = output else {
+            continue;
+        };
+        let view = ui! { <Span .style={Style::new().fg(theme.text_dim)}>"{output}"</Span> };
+        match widget.position {
+            WidgetPosition::Header => header.push(view),
+            WidgetPosition::Footer => footer.push(view),
+        }
+    }
+    if breakpoint.tiny() {
+        header.clear();
+        footer.clear();
+    }
+
+    if model.has_power_menu() {
+        footer.push(ui! {
+            <PowerBar
+                .available={&model.power_actions}
+                .keys={&model.config.keys}
+                .selected={model.power_actions.get(model.power_selected).copied()}
+                .focused={model.focus.is_power_menu()}
+                .theme={theme}
+                On::new(|model: &Model, event| {
+                    if !model.focus.is_power_menu() {
+                        return None;
+                    }
+                    let msg = match model.config.keys.action(Scope::PowerMenu, event)? {
+                        Action::PowerNext => Msg::PowerMenuMove(1),
+                        Action::PowerPrev => Msg::PowerMenuMove(-1),
+                        Action::PowerRun => {
+                            let action = *model.power_actions.get(model.power_selected)?;
+                            request_power(action, &model.config.power)
+                        }
+                        Action::PowerBack if model.keyboard.is_some() => Msg::FocusOn(Focus::Keyboard),
+                        Action::PowerBack => Msg::FocusOn(Focus::PasswordField),
+                        _ => return None,
+                    };
+                    Some((msg, Effect::none()))
                 })
             />
-        </Block>
+        });
     }
-}
-
+    let in_use = |action: Action| match action.scope() {
+        Scope::UserMenu => !model.users.is_empty(),
+        Scope::PowerMenu => {
+            model.has_power_menu()
+                && action
+                    .power()
+                    .is_none_or(|action| model.power_actions.contains(&action))
+        }
+        Scope::Keyboard => model.keyboard.is_some(),
+        Scope::Preview => model.cli_args.is_preview(),
+        _ if matches!(action, Action::BrightnessUp | Action::BrightnessDown) => {
+            model.config.backlight.enabled
+        }
+        _ if action == Action::NextKeymap => !model.config.console_keymaps.keymaps.is_empty(),
+        _ => action != Action::Quit || model.can_quit(),
+    };
+    // The error screen takes over everything else.
+    if model.error.is_some() {
+        header.clear();
+        footer.clear();
+    }
+
+    let greeting = match breakpoint.tiny() {
+        true => ui! {
+            <Block>
+                <Span>"{greeting}"</Span>
+                <SpanLine .spans={hostname}/>
+            </Block>
+        },
+        false => ui! {
+            <Block Direction::Horizontal>
+                <Span>"{greeting}"</Span>
+                <SpanLine .spans={hostname}/>
+                <Span .style={Style::new().fg(theme.text_dim)}>"{os_info}"</Span>
+            </Block>
+        },
+    };
+    let (form_gap, help_gap) = match breakpoint {
+        Breakpoint::Full => (1, 4),
+        Breakpoint::Compact => (1, 1),
+        Breakpoint::Tiny => (0, 1),
+    };
+    let user_menu = (!model.users.is_empty()).then(|| {
+        ui! {
+            <UserMenu
+                .users={&model.users}
+                .selected={model.user_menu_selected}
+                .focused={model.focus.is_user_menu()}
+                .theme={theme}
+                .symbols={&model.config.symbols}
+                On::new(|model: &Model, event| {
+                    if !model.focus.is_user_menu() {
+                        return None;
+                    }
+                    let msg = match model.config.keys.action(Scope::UserMenu, event)? {
+                        Action::UserDown => Msg::UserMenuMove(1),
+                        Action::UserUp => Msg::UserMenuMove(-1),
+                        Action::PickUser => {
+                            let user = model.users.get(model.user_menu_selected)?;
+                            Msg::PickUser(user.name.clone())
+                        }
+                        Action::UserMenuBack => Msg::FocusOn(model.tab_focus(-1)?),
+                        _ => Msg::FocusOn(Focus::UsernameField),
+                    };
+                    Some((msg, Effect::none()))
+                })
+            />
+        }
+    });
+    let desktop_picker = matches!(model.form_state, FormState::PickingDesktop)
+        .then(|| ui! { <DesktopPicker .model={model}/> });
+    let side_column = layout::side_column(&model.config.layout, breakpoint, cols);
+    let (user_menu_below, desktop_picker_below, side) = match side_column {
+        true => (None, None, [user_menu, desktop_picker]),
+        false => (user_menu, desktop_picker, [None, None]),
+    };
+    let scheduled = model.scheduled.as_ref().map(|scheduled| {
+        let text = scheduled.describe(SystemTime::now());
+        ui! { <Span .style={Style::new().fg(theme.warning).bold()}>"{text}"</Span> }
+    });
+    let nologin = model.nologin.as_ref().map(|message| {
+        let lines = message
+            .lines()
+            .map(|line| ui! { <Span .style={Style::new().fg(theme.warning)}>"{line}"</Span> });
+        ui! {
+            <Block>
+                {lines}
+            </Block>
+        }
+    });
+    let form = ui! {
+        <Block Gap(form_gap)>
+            {scheduled}
+            {nologin}
+            <Maybe
+                .cond={!model.issue.is_empty() && breakpoint.decorations()}
+                .then={ui! { <AnsiArt .lines={model.issue.clone()}/> }}
+            />
+            <Block>
+                {banner}
+            </Block>
+            {[greeting]}
+            <Maybe
+                .cond={model.avatar.is_some() && breakpoint.decorations()}
+                .then={ui! { <AvatarBox .avatar={model.avatar.as_ref().map(|(_, avatar)| avatar)} .theme={theme}/> }}
+            />
+            {user_menu_below}
+            <FieldInput
+                .field={Field::Username}
+                .value={model.username.value()}
+                .cursor={model.username.cursor()}
+                .vi={model.vi.as_ref()}
+                .label={username_label}
+                .focused={model.focus.is_username_field()}
+                .highlight={focus_fade}
+                .theme={theme}
+                .symbols={&model.config.symbols}
+                On::new(|model: &Model, event| {
+                    if !model.focus.is_username_field() {
+                        return None;
+                    }
+                    let action = model.config.keys.action(Scope::Form, event)?;
+                    // Vi mode has Esc for itself.
+                    if action == Action::Escape && model.vi.is_none() {
+                        let msg = escape(model, model.config.escape.username)?;
+                        return Some((msg, Effect::none()));
+                    }
+                    let browse = match action {
+                        Action::PrevField => model.history.older(model.username.value()),
+                        Action::NextField => model.history.newer(),
+                        _ => None,
+                    };
+                    if let Some((history, value)) = browse {
+                        return Some((Msg::BrowseHistory(history, Input::new(value)), Effect::none()));
+                    }
+                    match action {
+                        Action::PrevField if model.users.is_empty() => None,
+                        Action::PrevField => Some((Msg::FocusOn(Focus::UserMenu), Effect::none())),
+                        Action::SwitchBack => Some((Msg::FocusOn(model.tab_focus(-1)?), Effect::none())),
+                        Action::SwitchField | Action::Complete => {
+                            match (complete_username(model), action) {
+                                (Some(msg), _) => Some((msg, Effect::none())),
+                                (None, Action::SwitchField) => {
+                                    Some((Msg::FocusOn(model.tab_focus(1)?), Effect::none()))
+                                }
+                                (None, _) => None,
+                            }
+                        }
+                        _ => Some((Msg::FocusOn(Focus::PasswordField), Effect::none())),
+                    }
+                })
+            />
+            <Maybe
+                .cond={username_hint.is_some()}
+                .then={ui! {
+                    <UsernameHint .hint={username_hint} .theme={theme} .symbols={&model.config.symbols}/>
+                }}
+            />
+            <Maybe
+                .cond={!model.completion.candidates().is_empty()}
+                .then={ui! {
+                    <CompletionPopup
+                        .completion={&model.completion}
+                        .theme={theme}
+                        .symbols={&model.config.symbols}
+                    />
+                }}
+            />
+            <FieldInput
+                .field={Field::Password}
+                .value={password_text}
+                .cursor={password_cursor}
+                .label={password_label}
+                .focused={model.focus.is_password_field()}
+                .highlight={focus_fade}
+                .theme={theme}
+                .symbols={&model.config.symbols}
+                Padding::new(shake, 0, 0, 0)
+                On::new(|model: &Model, event| {
+                    if !model.focus.is_password_field() {
+                        return None;
+                    }
+                    match model.config.keys.action(Scope::Form, event)? {
+                        Action::Submit if model.auth.question().is_some() => {
+                            Some((Msg::AnswerPrompt, Effect::none()))
+                        }
+                        Action::Submit => Some((Msg::SubmitLogin, Effect::none())),
+                        Action::SwitchField => Some((Msg::FocusOn(model.tab_focus(1)?), Effect::none())),
+                        Action::SwitchBack | Action::PrevField => {
+                            Some((Msg::FocusOn(Focus::UsernameField), Effect::none()))
+                        }
+                        Action::Escape => Some((escape(model, model.config.escape.password)?, Effect::none())),
+                        Action::NextField if model.keyboard.is_some() => {
+                            Some((Msg::FocusOn(Focus::Keyboard), Effect::none()))
+                        }
+                        Action::NextField if model.has_power_menu() => {
+                            Some((Msg::FocusOn(Focus::PowerMenu), Effect::none()))
+                        }
+                        _ => None
+                    }
+                })
+            />
+            <Maybe
+                .cond={model.spinner.is_some()}
+                .then={ui!{
+                    <Span .style={Style::new().fg(theme.accent)}>"  {spinner} authenticating"</Span>
+                }}
+            />
+            <Maybe
+                .cond={
+                    matches!(model.form_state, FormState::CreatedSession)
+                        && model.auth.indicator().is_some()
+                }
+                .then={ui! {
+                    <AuthSteps .auth={&model.auth} .theme={theme} .symbols={&model.config.symbols}/>
+                }}
+            />
+            {desktop_picker_below}
+            <StatusLine
+                .form_state={&model.form_state}
+                .width={(cols as usize).saturating_sub(8).min(60)}
+                .theme={theme}
+                .symbols={&model.config.symbols}
+                .keys={&model.config.keys}
+            />
+            <Maybe
+                .cond={model.keyboard.is_some()}
+                .then={ui! {
+                    <OnScreenKeyboard
+                        .keyboard={model.keyboard.as_ref()}
+                        .target={&model.keyboard_target}
+                        .focused={model.focus.is_keyboard()}
+                        .theme={theme}
+                        On::new(|model: &Model, event| {
+                            if !model.focus.is_keyboard() {
+                                return None;
+                            }
+                            let msg = match model.config.keys.action(Scope::Keyboard, event)? {
+                                Action::KeyboardUp => Msg::KeyboardMove(-1, 0),
+                                Action::KeyboardDown => Msg::KeyboardMove(1, 0),
+                                Action::KeyboardLeft => Msg::KeyboardMove(0, -1),
+                                Action::KeyboardRight => Msg::KeyboardMove(0, 1),
+                                Action::KeyboardPress => Msg::KeyboardPress,
+                                _ => Msg::FocusOn(model.keyboard_target.focus()),
+                            };
+                            Some((msg, Effect::none()))
+                        })
+                    />
+                }}
+            />
+            <Maybe
+                .cond={!model.motd.is_empty() && breakpoint.decorations()}
+                .then={ui! {
+                    <MotdPanel
+                        .lines={&model.motd}
+                        .scroll={model.motd_scroll}
+                        .height={model.config.motd.height}
+                        .theme={theme}
+                        .keys={&model.config.keys}
+                    />
+                }}
+            />
+            <Maybe
+                .cond={model.log_open}
+                .then={ui! {
+                    <LogPane
+                        .log={&model.event_log}
+                        .scroll={model.log_scroll}
+                        .theme={theme}
+                        .keys={&model.config.keys}
+                    />
+                }}
+            />
+            <Maybe
+                .cond={model.debug_open}
+                .then={ui! { <DebugPanel .model={model} .theme={theme}/> }}
+            />
+            <KeyHints .hints={hints} .leader={model.chord} .theme={theme} Padding::new(0, 0, help_gap, 0)/>
+        </Block>
+    };
+    let side = side.into_iter().flatten().collect::<Vec<_>>();
+    let form = match side.is_empty() {
+        true => form,
+        false => ui! {
+            <Block Direction::Horizontal Gap(4)>
+                {[form]}
+                <Block Gap(1)>
+                    {side}
+                </Block>
+            </Block>
+        },
+    };
+
+    ui! {
+        <Block
+            On::new(|model: &Model, event| {
+                model.record(event);
+                let keys = &model.config.keys;
+                let typing = model.palette.is_some()
+                    || (!model.help_open
+                        && matches!(model.focus, Focus::UsernameField | Focus::PasswordField));
+                // Bare characters go to the text field being typed in, not to bindings like `?`.
+                // Whatever comes after the first key of a chord finishes it, or gives up on it.
+                if let Some(leader) = model.chord {
+                    if !keymap::is_press(event) {
+                        return None;
+                    }
+                    let effect = match keys.chord(Scope::Global, leader, event).and_then(|action| go_to(model, action)) {
+                        Some(msg) => Effect::new(move |tx| async move {
+                            _ = queue::send(&tx, msg).await;
+                        }),
+                        None => Effect::none(),
+                    };
+                    return Some((Msg::Chord(None), effect));
+                }
+                let (global, leader) = match typing && keymap::is_text(event) {
+                    true => (None, None),
+                    false => (keys.action(Scope::Global, event), keys.leader(Scope::Global, event)),
+                };
+                let power = keys
+                    .action(Scope::PowerMenu, event)
+                    .and_then(Action::power)
+                    .filter(|action| model.config.power.enabled && model.power_actions.contains(action));
+                let preview = keys
+                    .action(Scope::Preview, event)
+                    .filter(|_| model.cli_args.is_preview());
+                match global {
+                    Some(Action::Quit) => model.can_quit().then_some((Msg::Quit, Effect::none())),
+                    _ if model.modal.is_some() => {
+                        let msg = match keys.action(Scope::Dialog, event)? {
+                            Action::DialogClose => Msg::CloseModal,
+                            Action::DialogPress => Msg::ModalPress,
+                            Action::DialogNext => Msg::ModalFocus(1),
+                            _ => Msg::ModalFocus(-1),
+                        };
+                        Some((msg, Effect::none()))
+                    }
+                    Some(Action::Palette) => Some((Msg::TogglePalette, Effect::none())),
+                    _ if model.palette.is_some() => {
+                        let msg = match keys.action(Scope::Palette, event) {
+                            Some(Action::PaletteClose) => Msg::TogglePalette,
+                            Some(Action::PaletteRun) => Msg::PaletteRun,
+                            Some(Action::PaletteNext) => Msg::PaletteMove(1),
+                            Some(_) => Msg::PaletteMove(-1),
+                            None => {
+                                let mut query = model.palette.as_ref()?.query.clone();
+                                readline::handle(&mut query, event)?;
+                                Msg::PaletteQuery(query)
+                            }
+                        };
+                        Some((msg, Effect::none()))
+                    }
+                    Some(Action::Help) => Some((Msg::ToggleHelp, Effect::none())),
+                    _ if model.help_open => match keys.action(Scope::Dialog, event) {
+                        Some(Action::DialogClose) => Some((Msg::ToggleHelp, Effect::none())),
+                        _ => None,
+                    },
+                    Some(Action::Debug) => Some((Msg::ToggleDebug, Effect::none())),
+                    Some(
+                        action @ (Action::GoForm
+                        | Action::GoUsers
+                        | Action::GoSessions
+                        | Action::GoPower
+                        | Action::GoKeyboard),
+                    ) => Some((go_to(model, action)?, Effect::none())),
+                    _ if leader.is_some() => Some((Msg::Chord(leader), Effect::none())),
+                    _ if power.is_some() => {
+                        let msg = match model.config.power.confirm {
+                            true => Msg::OpenModal(confirm_power(power?)),
+                            false => Msg::Power(power?),
+                        };
+                        Some((msg, Effect::none()))
+                    }
+                    Some(Action::ErrorDetails) => match &model.form_state {
+                        FormState::LoginFailed(error_type, description) => {
+                            let modal = error_details(error_type, description);
+                            Some((Msg::OpenModal(modal), Effect::none()))
+                        }
+                        _ => None,
+                    },
+                    Some(Action::ToggleLog) => Some((Msg::ToggleLog, Effect::none())),
+                    Some(Action::NextKeymap) if !model.config.console_keymaps.keymaps.is_empty() => {
+                        Some((Msg::NextKeymap, Effect::none()))
+                    }
+                    Some(action @ (Action::BrightnessUp | Action::BrightnessDown))
+                        if model.config.backlight.enabled =>
+                    {
+                        let step = model.config.backlight.step.min(100) as i8;
+                        let by = match action {
+                            Action::BrightnessUp => step,
+                            _ => -step,
+                        };
+                        Some((Msg::Brightness(by), Effect::none()))
+                    }
+                    Some(Action::ScrollUp) if model.log_open => Some((Msg::ScrollLog(1), Effect::none())),
+                    Some(Action::ScrollDown) if model.log_open => Some((Msg::ScrollLog(-1), Effect::none())),
+                    Some(Action::ScrollUp) => Some((Msg::ScrollMotd(-1), Effect::none())),
+                    Some(Action::ScrollDown) => Some((Msg::ScrollMotd(1), Effect::none())),
+                    _ if preview.is_some() => {
+                        let next = preview == Some(Action::NextState);
+                        Some((Msg::PreviewState(model.form_state.preview_cycle(next)), Effect::none()))
+                    }
+                    _ if matches!(event, event::Event::Key(_)) && !model.idle.is_active() => {
+                        Some((Msg::Wake, Effect::none()))
+                    }
+                    _ => None
+                }
+            })
+            Width::grow()
+            Height::grow()
+        >
+            <Maybe
+                .cond={model.idle != IdleLevel::Blank}
+                .then={ui! {
+                    <Screen
+                        .toasts={toasts}
+                        .header={header}
+                        .body={match &model.modal {
+                            Some(modal) => ui! { <ModalDialog .modal={modal} .theme={modal_theme} .keys={&model.config.keys}/> },
+                            None if model.palette.is_some() => ui! {
+                                <CommandPalette
+                                    .palette={model.palette.as_ref()}
+                                    .commands={palette_commands(model)}
+                                    .theme={modal_theme}
+                                    .symbols={&model.config.symbols}
+                                />
+                            },
+                            None if model.help_open => ui! {
+                                <HelpOverlay
+                                    .sections={help::sections(&model.config.keys, in_use)}
+                                    .theme={modal_theme}
+                                    .keys={&model.config.keys}
+                                />
+                            },
+                            None => ui! {
+                                <WithBackground
+                                    .content={form}
+                                    .art={art}
+                                    .placement={model.config.background.placement}
+                                />
+                            },
+                        }}
+                        .footer={footer}
+                        .status_bar={status_bar}
+                    />
+                }}
+            />
+        </Block>
+    }
+}
+
+/// `body` centered on screen, with optional lines of widgets pinned to the top and bottom,
+/// toasts stacked in the top right and the status bar along the bottom edge.
+#[subview]
+fn screen(
+    toasts: Vec<View>,
+    header: Vec<View>,
+    body: View,
+    footer: Vec<View>,
+    status_bar: Option<View>,
+) -> View {
+    let toasts = toasts.into_iter().map(|toast| {
+        ui! {
+            <Block Direction::Horizontal Width::grow() Height::fixed(1)>
+                <Block Width::grow()/>
+                {[toast]}
+            </Block>
+        }
+    });
+    let edge = |views: Vec<View>| {
+        (!views.is_empty()).then(|| {
+            ui! {
+                <Block Direction::Horizontal Gap(3) Center Width::grow() Height::fixed(1)>
+                    {views}
+                </Block>
+            }
+        })
+    };
+    let body = ui! { <Block Center Width::grow() Height::grow()>{[body]}</Block> };
+    let rows = toasts.chain(
+        [edge(header), Some(body), edge(footer), status_bar]
+            .into_iter()
+            .flatten(),
+    );
+    ui! {
+        <Block Width::grow() Height::grow()>
+            {rows}
+        </Block>
+    }
+}
+
+/// A [`Modal`] with its focused button highlighted, in place of the form.
+#[subview]
+fn modal_dialog(modal: &Modal, theme: Theme, keys: &KeyMap) -> View {
+    let title = &modal.title;
+    let width = modal
+        .body
+        .iter()
+        .chain([title])
+        .map(|line| styling::width(line))
+        .max()
+        .unwrap_or_default()
+        .max(24);
+    let rule = "─".repeat(width);
+    let body = modal.body.iter().map(|line| {
+        ui! { <Span .style={Style::new().fg(theme.text)}>"{line}"</Span> }
+    });
+    let buttons = modal.buttons.iter().enumerate().map(|(idx, button)| {
+        let style = match idx == modal.focused {
+            true => Style::new().fg(theme.badge_fg).bg(theme.accent).bold(),
+            false => Style::new().fg(theme.text_dim),
+        };
+        let label = &button.label;
+        ui! { <Span .style={style}>" {label} "</Span> }
+    });
+    let (bright, dark) = (theme.help_key, theme.help_desc);
+    let hints = help::hints(
+        keys,
+        &[
+            (Action::DialogNext, "choose"),
+            (Action::DialogPress, "confirm"),
+            (Action::DialogClose, "close"),
+        ],
+    );
+    let last = hints.len().saturating_sub(1);
+    let hints = hints.into_iter().enumerate().flat_map(|(idx, hint)| {
+        let (keys, action) = (hint.keys, hint.action);
+        let separator = match idx == last {
+            true => "",
+            false => " • ",
+        };
+        [
+            ui! { <Span .style={Style::new().fg(bright)}>"{keys} "</Span> },
+            ui! { <Span .style={Style::new().fg(dark)}>"{action}{separator}"</Span> },
+        ]
+    });
+    ui! {
+        <Block Gap(1) Padding::new(2, 2, 1, 1)>
+            <Block>
+                <Span .style={Style::new().fg(theme.accent).bold()}>"{title}"</Span>
+                <Span .style={Style::new().fg(theme.border)}>"{rule}"</Span>
+            </Block>
+            <Block>
+                {body}
+            </Block>
+            <Block Direction::Horizontal Gap(2)>
+                {buttons}
+            </Block>
+            <Block Direction::Horizontal>
+                {hints}
+            </Block>
+        </Block>
+    }
+}
+
+/// Runs a power action picked from the menu or palette, asking first if it would end
+/// everyone's sessions.
+fn request_power(action: PowerAction, config: &PowerConfig) -> Msg {
+    match config.confirm && action.is_destructive() {
+        true => Msg::OpenModal(confirm_power(action)),
+        false => Msg::Power(action),
+    }
+}
+
+/// Asks before running a power action from its key, which is easy to hit by accident.
+fn confirm_power(action: PowerAction) -> Modal {
+    let label = action.label();
+    Modal::new(
+        format!("{label}?"),
+        "This affects everyone using the machine.",
+    )
+    .button("Cancel", Msg::Nothing)
+    .button(label, Msg::Power(action))
+}
+
+/// Everything greetd said about a failed login, more than fits on the status line.
+fn error_details(error_type: &ErrorType, description: &str) -> Modal {
+    let kind = match error_type {
+        ErrorType::AuthError => "The credentials were rejected.",
+        ErrorType::Error => "greetd ran into an error.",
+    };
+    Modal::new("Login failed", &format!("{description}\n\n{kind}")).button("Close", Msg::Nothing)
+}
+
+/// Segments pinned to the left and right edges, and centered between them.
+#[subview]
+fn segment_bar(
+    left: Vec<Span<'static>>,
+    center: Vec<Span<'static>>,
+    right: Vec<Span<'static>>,
+) -> View {
+    let views = |spans: Vec<Span<'static>>| {
+        spans
+            .into_iter()
+            .map(|span| ui! { <SpanLine .spans={vec![span]}/> })
+            .collect::<Vec<_>>()
+    };
+    ui! {
+        <Block Direction::Horizontal Width::grow() Height::fixed(1) Padding::new(1, 1, 0, 0)>
+            <Block Direction::Horizontal Gap(2) Width::grow()>
+                {views(left)}
+            </Block>
+            <Block Direction::Horizontal Gap(2) Center Width::grow()>
+                {views(center)}
+            </Block>
+            <Block Direction::Horizontal Width::grow()>
+                <Block Width::grow()/>
+                <Block Direction::Horizontal Gap(2)>
+                    {views(right)}
+                </Block>
+            </Block>
+        </Block>
+    }
+}
+
+#[subview]
+fn with_background(content: View, art: Option<View>, placement: Placement) -> View {
+    let Some(art) = art else {
+        return content;
+    };
+    match placement {
+        Placement::Above => ui! { <Block Gap(1)>{[art, content]}</Block> },
+        Placement::Below => ui! { <Block Gap(1)>{[content, art]}</Block> },
+        Placement::Left => ui! { <Block Direction::Horizontal Gap(4)>{[art, content]}</Block> },
+        Placement::Right => ui! { <Block Direction::Horizontal Gap(4)>{[content, art]}</Block> },
+    }
+}
+
+#[subview]
+fn ansi_art(lines: Vec<Line<'static>>) -> View {
+    let lines = lines
+        .into_iter()
+        .map(|line| ui! { <SpanLine .spans={line.spans}/> });
+    ui! {
+        <Block>
+            {lines}
+        </Block>
+    }
+}
+
+#[subview]
+fn field_input(
+    field: Field,
+    value: &str,
+    cursor: usize,
+    label: &str,
+    focused: bool,
+    /// How far the focus fade-in has progressed, `0.0..=1.0`.
+    highlight: f32,
+    theme: Theme,
+    symbols: &SymbolsConfig,
+    #[builder(default)] vi: Option<&Vi>,
+) -> View {
+    let vi = vi.cloned();
+    let label_style = match focused {
+        true => Style::new().fg(color::mix(theme.label, theme.label_focused, highlight)),
+        false => Style::new().fg(theme.label),
+    };
+    let input_style = match focused {
+        true => Style::new().fg(theme.text).bold(),
+        false => Style::new().fg(theme.text_dim).bold(),
+    };
+    let label = match focused {
+        true => format!("{}{label}", symbols.prompt),
+        false => format!("{}{label}", " ".repeat(styling::width(&symbols.prompt))),
+    };
+    let value = match focused {
+        true => styling::with_cursor(value, cursor, symbols.cursor, input_style),
+        false => vec![Span::styled(value.to_string(), input_style)],
+    };
+    ui! {
+        <Block
+            Direction::Horizontal
+        >
+            <Span .style={label_style}>"{label} "</Span>
+            <Block
+                Direction::Horizontal
+                On::new(move |model: &Model, event| -> Option<(Msg, _)> {
+                    if !focused {
+                        return None;
+                    }
+                    if let Field::Password = field {
+                        let mut password = model.password.clone();
+                        readline::handle(&mut password, event)?;
+                        let edited = Msg::PasswordUpdate(password);
+                        if !matches!(event, event::Event::Paste(_)) {
+                            return Some((edited, caps_lock_effect(event, model.caps_lock)));
+                        }
+                        let msg = match model.config.input.password_paste {
+                            PastePolicy::Allow => edited,
+                            // Neither says how long the paste was, which would give away
+                            // how long the password is.
+                            PastePolicy::Confirm => Msg::OpenModal(
+                                Modal::new(
+                                    "Paste into the password field?",
+                                    "Make sure it's the password and not something else on the clipboard.",
+                                )
+                                .button("Cancel", Msg::Nothing)
+                                .button("Paste", edited),
+                            ),
+                            PastePolicy::Block => Msg::Toast(
+                                "pasting into the password field is turned off".into(),
+                                ToastLevel::Warning,
+                            ),
+                        };
+                        return Some((msg, Effect::none()));
+                    }
+                    let mut username = model.username.clone();
+                    if let Some(mut vi) = vi.clone() {
+                        // Only the username field has vi mode, with the password below it.
+                        let focus = match vi.handle(&mut username, event) {
+                            vi::Outcome::Insert => None,
+                            vi::Outcome::Edited => return Some((Msg::ViUpdate(vi, username), Effect::none())),
+                            vi::Outcome::MoveField(1) => Some(Focus::PasswordField),
+                            vi::Outcome::MoveField(_) if !model.users.is_empty() => Some(Focus::UserMenu),
+                            vi::Outcome::MoveField(_) | vi::Outcome::Unhandled => return None,
+                        };
+                        if let Some(focus) = focus {
+                            return Some((Msg::FocusOn(focus), Effect::none()));
+                        }
+                    }
+                    readline::handle(&mut username, event)?;
+                    Some((Msg::UsernameUpdate(username), caps_lock_effect(event, model.caps_lock)))
+                })
+            >
+                <SpanLine .spans={value}/>
+            </Block>
+        </Block>
+    }
+}
+
+/// Checks caps lock on a key typed into a field, if that can be told, and says so when it
+/// isn't what it was.
+fn caps_lock_effect(event: &event::Event, was: bool) -> Effect<Msg> {
+    let caps_lock = match event {
+        event::Event::Key(key) => term::caps_lock(key),
+        _ => None,
+    };
+    match caps_lock {
+        Some(on) if on != was => Effect::new(move |tx| async move {
+            _ = queue::send(&tx, Msg::CapsLock(on)).await;
+        }),
+        _ => Effect::none(),
+    }
+}
+
+/// The outcome of the last login attempt, marked with a symbol and weight as well as color.
+#[subview]
+fn status_line(
+    form_state: &FormState,
+    /// How wide error descriptions get before they wrap.
+    width: usize,
+    theme: Theme,
+    symbols: &SymbolsConfig,
+    keys: &KeyMap,
+) -> View {
+    match form_state {
+        FormState::LoginFailed(_, description) => {
+            let (symbol, style) = (&symbols.error, Style::new().fg(theme.error).bold());
+            let indent = styling::width(symbol);
+            let (lines, cut) =
+                styling::wrap(description, width.saturating_sub(indent), ERROR_LINES);
+            let lines = lines.into_iter().enumerate().map(|(idx, line)| {
+                let prefix = match idx {
+                    0 => symbol.to_string(),
+                    _ => " ".repeat(indent),
+                };
+                ui! { <Span .style={style}>"{prefix}{line}"</Span> }
+            });
+            let label = match cut {
+                true => "for the rest",
+                false => "details",
+            };
+            let hint = help::footer(keys, &[(Action::ErrorDetails, label)]);
+            ui! {
+                <Block>
+                    {lines}
+                    <Span .style={Style::new().fg(theme.text_dim)}>"{hint}"</Span>
+                </Block>
+            }
+        }
+        FormState::PickingDesktop => {
+            let (symbol, style) = (&symbols.success, Style::new().fg(theme.success));
+            ui! { <Span .style={style}>"{symbol}authenticated"</Span> }
+        }
+        FormState::Idle | FormState::CreatedSession => ui! { "" },
+    }
+}
+
+#[subview]
+fn motd_panel(
+    lines: &[Line<'static>],
+    scroll: usize,
+    height: u16,
+    theme: Theme,
+    keys: &KeyMap,
+) -> View {
+    let (visible, more) = motd::window(lines, scroll, height as usize);
+    let visible = visible.to_vec();
+    let actions: &[_] = match (scroll > 0, more) {
+        (false, false) => &[],
+        (false, true) => &[(Action::ScrollDown, "more")],
+        (true, false) => &[(Action::ScrollUp, "back")],
+        (true, true) => &[(Action::ScrollUp, "back"), (Action::ScrollDown, "more")],
+    };
+    let hint = help::footer(keys, actions);
+    ui! {
+        <Block>
+            <AnsiArt .lines={visible}/>
+            <Span .style={Style::new().fg(theme.text_dim)}>"{hint}"</Span>
+        </Block>
+    }
+}
+
+/// The newest entries of the event log, with their timestamps.
+#[subview]
+fn log_pane(log: &EventLog, scroll: usize, theme: Theme, keys: &KeyMap) -> View {
+    let entries = log.tail(scroll, LOG_HEIGHT).iter().map(|entry| {
+        let at = entry.at.format("%H:%M:%S");
+        let text = &entry.text;
+        ui! {
+            <Block Direction::Horizontal>
+                <Span .style={Style::new().fg(theme.text_dim)}>"{at} "</Span>
+                <Span .style={Style::new().fg(theme.text)}>"{text}"</Span>
+            </Block>
+        }
+    });
+    let actions: &[_] = match scroll > 0 {
+        true => &[
+            (Action::ScrollUp, "older"),
+            (Action::ScrollDown, "newer"),
+            (Action::ToggleLog, "hide"),
+        ],
+        false => &[(Action::ScrollUp, "older"), (Action::ToggleLog, "hide")],
+    };
+    let hint = help::footer(keys, actions);
+    ui! {
+        <Block>
+            {entries}
+            <Span .style={Style::new().fg(theme.text_dim)}>"{hint}"</Span>
+        </Block>
+    }
+}
+
+/// Every power action with its key, the selected one highlighted while focused and the ones
+/// that can't run greyed out.
+#[subview]
+fn power_bar(
+    available: &[PowerAction],
+    keys: &KeyMap,
+    selected: Option<PowerAction>,
+    focused: bool,
+    theme: Theme,
+) -> View {
+    let actions = PowerAction::ALL.into_iter().map(|action| {
+        let key = keys
+            .keys(action.into())
+            .first()
+            .map(|key| format!("{key} "))
+            .unwrap_or_default();
+        let label = action.label();
+        let (key_style, style) = match available.contains(&action) {
+            false => (
+                Style::new().fg(theme.text_dim),
+                Style::new().fg(theme.text_dim),
+            ),
+            true if focused && selected == Some(action) => (
+                Style::new().fg(theme.help_key),
+                Style::new().fg(theme.badge_fg).bg(theme.accent),
+            ),
+            true => (
+                Style::new().fg(theme.help_key),
+                Style::new().fg(theme.help_desc),
+            ),
+        };
+        ui! {
+            <Block Direction::Horizontal>
+                <Span .style={key_style}>"{key}"</Span>
+                <Span .style={style}>"{label}"</Span>
+            </Block>
+        }
+    });
+    ui! {
+        <Block Direction::Horizontal Gap(2)>
+            {actions}
+        </Block>
+    }
+}
+
+#[subview]
+fn avatar_box(avatar: Option<&Avatar>, theme: Theme) -> View {
+    match avatar {
+        Some(Avatar::Picture(lines)) => ui! { <AnsiArt .lines={lines.clone()}/> },
+        Some(Avatar::Initials(initials)) => {
+            let edge = "─".repeat(styling::width(initials) + 2);
+            let style = Style::new().fg(theme.accent);
+            ui! {
+                <Block>
+                    <Span .style={style}>"╭{edge}╮"</Span>
+                    <Span .style={style.bold()}>"│ {initials} │"</Span>
+                    <Span .style={style}>"╰{edge}╯"</Span>
+                </Block>
+            }
+        }
+        None => ui! { "" },
+    }
+}
+
+/// The users to pick from, scrolled to keep the selection in view.
+#[subview]
+fn user_menu(
+    users: &[User],
+    selected: usize,
+    focused: bool,
+    theme: Theme,
+    symbols: &SymbolsConfig,
+) -> View {
+    let start = selected
+        .saturating_sub(USER_MENU_HEIGHT - 1)
+        .min(users.len().saturating_sub(USER_MENU_HEIGHT));
+    let end = (start + USER_MENU_HEIGHT).min(users.len());
+    let indent = " ".repeat(styling::width(&symbols.selection));
+    let rows = users[start..end].iter().enumerate().map(|(idx, user)| {
+        let (marker, style) = match (start + idx == selected, focused) {
+            (true, true) => (&*symbols.selection, Style::new().fg(theme.accent).bold()),
+            (true, false) => (&*symbols.selection, Style::new().fg(theme.text)),
+            (false, _) => (indent.as_str(), Style::new().fg(theme.text_dim)),
+        };
+        let name = user.display_name();
+        let login = match user.real_name {
+            Some(_) => format!("  {}", user.name),
+            None => String::new(),
+        };
+        ui! {
+            <Block Direction::Horizontal>
+                <Span .style={style}>"{marker}{name}"</Span>
+                <Span .style={Style::new().fg(theme.text_dim)}>"{login}"</Span>
+            </Block>
+        }
+    });
+    ui! {
+        <Block>
+            {rows}
+        </Block>
+    }
+}
+
+/// Where a list showing `height` rows from `offset` has to start for `selected` to be one of
+/// them, scrolling no further than it needs to.
+fn scroll_to(offset: usize, selected: usize, height: usize) -> usize {
+    match selected {
+        selected if selected < offset => selected,
+        selected if selected >= offset + height => selected + 1 - height,
+        _ => offset,
+    }
+}
+
+/// The on-screen keyboard, with the selected key highlighted while it has focus.
+#[subview]
+fn on_screen_keyboard(
+    keyboard: Option<&Keyboard>,
+    target: &Field,
+    focused: bool,
+    theme: Theme,
+) -> View {
+    let Some(keyboard) = keyboard else {
+        return ui! { "" };
+    };
+    let target = match target {
+        Field::Username => "typing the username",
+        Field::Password => "typing the password",
+    };
+    let (selected_row, selected_col) = keyboard.selected();
+    let rows = Keyboard::rows().enumerate().map(|(row, keys)| {
+        let keys = keys.into_iter().enumerate().map(|(col, key)| {
+            let label = key.label(keyboard.shift());
+            let style = match focused && (row, col) == (selected_row, selected_col) {
+                true => Style::new().fg(theme.badge_fg).bg(theme.accent),
+                false => Style::new().fg(theme.text),
+            };
+            ui! { <Span .style={style}>" {label} "</Span> }
+        });
+        ui! {
+            <Block Direction::Horizontal>
+                {keys}
+            </Block>
+        }
+    });
+    ui! {
+        <Block>
+            <Span .style={Style::new().fg(theme.text_dim)}>"{target}"</Span>
+            {rows}
+        </Block>
+    }
+}
+
+/// Where a multi-step login is at, with the prompts that came before.
+#[subview]
+fn auth_steps(auth: &AuthProgress, theme: Theme, symbols: &SymbolsConfig) -> View {
+    let step = auth.indicator().unwrap_or_default();
+    let history = auth.history().iter().map(|prompt| {
+        let text = prompt.text.trim();
+        let (text, style) = match prompt.kind {
+            greetd::AuthMessageType::Visible | greetd::AuthMessageType::Secret => (
+                format!("{}{text}", symbols.success),
+                Style::new().fg(theme.text_dim),
+            ),
+            greetd::AuthMessageType::Info => (text.to_string(), Style::new().fg(theme.text)),
+            greetd::AuthMessageType::Error => (
+                format!("{}{text}", symbols.error),
+                Style::new().fg(theme.error),
+            ),
+        };
+        ui! { <Span .style={style}>"  {text}"</Span> }
+    });
+    ui! {
+        <Block>
+            <Span .style={Style::new().fg(theme.accent).bold()}>"  {step}"</Span>
+            {history}
+        </Block>
+    }
+}
+
+/// Internal state for debugging the greeter itself.
+#[subview]
+fn debug_panel(model: &Model, theme: Theme) -> View {
+    let rows = [
+        ("state", format!("{:?}", model.form_state)),
+        ("focus", format!("{:?}", model.focus)),
+        ("pending", format!("{} requests", model.req_tx.len())),
+        ("idle", format!("{:?}", model.idle)),
+        (
+            "greetd",
+            match &model.error {
+                Some(err) => format!("failed: {}", secret::scrub(&err.to_string())),
+                None => "running".to_string(),
+            },
+        ),
+    ];
+    let rows = rows.into_iter().map(|(name, value)| {
+        ui! {
+            <Block Direction::Horizontal>
+                <Span .style={Style::new().fg(theme.text_dim)}>"{name:<8}"</Span>
+                <Span .style={Style::new().fg(theme.text)}>"{value}"</Span>
+            </Block>
+        }
+    });
+    let frames = model.event_log.tail(0, DEBUG_FRAMES).iter().map(|entry| {
+        let text = &entry.text;
+        ui! { <Span .style={Style::new().fg(theme.text_dim)}>"{text}"</Span> }
+    });
+    let title = match model.config.keys.keys(Action::Debug).first() {
+        Some(key) => format!("debug ({key})"),
+        None => "debug".to_string(),
+    };
+    ui! {
+        <Block>
+            <Span .style={Style::new().fg(theme.warning).bold()}>"{title}"</Span>
+            {rows}
+            {frames}
+        </Block>
+    }
+}
+
+#[subview]
+fn span_line(spans: Vec<Span<'static>>) -> View {
+    let chars = spans.into_iter().map(|span| {
+        let style = span.style;
+        let content = span.content;
+        ui! { <Span .style={style}>"{content}"</Span> }
+    });
+    ui! {
+        <Block Direction::Horizontal>
+            {chars}
+        </Block>
+    }
+}
+
+#[subview]
+fn maybe(cond: bool, then: View, r#else: Option<View>) -> View {
+    if cond {
+        then
+    } else {
+        r#else.unwrap_or(ui! { "" })
+    }
+}
+
+/// The sessions to start, with the selection marked while the picker has focus.
+#[subview]
+fn desktop_picker(model: &Model) -> View {
+    let (theme, symbols) = (model.theme, &model.config.symbols);
+    let indent = " ".repeat(styling::width(&symbols.selection));
+    let focused = model.focus.is_desktop_picker();
+    let start = model.desktop_offset;
+    let end = (start + SESSION_PICKER_HEIGHT).min(model.desktops.len());
+    let rows = model.desktops[start..end]
+        .iter()
+        .enumerate()
+        .map(|(idx, desktop)| {
+            let name = desktop.path.to_string_lossy();
+            let (marker, style) = match (start + idx == model.desktop_selected, focused) {
+                (true, true) => (&*symbols.selection, Style::new().fg(theme.accent).bold()),
+                (true, false) => (&*symbols.selection, Style::new().fg(theme.text)),
+                (false, _) => (indent.as_str(), Style::new().fg(theme.text_dim)),
+            };
+            ui! { <Span .style={style}>"{marker}{name}"</Span> }
+        });
+    ui! {
+        <Block
+            On::new(|model: &Model, event| {
+                if !model.focus.is_desktop_picker() {
+                    return None;
+                }
+                match model.config.keys.action(Scope::SessionPicker, event)? {
+                    Action::SessionDown => Some((Msg::PickerMove(1), Effect::none())),
+                    Action::SessionUp => Some((Msg::PickerMove(-1), Effect::none())),
+                    Action::LeavePicker => Some((escape(model, model.config.escape.session_picker)?, Effect::none())),
+                    Action::StartShell => {
+                        let modal = Modal::new(
+                            "Start a shell?",
+                            "Skips the session list and logs in to /bin/sh.",
+                        )
+                        .button("Cancel", Msg::Nothing)
+                        .button("Start shell", Msg::StartShell);
+                        Some((Msg::OpenModal(modal), Effect::none()))
+                    }
+                    _ => None,
+                }
+            })
+        >
+            "Pick a session"
+            {rows}
+        </Block>
+    }
+}
+
+/// The keys that do something where the focus is, or while a chord is waiting for its second
+/// key, the ones that can finish it after `leader`.
 #[subview]
-fn help_section() -> View {
-    let bright = Color::from_u32(0x626262);
-    let dark = Color::from_u32(0x4e4e4e);
+fn key_hints(
+    hints: Vec<Binding>,
+    theme: Theme,
+    #[builder(default)] leader: Option<KeyCombo>,
+) -> View {
+    let (bright, dark) = (theme.help_key, theme.help_desc);
+    let leader = leader.map(|leader| {
+        ui! { <Span .style={Style::new().fg(theme.accent).bold()}>"{leader} …"</Span> }
+    });
+    let hints = hints.into_iter().map(|binding| {
+        let (key, action) = (binding.keys, binding.action);
+        ui! {
+            <Block Direction::Horizontal>
+                <Span .style={Style::new().fg(bright)}>"{key} "</Span>
+                <Span .style={Style::new().fg(dark)}>"{action}"</Span>
+            </Block>
+        }
+    });
     ui! {
-        <Block Direction::Horizontal>
-            <Span .style={Style::new().fg(bright)}>"↑↓ / Tab / ^J ^K "</Span>
-            <Span .style={Style::new().fg(dark)}>"navigate • "</Span>
-            <Span .style={Style::new().fg(bright)}>"Enter "</Span>
-            <Span .style={Style::new().fg(dark)}>"confirm "</Span>
+        <Block Direction::Horizontal Gap(2)>
+            {leader}
+            {hints}
+        </Block>
+    }
+}
+
+/// The actions the footer hints at for where the focus is, with short labels.
+fn hint_actions(model: &Model) -> Vec<(Action, &'static str)> {
+    let mut actions = match model.focus {
+        Focus::UserMenu => vec![
+            (Action::PickUser, "log in"),
+            (Action::TypeUsername, "type a name"),
+        ],
+        Focus::UsernameField if model.config.input.complete_usernames => {
+            vec![(Action::Submit, "next"), (Action::SwitchField, "complete")]
+        }
+        Focus::UsernameField => vec![(Action::Submit, "next")],
+        Focus::PasswordField if model.auth.question().is_some() => {
+            vec![(Action::Submit, "answer"), (Action::SwitchBack, "back")]
+        }
+        Focus::PasswordField => vec![(Action::Submit, "log in"), (Action::SwitchBack, "back")],
+        Focus::DesktopPicker => vec![
+            (Action::SessionDown, "next"),
+            (Action::StartShell, "shell"),
+            (Action::LeavePicker, "cancel"),
+        ],
+        Focus::PowerMenu => vec![(Action::PowerRun, "run"), (Action::PowerBack, "back")],
+        Focus::Keyboard => vec![
+            (Action::KeyboardPress, "press"),
+            (Action::KeyboardBack, "back"),
+        ],
+    };
+    actions.extend([(Action::Palette, "commands"), (Action::Help, "help")]);
+    actions
+}
+
+/// Why the username can't be logged in to, under the field.
+#[subview]
+fn username_hint(hint: Option<InvalidUsername>, theme: Theme, symbols: &SymbolsConfig) -> View {
+    let indent = " ".repeat(styling::width(&symbols.prompt) + 2);
+    let hint = hint.map(InvalidUsername::hint).unwrap_or_default();
+    ui! { <Span .style={Style::new().fg(theme.warning)}>"{indent}{hint}"</Span> }
+}
+
+/// Where one of the go to actions, `g p` and the like, takes the focus if that's on screen.
+fn go_to(model: &Model, action: Action) -> Option<Msg> {
+    let picking = matches!(model.form_state, FormState::PickingDesktop);
+    let focus = match action {
+        Action::GoForm if !picking => Focus::UsernameField,
+        Action::GoUsers if !model.users.is_empty() => Focus::UserMenu,
+        Action::GoSessions if picking => Focus::DesktopPicker,
+        Action::GoPower if model.has_power_menu() => Focus::PowerMenu,
+        Action::GoKeyboard if model.keyboard.is_some() => Focus::Keyboard,
+        _ => return None,
+    };
+    Some(Msg::FocusOn(focus))
+}
+
+/// What Esc does where the focus is now.
+fn escape(model: &Model, action: EscapeAction) -> Option<Msg> {
+    let cleared = match model.focus {
+        Focus::UsernameField => Some(Msg::UsernameUpdate(Input::default())),
+        Focus::PasswordField => Some(Msg::PasswordUpdate(SecretInput::default())),
+        _ => None,
+    };
+    match action {
+        EscapeAction::Nothing => None,
+        EscapeAction::Clear => cleared,
+        EscapeAction::Back => match model.focus {
+            // The session greetd has open would be in the way of the next login.
+            Focus::PasswordField if matches!(model.form_state, FormState::CreatedSession) => {
+                Some(Msg::CancelSession)
+            }
+            Focus::PasswordField => Some(Msg::FocusOn(Focus::UsernameField)),
+            Focus::UsernameField if !model.users.is_empty() => Some(Msg::FocusOn(Focus::UserMenu)),
+            Focus::DesktopPicker => Some(Msg::CancelSession),
+            _ => None,
+        },
+        EscapeAction::Cancel => Some(Msg::CancelSession),
+        EscapeAction::PowerMenu => model
+            .has_power_menu()
+            .then_some(Msg::FocusOn(Focus::PowerMenu)),
+    }
+}
+
+/// Completes the typed username, `None` if there's nothing to complete.
+fn complete_username(model: &Model) -> Option<Msg> {
+    let value = model.username.value();
+    let (completion, value) = model.completion.complete(&model.usernames, value)?;
+    Some(Msg::Complete(completion, Input::new(value)))
+}
+
+/// The usernames the last completion left to choose from.
+#[subview]
+fn completion_popup(completion: &Completion, theme: Theme, symbols: &SymbolsConfig) -> View {
+    let candidates = completion.candidates();
+    let selected = completion.selected().unwrap_or_default();
+    let start = selected
+        .saturating_sub(complete::HEIGHT - 1)
+        .min(candidates.len().saturating_sub(complete::HEIGHT));
+    let end = (start + complete::HEIGHT).min(candidates.len());
+    let indent = " ".repeat(styling::width(&symbols.prompt) + 2);
+    let rows = candidates[start..end]
+        .iter()
+        .enumerate()
+        .map(|(idx, name)| {
+            let style = match completion.selected() == Some(start + idx) {
+                true => Style::new().fg(theme.accent).bold(),
+                false => Style::new().fg(theme.text_dim),
+            };
+            ui! { <Span .style={style}>"{indent}{name}"</Span> }
+        });
+    let more = candidates.len() - (end - start);
+    ui! {
+        <Block>
+            {rows}
+            <Maybe
+                .cond={more > 0}
+                .then={ui! { <Span .style={Style::new().fg(theme.text_dim)}>"{indent}+{more} more"</Span> }}
+            />
+        </Block>
+    }
+}
+
+/// Everything the command palette can do with what's enabled right now.
+fn palette_commands(model: &Model) -> Vec<PaletteCommand> {
+    let mut commands = vec![
+        PaletteCommand::new("Focus username", Msg::FocusOn(Focus::UsernameField)),
+        PaletteCommand::new("Focus password", Msg::FocusOn(Focus::PasswordField)),
+    ];
+    if !model.users.is_empty() {
+        commands.push(PaletteCommand::new(
+            "Choose a user",
+            Msg::FocusOn(Focus::UserMenu),
+        ));
+    }
+    if matches!(model.form_state, FormState::PickingDesktop) {
+        commands.push(PaletteCommand::new(
+            "Choose a session",
+            Msg::FocusOn(Focus::DesktopPicker),
+        ));
+    }
+    if model.keyboard.is_some() {
+        commands.push(PaletteCommand::new(
+            "On-screen keyboard",
+            Msg::FocusOn(Focus::Keyboard),
+        ));
+    }
+    if !model.config.console_keymaps.keymaps.is_empty() {
+        commands.push(PaletteCommand::new("Next console keymap", Msg::NextKeymap));
+    }
+    if model.has_power_menu() {
+        commands.extend(model.power_actions.iter().map(|&action| {
+            PaletteCommand::new(action.label(), request_power(action, &model.config.power))
+        }));
+    }
+    commands.extend([
+        PaletteCommand::new("Toggle event log", Msg::ToggleLog),
+        PaletteCommand::new("Toggle debug info", Msg::ToggleDebug),
+        PaletteCommand::new("Key bindings", Msg::ToggleHelp),
+        PaletteCommand::new("Reload config", Msg::ReloadConfig),
+    ]);
+    if model.can_quit() {
+        commands.push(PaletteCommand::new("Quit", Msg::Quit));
+    }
+    commands
+}
+
+/// A search box over [`palette_commands`], with the best matches below it.
+#[subview]
+fn command_palette(
+    palette: Option<&Palette>,
+    commands: Vec<PaletteCommand>,
+    theme: Theme,
+    symbols: &SymbolsConfig,
+) -> View {
+    let Some(palette) = palette else {
+        return ui! { "" };
+    };
+    let input_style = Style::new().fg(theme.text).bold();
+    let query = styling::with_cursor(
+        palette.query.value(),
+        palette.query.cursor(),
+        symbols.cursor,
+        input_style,
+    );
+    let prompt = &symbols.prompt;
+    let matches = palette.matches(&commands);
+    let selected = palette.selected();
+    let skip = (selected + 1).saturating_sub(palette::HEIGHT);
+    let rows = matches
+        .iter()
+        .enumerate()
+        .skip(skip)
+        .take(palette::HEIGHT)
+        .map(|(idx, command)| {
+            let label = &command.label;
+            let style = match idx == selected {
+                true => Style::new().fg(theme.badge_fg).bg(theme.accent),
+                false => Style::new().fg(theme.text),
+            };
+            ui! { <Span .style={style}>" {label} "</Span> }
+        });
+    ui! {
+        <Block Padding::new(2, 2, 1, 1)>
+            <Block Direction::Horizontal>
+                <Span .style={Style::new().fg(theme.accent)}>"{prompt}"</Span>
+                <SpanLine .spans={query}/>
+            </Block>
+            {rows}
+            <Maybe
+                .cond={matches.is_empty()}
+                .then={ui! { <Span .style={Style::new().fg(theme.text_dim)}>"no matching commands"</Span> }}
+            />
+        </Block>
+    }
+}
+
+/// Every key binding in use, listed from the [`keymap::KeyMap`].
+#[subview]
+fn help_overlay(sections: Vec<(Scope, Vec<Binding>)>, theme: Theme, keys: &KeyMap) -> View {
+    let width = sections
+        .iter()
+        .flat_map(|(_, bindings)| bindings)
+        .map(|binding| styling::width(&binding.keys))
+        .max()
+        .unwrap_or_default();
+    let sections = sections.into_iter().map(|(scope, bindings)| {
+        let title = scope.title();
+        let rows = bindings.into_iter().map(|binding| {
+            let keys = format!("{:<width$}  ", binding.keys);
+            let action = binding.action;
+            ui! {
+                <Block Direction::Horizontal>
+                    <Span .style={Style::new().fg(theme.help_key)}>"{keys}"</Span>
+                    <Span .style={Style::new().fg(theme.help_desc)}>"{action}"</Span>
+                </Block>
+            }
+        });
+        ui! {
+            <Block>
+                <Span .style={Style::new().fg(theme.accent).bold()}>"{title}"</Span>
+                {rows}
+            </Block>
+        }
+    });
+    let hint = help::footer(keys, &[(Action::DialogClose, "to close")]);
+    ui! {
+        <Block Gap(1) Padding::new(2, 2, 1, 1)>
+            {sections}
+            <Span .style={Style::new().fg(theme.text_dim)}>"{hint}"</Span>
         </Block>
     }
 }
 
+/// [`apply`], mirroring what changed to `--json-events`. The arms of `apply` that change
+/// anything on screen mark the model [dirty](Model::dirty).
+///
+/// The runtime hands the model over and takes it back with each message, which only moves it.
+/// Everything else changes it in place.
 async fn update(mut model: Model, msg: Msg) -> (Model, Effect<Msg>) {
+    queue::received();
+    model.dirty = false;
+    let before = model.json_events.is_some().then(|| Observed::of(&model));
+    let effect = apply(&mut model, msg);
+    if let (Some(json_events), Some(before)) = (&model.json_events, before) {
+        json_events.changes(&before, &model);
+    }
+    (model, effect)
+}
+
+fn apply(model: &mut Model, msg: Msg) -> Effect<Msg> {
+    if msg.is_input() {
+        if model.idle == IdleLevel::Blank && model.config.idle.blank_console {
+            idle::blank_console(false);
+        }
+        model.dirty = !model.idle.is_active() || model.dim.is_some();
+        model.last_input = model.now();
+        model.idle = IdleLevel::Active;
+        model.idle_cleared = false;
+        model.dim = None;
+    }
     match msg {
         Msg::Quit => unreachable!(),
+        Msg::Terminate(signal) => {
+            model.dirty = true;
+            tracing::info!("exiting on {signal}");
+            model.forget_secrets();
+            // Leave greetd ready for the next greeter instead of halfway through a login. Its
+            // answer to the cancel, or the timeout, is what quits.
+            if matches!(
+                model.form_state,
+                FormState::CreatedSession | FormState::PickingDesktop
+            ) {
+                model.send(greetd::Request::CancelSession);
+                model.audit(audit::Outcome::Cancelled, None);
+                model.terminating = true;
+                return Effect::new(|tx| async move {
+                    tokio::time::sleep(TERMINATE_TIMEOUT).await;
+                    _ = queue::send(&tx, Msg::Quit).await;
+                });
+            }
+            Effect::new(|tx| async move {
+                _ = queue::send(&tx, Msg::Quit).await;
+            })
+        }
         Msg::Error(report) => {
-            panic!("{report:?}")
+            model.dirty = true;
+            // Whatever failed could have quoted a request to greetd.
+            tracing::error!("{}", secret::scrub(&format!("{report:?}")));
+            model
+                .event_log
+                .push(format!("error: {}", secret::scrub(&report.to_string())));
+            let chain = report
+                .chain()
+                .map(|err| secret::scrub(&err.to_string()).into_owned())
+                .enumerate()
+                .map(|(idx, err)| match idx {
+                    0 => err,
+                    _ => format!("  caused by: {err}"),
+                })
+                .collect::<Vec<_>>()
+                .join("\n");
+            let modal = Modal::new("Something went wrong", &chain)
+                .button("Retry", Msg::Retry)
+                .closable(false);
+            let modal = match model.can_quit() {
+                true => modal.button("Quit", Msg::Quit),
+                false => modal,
+            };
+            model.error = Some(report);
+            model.modal = Some(modal);
+            model.spinner = None;
+            Effect::none()
+        }
+        Msg::Retry => {
+            model.dirty = true;
+            model.event_log.push("restarting greetd");
+            let (cli_args, req_rx) = (model.cli_args, model.req_rx.clone());
+            model.error = None;
+            model.form_state = FormState::Idle;
+            model.focus = Focus::UsernameField;
+            Effect::new(move |tx| run_greetd(cli_args, req_rx.clone(), tx))
+        }
+        Msg::GreetdRestarting(report, delay) => {
+            model.dirty = true;
+            tracing::warn!("{}", secret::scrub(&format!("{report:?}")));
+            model.event_log.push(format!(
+                "greetd: {}, restarting in {:.1}s",
+                secret::scrub(&report.to_string()),
+                delay.as_secs_f32()
+            ));
+            // The login in progress went with the connection.
+            if matches!(
+                model.form_state,
+                FormState::CreatedSession | FormState::PickingDesktop
+            ) {
+                model.audit(audit::Outcome::Cancelled, Some("greetd restarted"));
+                model.form_state = FormState::Idle;
+                model.auth.clear();
+                model.spinner = None;
+            }
+            let id = model
+                .toasts
+                .push("lost greetd, reconnecting".into(), ToastLevel::Warning);
+            toast::dismiss_after(id, toast::TOAST_DURATION)
         }
         Msg::GreetdRes(res) => {
-            let (form_state, form_effect) = model.form_state.clone().update(res.clone());
+            model.dirty = true;
+            model.event_log.response(&res);
+            if let Some(recorder) = &model.recorder {
+                recorder.greetd(&res);
+            }
+            // The cancel sent on the way out went through.
+            if model.terminating {
+                return Effect::new(|tx| async move {
+                    _ = queue::send(&tx, Msg::Quit).await;
+                });
+            }
+            if let greetd::Response::AuthMessage {
+                auth_message_type,
+                auth_message,
+            } = &res
+            {
+                model.auth.push(*auth_message_type, auth_message.clone());
+            }
+            match &res {
+                greetd::Response::Success
+                    if matches!(model.form_state, FormState::CreatedSession) =>
+                {
+                    model.metrics.login_answered();
+                    model.audit(audit::Outcome::Success, None);
+                }
+                greetd::Response::Error { description, .. } => {
+                    model.metrics.login_answered();
+                    model.audit(audit::Outcome::Failure, Some(&**description));
+                }
+                _ => {}
+            }
+            let (form_state, form_effect) = model.form_state.clone().update(res);
             match form_effect {
                 FormEffect::None => {}
-                FormEffect::SendPassword => {
-                    model
-                        .req_tx
-                        .send_async(greetd::Request::PostAuthMessageResponse {
-                            response: Some(model.field(Field::Password).value().into()),
-                        })
-                        .await
-                        .unwrap();
+                // The first question is the one the password was typed for.
+                FormEffect::Answer if model.auth.step() == 1 => {
+                    model.send(greetd::Request::PostAuthMessageResponse {
+                        response: Some(model.password.value().into()),
+                    });
+                    model.password.clear();
+                    model.auth.answer();
+                }
+                FormEffect::Answer => {
+                    model.password.clear();
+                    model.focus = Focus::PasswordField;
+                    model.spinner = None;
+                }
+                FormEffect::Acknowledge => {
+                    model.send(greetd::Request::PostAuthMessageResponse { response: None });
+                }
+                FormEffect::FocusDesktopPicker => {
+                    model.focus = Focus::DesktopPicker;
+                    model.remember_username();
                 }
-                FormEffect::FocusDesktopPicker => model.focus = Focus::DesktopPicker,
             };
-            (
-                Model {
-                    form_state,
-                    last_response: Some(res),
-                    ..model
-                },
-                Effect::none(),
-            )
+            let failed = !matches!(model.form_state, FormState::LoginFailed(..))
+                && matches!(form_state, FormState::LoginFailed(..));
+            let mut ticks = Duration::ZERO;
+            if failed && model.config.greeting.retype_on_failure && model.typewriter.is_some() {
+                let typewriter = Typewriter::start(
+                    model.greeting_len(),
+                    Duration::from_millis(model.config.greeting.typewriter_delay_ms),
+                );
+                ticks = ticks.max(typewriter.duration());
+                model.typewriter = Some(typewriter);
+            }
+            if failed && model.config.animation.enabled {
+                model.shake = Some(Tween::new(0.0, 1.0, SHAKE, Easing::Linear));
+                ticks = ticks.max(SHAKE);
+            }
+            if !ticks.is_zero() {
+                model.ticker.play(ticks, model.config.animation.fps);
+            }
+            if !matches!(form_state, FormState::CreatedSession) {
+                model.spinner = None;
+            }
+            model.form_state = form_state;
+            Effect::none()
+        }
+        Msg::UsernameUpdate(input) => {
+            model.dirty = true;
+            model.username_hint = None;
+            model.completion = Completion::default();
+            model.history.stop_browsing();
+            model.username = input;
+            Effect::none()
+        }
+        Msg::PasswordUpdate(input) => {
+            model.dirty = true;
+            model.password = input;
+            Effect::none()
+        }
+        Msg::ViUpdate(vi, input) => {
+            model.dirty = true;
+            model.vi = Some(vi);
+            model.username = input;
+            model.completion = Completion::default();
+            model.history.stop_browsing();
+            Effect::none()
+        }
+        Msg::Complete(completion, input) => {
+            model.dirty = true;
+            model.completion = completion;
+            model.history.stop_browsing();
+            model.username = input;
+            Effect::none()
+        }
+        Msg::BrowseHistory(history, input) => {
+            model.dirty = true;
+            model.history = history;
+            model.completion = Completion::default();
+            model.username = input;
+            Effect::none()
+        }
+        Msg::FocusOn(focus) => {
+            model.dirty = true;
+            model.completion = Completion::default();
+            let user_theme = match (&model.focus, &focus) {
+                (Focus::UserMenu | Focus::UsernameField, Focus::PasswordField)
+                    if model.config.theme.per_user =>
+                {
+                    Some(Str::from(model.username.value()))
+                }
+                _ => None,
+            };
+            if model.config.animation.enabled {
+                model.focus_fade = Tween::new(0.0, 1.0, FOCUS_FADE, Easing::EaseOut);
+                model.ticker.play(FOCUS_FADE, model.config.animation.fps);
+            }
+            let effect = match user_theme {
+                Some(username) => Effect::new(move |tx| {
+                    let username = username.clone();
+                    async move {
+                        let theme = theme::load_user(&username).ok().flatten();
+                        _ = queue::send(&tx, Msg::UserTheme(theme)).await;
+                    }
+                }),
+                None => Effect::none(),
+            };
+            match (&model.focus, &focus) {
+                (Focus::PasswordField, Focus::Keyboard) if !model.username.value().is_empty() => {
+                    model.keyboard_target = Field::Password;
+                }
+                (Focus::UsernameField | Focus::PasswordField, Focus::Keyboard) => {
+                    model.keyboard_target = Field::Username;
+                }
+                _ => {}
+            }
+            model.focus = focus;
+            model.refresh_avatar();
+            effect
+        }
+        Msg::CapsLock(caps_lock) => {
+            if caps_lock == model.caps_lock {
+                return Effect::none();
+            }
+            model.dirty = true;
+            let effect = match caps_lock {
+                true => {
+                    let id = model
+                        .toasts
+                        .push("caps lock is on".into(), ToastLevel::Warning);
+                    toast::dismiss_after(id, toast::TOAST_DURATION)
+                }
+                false => Effect::none(),
+            };
+            model.caps_lock = caps_lock;
+            effect
+        }
+        Msg::Toast(text, level) => {
+            model.dirty = true;
+            let id = model.toasts.push(text, level);
+            toast::dismiss_after(id, toast::TOAST_DURATION)
+        }
+        Msg::OpenModal(modal) => {
+            model.dirty = true;
+            model.modal = Some(modal);
+            Effect::none()
+        }
+        Msg::ModalFocus(by) => {
+            if let Some(modal) = &mut model.modal {
+                modal.move_focus(by);
+                model.dirty = true;
+            }
+            Effect::none()
+        }
+        Msg::ModalPress => {
+            // Buttons like Cancel only close the modal.
+            let modal = model.modal.take();
+            model.dirty |= modal.is_some();
+            let pressed = modal.and_then(|modal| modal.press());
+            let effect = match pressed.filter(|msg| !matches!(msg, Msg::Nothing)) {
+                Some(msg) => Effect::new(move |tx| {
+                    let msg = msg.clone();
+                    async move {
+                        _ = queue::send(&tx, msg).await;
+                    }
+                }),
+                None => Effect::none(),
+            };
+            effect
+        }
+        Msg::UserMenuMove(by) => {
+            let len = model.users.len() as isize;
+            if len > 0 {
+                let selected = (model.user_menu_selected as isize + by).rem_euclid(len) as usize;
+                model.dirty |= selected != model.user_menu_selected;
+                model.user_menu_selected = selected;
+            }
+            model.refresh_avatar();
+            Effect::none()
+        }
+        Msg::PickUser(name) => {
+            model.dirty = true;
+            model.history.stop_browsing();
+            model.username = Input::new(name.to_string());
+            Effect::new(|tx| async move {
+                _ = queue::send(&tx, Msg::FocusOn(Focus::PasswordField)).await;
+            })
+        }
+        Msg::PickerMove(by) => {
+            // Stops at either end, like the list it replaced.
+            let last = model.desktops.len().saturating_sub(1) as isize;
+            let selected = (model.desktop_selected as isize + by).clamp(0, last) as usize;
+            model.dirty |= selected != model.desktop_selected;
+            model.desktop_selected = selected;
+            model.desktop_offset = scroll_to(
+                model.desktop_offset,
+                model.desktop_selected,
+                SESSION_PICKER_HEIGHT,
+            );
+            Effect::none()
+        }
+        Msg::PowerMenuMove(by) => {
+            let len = model.power_actions.len().max(1) as isize;
+            let selected = (model.power_selected as isize + by).rem_euclid(len) as usize;
+            model.dirty |= selected != model.power_selected;
+            model.power_selected = selected;
+            Effect::none()
+        }
+        Msg::Scheduled(scheduled) => {
+            model.dirty |= scheduled != model.scheduled;
+            model.scheduled = scheduled;
+            Effect::none()
+        }
+        Msg::PowerActions(actions) => {
+            model.dirty = true;
+            if actions.is_empty() && model.focus.is_power_menu() {
+                model.focus = Focus::PasswordField;
+            }
+            model.power_selected = 0;
+            model.power_actions = actions;
+            Effect::none()
+        }
+        Msg::KeyboardMove(rows, cols) => {
+            let moved = model
+                .keyboard
+                .as_mut()
+                .is_some_and(|keyboard| keyboard.move_by(rows, cols));
+            model.dirty |= moved;
+            // Moving off the top or bottom row leaves the keyboard.
+            let focus = match (moved, rows) {
+                (true, _) | (false, 0) => None,
+                (false, rows) if rows < 0 => Some(model.keyboard_target.focus()),
+                (false, _) => model.has_power_menu().then_some(Focus::PowerMenu),
+            };
+            let effect = match focus {
+                Some(focus) => Effect::new(move |tx| {
+                    let focus = focus.clone();
+                    async move {
+                        _ = queue::send(&tx, Msg::FocusOn(focus)).await;
+                    }
+                }),
+                None => Effect::none(),
+            };
+            effect
+        }
+        Msg::KeyboardPress => {
+            model.dirty = true;
+            let Some(key) = model.keyboard.as_mut().and_then(Keyboard::press) else {
+                return Effect::none();
+            };
+            let msg = match (key, model.keyboard_target.clone()) {
+                (keyboard::Key::Enter, Field::Username) => {
+                    model.keyboard_target = Field::Password;
+                    None
+                }
+                (keyboard::Key::Enter, Field::Password) if model.auth.question().is_some() => {
+                    Some(Msg::AnswerPrompt)
+                }
+                (keyboard::Key::Enter, Field::Password) => Some(Msg::SubmitLogin),
+                (key, field) => {
+                    let request = match key {
+                        keyboard::Key::Char(c) => Some(InputRequest::InsertChar(c)),
+                        keyboard::Key::Space => Some(InputRequest::InsertChar(' ')),
+                        keyboard::Key::Backspace => Some(InputRequest::DeletePrevChar),
+                        keyboard::Key::Shift | keyboard::Key::Enter => None,
+                    };
+                    if let Some(request) = request {
+                        match field {
+                            Field::Username => model.username.handle(request),
+                            Field::Password => model.password.handle(request),
+                        };
+                    }
+                    None
+                }
+            };
+            let effect = match msg {
+                Some(msg) => Effect::new(move |tx| {
+                    let msg = msg.clone();
+                    async move {
+                        _ = queue::send(&tx, msg).await;
+                    }
+                }),
+                None => Effect::none(),
+            };
+            effect
+        }
+        Msg::Power(action) => {
+            model.dirty = true;
+            let label = action.label();
+            model.event_log.push(format!("power: {label}"));
+            // A preview or a bug report's trace shouldn't turn off the machine it runs on.
+            if model.cli_args.is_pretend() {
+                let id = model.toasts.push(
+                    format!("{label} is skipped when the login is pretend").into(),
+                    ToastLevel::Info,
+                );
+                return toast::dismiss_after(id, toast::TOAST_DURATION);
+            }
+            let config = model.config;
+            Effect::new(move |tx| async move {
+                if let Err(err) = power::run(action, &config.power).await {
+                    let text = format!("{label} failed: {err}").into();
+                    _ = queue::send(&tx, Msg::Toast(text, ToastLevel::Error)).await;
+                }
+            })
+        }
+        Msg::Connectivity(connectivity) => {
+            model.dirty |= model.connectivity != Some(connectivity);
+            model.connectivity = Some(connectivity);
+            Effect::none()
+        }
+        Msg::Brightness(by) => {
+            let config = model.config;
+            Effect::new(move |tx| async move {
+                let device = config.backlight.device.as_deref();
+                let (text, level) = match backlight::step(device, by) {
+                    Ok(percent) => (format!("Brightness {percent}%"), ToastLevel::Info),
+                    Err(err) => (
+                        format!("Can't change the brightness: {err}"),
+                        ToastLevel::Error,
+                    ),
+                };
+                _ = queue::send(&tx, Msg::Toast(text.into(), level)).await;
+            })
+        }
+        Msg::NextKeymap => {
+            let keymaps = &model.config.console_keymaps.keymaps;
+            let Some(keymap) = console_keymap::next(keymaps, model.keymap.as_deref()) else {
+                return Effect::none();
+            };
+            let keymap: Str = keymap.into();
+            Effect::new(move |tx| {
+                let keymap = keymap.clone();
+                async move {
+                    let msg = match console_keymap::load(&keymap).await {
+                        Ok(()) => Msg::KeymapLoaded(keymap),
+                        Err(err) => {
+                            let text = format!("Can't switch the keymap: {err}").into();
+                            Msg::Toast(text, ToastLevel::Error)
+                        }
+                    };
+                    _ = queue::send(&tx, msg).await;
+                }
+            })
+        }
+        Msg::KeymapLoaded(keymap) => {
+            model.dirty = true;
+            model.event_log.push(format!("keymap: {keymap}"));
+            model.keymap = Some(keymap.to_string());
+            Effect::none()
+        }
+        Msg::CloseModal => {
+            if model.modal.as_ref().is_some_and(|modal| modal.closable) {
+                model.modal = None;
+                model.dirty = true;
+            }
+            Effect::none()
+        }
+        Msg::DismissToast(id) => {
+            model.dirty |= model.toasts.dismiss(id);
+            Effect::none()
+        }
+        Msg::WidgetOutput(idx, output) => {
+            if let Some(slot) = model.widget_outputs.get_mut(idx)
+                && slot.as_ref() != Some(&output)
+            {
+                *slot = Some(output);
+                model.dirty = true;
+            }
+            Effect::none()
+        }
+        Msg::Chord(chord) => {
+            model.dirty |= chord != model.chord;
+            model.chord = chord;
+            Effect::none()
+        }
+        Msg::ToggleDebug => {
+            model.dirty = true;
+            model.debug_open = !model.debug_open;
+            Effect::none()
+        }
+        Msg::TogglePalette => {
+            model.dirty = true;
+            model.palette = match model.palette {
+                Some(_) => None,
+                None => Some(Palette::default()),
+            };
+            Effect::none()
+        }
+        Msg::PaletteQuery(query) => {
+            if let Some(palette) = &mut model.palette {
+                palette.set_query(query);
+                model.dirty = true;
+            }
+            Effect::none()
+        }
+        Msg::PaletteMove(by) => {
+            let commands = palette_commands(model);
+            if let Some(palette) = &mut model.palette {
+                let len = palette.matches(&commands).len();
+                palette.move_by(by, len);
+                model.dirty = true;
+            }
+            Effect::none()
+        }
+        Msg::PaletteRun => {
+            let commands = palette_commands(model);
+            model.dirty |= model.palette.is_some();
+            let Some(command) = model
+                .palette
+                .take()
+                .and_then(|palette| palette.pick(&commands).cloned())
+            else {
+                return Effect::none();
+            };
+            model.event_log.push(format!("palette: {}", command.label));
+            Effect::new(move |tx| {
+                let msg = command.msg.clone();
+                async move {
+                    _ = queue::send(&tx, msg).await;
+                }
+            })
+        }
+        Msg::ReloadConfig => {
+            model.dirty = true;
+            let (text, level) = match load_config(model.cli_args) {
+                Ok(config) => {
+                    // Everything holds on to the config as `&'static`, so the old one is
+                    // leaked along with it. Reloads are rare enough for that not to matter.
+                    let config = Box::leak(Box::new(config)) as &'static Config;
+                    model.config = config;
+                    model.theme = Theme::resolve(&config.theme, model.appearance);
+                    if config.input.vi_mode != model.vi.is_some() {
+                        model.vi = config.input.vi_mode.then(Vi::default);
+                    }
+                    model.event_log.push("config reloaded");
+                    ("config reloaded", ToastLevel::Info)
+                }
+                Err(err) => {
+                    model.event_log.push(format!("config reload failed: {err}"));
+                    ("couldn't reload the config", ToastLevel::Error)
+                }
+            };
+            let id = model.toasts.push(text.into(), level);
+            toast::dismiss_after(id, toast::TOAST_DURATION)
+        }
+        Msg::ToggleHelp => {
+            model.dirty = true;
+            model.help_open = !model.help_open;
+            Effect::none()
+        }
+        Msg::ToggleLog => {
+            model.dirty = true;
+            model.log_open = !model.log_open;
+            model.log_scroll = 0;
+            Effect::none()
+        }
+        Msg::ScrollLog(by) => {
+            let max = model.event_log.max_scroll(LOG_HEIGHT);
+            let scroll = model.log_scroll.saturating_add_signed(by).min(max);
+            model.dirty |= scroll != model.log_scroll;
+            model.log_scroll = scroll;
+            Effect::none()
+        }
+        Msg::ScrollMotd(by) => {
+            let max = motd::max_scroll(&model.motd, model.config.motd.height as usize);
+            let scroll = model.motd_scroll.saturating_add_signed(by).min(max);
+            model.dirty |= scroll != model.motd_scroll;
+            model.motd_scroll = scroll;
+            Effect::none()
         }
-        Msg::FieldUpdate(field, input) => {
-            model.fields[field as usize] = input;
-            (model, Effect::none())
+        Msg::PreviewState(form_state) => {
+            model.dirty = true;
+            let focus = match form_state {
+                FormState::PickingDesktop => Focus::DesktopPicker,
+                _ => Focus::UsernameField,
+            };
+            model.spinner = matches!(form_state, FormState::CreatedSession)
+                .then(|| (Instant::now(), model.spinner_ticks()));
+            model.form_state = form_state;
+            model.focus = focus;
+            Effect::none()
+        }
+        Msg::UserTheme(user) => {
+            model.dirty = true;
+            let theme = match user {
+                Some(user) => {
+                    let appearance = match user.appearance {
+                        AppearancePreference::Auto => model.appearance,
+                        preference => preference.resolve(),
+                    };
+                    Theme::resolve(&user, appearance)
+                }
+                None => Theme::resolve(&model.config.theme, model.appearance),
+            };
+            model.theme = theme;
+            Effect::none()
         }
-        Msg::FocusOn(focus) => (Model { focus, ..model }, Effect::none()),
         Msg::SubmitLogin => {
-            model
-                .req_tx
-                .send_async(greetd::Request::CreateSession {
-                    username: model.field(Field::Username).value().into(),
-                })
-                .await
-                .unwrap();
-            let form_state = FormState::CreatedSession;
-
-            (
-                Model {
-                    form_state,
-                    ..model
-                },
-                Effect::none(),
-            )
+            model.dirty = true;
+            // greetd would only fail these after a round trip through PAM.
+            if let Some(invalid) = users::check_username(model.username.value()) {
+                model.username_hint = Some(invalid);
+                model.focus = Focus::UsernameField;
+                return Effect::none();
+            }
+            // Like login(1), only root gets in while nologin(5) is there. A script says itself
+            // whether it is.
+            if model.cli_args.script.is_none() {
+                model.nologin = nologin::read();
+            }
+            if let Some(message) = &model.nologin
+                && users::uid(model.username.value()) != Some(0)
+            {
+                model.event_log.push("login refused: logins are disabled");
+                model.audit(audit::Outcome::Failure, Some("nologin"));
+                model.form_state =
+                    FormState::LoginFailed(ErrorType::Error, message.as_str().into());
+                model.password.clear();
+                return Effect::none();
+            }
+            if !model.login_throttle.allow(model.now()) {
+                let id = model
+                    .toasts
+                    .push("too many logins, wait a moment".into(), ToastLevel::Warning);
+                return toast::dismiss_after(id, toast::TOAST_DURATION);
+            }
+            model.auth.start();
+            model.send(greetd::Request::CreateSession {
+                username: model.username.value().into(),
+            });
+            model.audit(audit::Outcome::Attempt, None);
+            model.form_state = FormState::CreatedSession;
+            model.spinner = Some((Instant::now(), model.spinner_ticks()));
+            Effect::none()
+        }
+        Msg::CancelSession => {
+            model.dirty = true;
+            if matches!(
+                model.form_state,
+                FormState::CreatedSession | FormState::PickingDesktop
+            ) {
+                model.send(greetd::Request::CancelSession);
+                model.audit(audit::Outcome::Cancelled, None);
+            }
+            model.event_log.push("login cancelled");
+            model.password.clear();
+            model.form_state = FormState::Idle;
+            model.spinner = None;
+            model.focus = Focus::UsernameField;
+            Effect::none()
         }
-        Msg::Nothing => (model, Effect::none()),
+        Msg::AnswerPrompt => {
+            model.dirty = true;
+            model.send(greetd::Request::PostAuthMessageResponse {
+                response: Some(model.password.value().into()),
+            });
+            model.password.clear();
+            model.auth.answer();
+            model.spinner = Some((Instant::now(), model.spinner_ticks()));
+            Effect::none()
+        }
+        Msg::Nothing => Effect::none(),
+        // A timer fired, and whatever is animating or shows the time moved on.
+        Msg::Tick => {
+            model.dirty = true;
+            if let Some(backdrop) = &mut model.backdrop {
+                backdrop.advance(Instant::now());
+            }
+            if model.shake.is_some_and(|shake| shake.is_done()) {
+                model.shake = None;
+            }
+            let idle_for = model.now().saturating_duration_since(model.last_input);
+            if !model.idle_cleared && idle::clears_after(idle_for, &model.config.idle) {
+                model.reset();
+                model.idle_cleared = true;
+            }
+            let idle = IdleLevel::after(idle_for, &model.config.idle);
+            if idle == IdleLevel::Blank && model.idle != IdleLevel::Blank {
+                if model.config.idle.blank_console {
+                    idle::blank_console(true);
+                }
+            } else if model.idle.is_active() && !idle.is_active() {
+                let fade = match model.config.animation.enabled {
+                    true => idle::DIM_FADE,
+                    false => Duration::ZERO,
+                };
+                model.dim = Some(Tween::new(0.0, idle::DIM_AMOUNT, fade, Easing::EaseInOut));
+                model.ticker.play(fade, model.config.animation.fps);
+            }
+            model.idle = idle;
+            Effect::none()
+        }
+        Msg::Wake => Effect::none(),
         Msg::StartShell => {
-            println!("DONE");
-            model
-                .req_tx
-                .send_async(greetd::Request::StartSession {
-                    cmd: ["/bin/sh".into()].into(),
-                    env: [].into(),
-                })
-                .await
-                .unwrap();
-            (
-                model,
-                Effect::new(async |tx| {
-                    tx.send_async(Msg::Quit).await.unwrap();
-                }),
-            )
+            model.dirty = true;
+            model.send(greetd::Request::StartSession {
+                cmd: ["/bin/sh".into()].into(),
+                env: [].into(),
+            });
+            model.forget_secrets();
+            Effect::new(async |tx| {
+                _ = queue::send(&tx, Msg::Quit).await;
+            })
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::time::Instant;
+
+    use clap::Parser;
+    use mana_tui::prelude::*;
+
+    use crate::config::{Config, ThemeConfig};
+    use crate::greetd::ErrorType;
+    use crate::metrics::Metrics;
+    use crate::snapshot::{assert_golden, render};
+    use crate::term::Appearance;
+    use crate::theme::Theme;
+    use crate::{
+        CliArgs, DesktopPicker, Field, FieldInput, FormState, ModalDialog, Msg, StatusLine,
+        error_details, init, scroll_to, update,
+    };
+
+    fn theme() -> Theme {
+        Theme::resolve(&ThemeConfig::default(), Appearance::Dark)
+    }
+
+    #[test]
+    fn form_snapshot() -> color_eyre::Result<()> {
+        let config = Config::default();
+        let view = ui! {
+            <Block>
+                <FieldInput
+                    .field={Field::Username}
+                    .value={"alice"}
+                    .cursor={5}
+                    .label={"username"}
+                    .focused={true}
+                    .highlight={1.0}
+                    .theme={theme()}
+                    .symbols={&config.symbols}
+                />
+                <FieldInput
+                    .field={Field::Password}
+                    .value={""}
+                    .cursor={0}
+                    .label={"password"}
+                    .focused={false}
+                    .highlight={0.0}
+                    .theme={theme()}
+                    .symbols={&config.symbols}
+                />
+            </Block>
+        };
+        assert_golden("form", &render(view, 40, 2)?);
+        Ok(())
+    }
+
+    #[test]
+    fn scrolls_only_as_far_as_needed() {
+        assert_eq!(scroll_to(0, 3, 4), 0);
+        assert_eq!(scroll_to(0, 4, 4), 1);
+        assert_eq!(scroll_to(3, 5, 4), 3);
+        assert_eq!(scroll_to(3, 1, 4), 1);
+    }
+
+    #[tokio::test]
+    async fn picker_snapshot() -> color_eyre::Result<()> {
+        // The demo's sessions, rather than whatever this machine has installed.
+        let cli_args = Box::leak(Box::new(CliArgs::parse_from(["impolite", "--demo"])));
+        let config = Box::leak(Box::new(Config::default()));
+        let metrics = Metrics::new(Instant::now());
+        let (model, _) = init(cli_args, config, Appearance::Dark, None, None, metrics).await;
+        let view = ui! { <DesktopPicker .model={&model}/> };
+        assert_golden("picker", &render(view, 60, 8)?);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn only_changes_mark_the_model_dirty() {
+        let cli_args = Box::leak(Box::new(CliArgs::parse_from(["impolite", "--demo"])));
+        let config = Box::leak(Box::new(Config::default()));
+        let metrics = Metrics::new(Instant::now());
+        let (model, _) = init(cli_args, config, Appearance::Dark, None, None, metrics).await;
+
+        // Already at the top.
+        let (model, _) = update(model, Msg::PickerMove(-1)).await;
+        assert!(!model.dirty);
+        let (model, _) = update(model, Msg::PickerMove(1)).await;
+        assert!(model.dirty);
+        let (model, _) = update(model, Msg::Nothing).await;
+        assert!(!model.dirty);
+        let (model, _) = update(model, Msg::Tick).await;
+        assert!(model.dirty);
+    }
+
+    #[test]
+    fn error_snapshot() -> color_eyre::Result<()> {
+        let config = Config::default();
+        let form_state =
+            FormState::LoginFailed(ErrorType::AuthError, "pam_authenticate: AUTH_ERR".into());
+        let view = ui! {
+            <StatusLine
+                .form_state={&form_state}
+                .width={40}
+                .theme={theme()}
+                .symbols={&config.symbols}
+                .keys={&config.keys}
+            />
+        };
+        assert_golden("error_line", &render(view, 40, 2)?);
+
+        let modal = error_details(&ErrorType::AuthError, "pam_authenticate: AUTH_ERR");
+        let view = ui! { <ModalDialog .modal={&modal} .theme={theme()} .keys={&config.keys}/> };
+        assert_golden("error_details", &render(view, 60, 12)?);
+        Ok(())
+    }
+}
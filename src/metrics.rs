@@ -0,0 +1,151 @@
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use color_eyre::{Result, eyre::Context};
+use serde::Serialize;
+
+/// Timings of the greeter, logged as they happen and summed up for `--metrics-file`.
+#[derive(Clone)]
+pub struct Metrics(Arc<Mutex<Inner>>);
+
+struct Inner {
+    started: Instant,
+    first_frame: Option<Duration>,
+    frames: Timings,
+    logins: Timings,
+    /// When the last `CreateSession` went out, until greetd settles it.
+    login_sent: Option<Instant>,
+}
+
+#[derive(Default)]
+struct Timings {
+    count: u64,
+    total: Duration,
+    max: Duration,
+}
+
+impl Timings {
+    fn add(&mut self, took: Duration) {
+        self.count += 1;
+        self.total += took;
+        self.max = self.max.max(took);
+    }
+
+    fn summary(&self) -> TimingSummary {
+        let mean = match self.count {
+            0 => Duration::ZERO,
+            count => self.total / count as u32,
+        };
+        TimingSummary {
+            count: self.count,
+            mean_ms: millis(mean),
+            max_ms: millis(self.max),
+        }
+    }
+}
+
+/// What `--metrics-file` gets on exit.
+#[derive(Serialize, Debug, PartialEq)]
+pub struct Summary {
+    /// From the start of the process to the first frame built.
+    pub first_frame_ms: Option<f64>,
+    /// Building the view for each frame.
+    pub frames: TimingSummary,
+    /// From `CreateSession` to greetd's success or error, which is mostly PAM.
+    pub logins: TimingSummary,
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+pub struct TimingSummary {
+    pub count: u64,
+    pub mean_ms: f64,
+    pub max_ms: f64,
+}
+
+fn millis(duration: Duration) -> f64 {
+    duration.as_nanos() as f64 / 1_000_000.0
+}
+
+impl Metrics {
+    /// Times are counted from `started`, which should be as early in `main` as it gets.
+    pub fn new(started: Instant) -> Self {
+        Self(Arc::new(Mutex::new(Inner {
+            started,
+            first_frame: None,
+            frames: Timings::default(),
+            logins: Timings::default(),
+            login_sent: None,
+        })))
+    }
+
+    /// A frame's view took `took` to build.
+    pub fn frame(&self, took: Duration) {
+        let mut inner = self.0.lock().unwrap();
+        if inner.first_frame.is_none() {
+            let elapsed = inner.started.elapsed();
+            tracing::info!(ms = millis(elapsed), "first frame");
+            inner.first_frame = Some(elapsed);
+        }
+        tracing::trace!(ms = millis(took), "built frame");
+        inner.frames.add(took);
+    }
+
+    pub fn login_sent(&self) {
+        self.0.lock().unwrap().login_sent = Some(Instant::now());
+    }
+
+    /// greetd said yes or no to the login sent last.
+    pub fn login_answered(&self) {
+        let mut inner = self.0.lock().unwrap();
+        if let Some(sent) = inner.login_sent.take() {
+            let took = sent.elapsed();
+            tracing::info!(ms = millis(took), "login answered");
+            inner.logins.add(took);
+        }
+    }
+
+    pub fn summary(&self) -> Summary {
+        let inner = self.0.lock().unwrap();
+        Summary {
+            first_frame_ms: inner.first_frame.map(millis),
+            frames: inner.frames.summary(),
+            logins: inner.logins.summary(),
+        }
+    }
+
+    /// Writes [`Metrics::summary`] to `path` as JSON.
+    pub fn write(&self, path: &Path) -> Result<()> {
+        let json = serde_json::to_string_pretty(&self.summary())?;
+        std::fs::write(path, json + "\n")
+            .wrap_err_with(|| format!("failed to write metrics to {}", path.display()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use crate::metrics::{Metrics, TimingSummary};
+
+    #[test]
+    fn sums_up_frames() {
+        let metrics = Metrics::new(Instant::now());
+        metrics.frame(Duration::from_millis(2));
+        metrics.frame(Duration::from_millis(4));
+        metrics.login_answered();
+
+        let summary = metrics.summary();
+        assert!(summary.first_frame_ms.is_some());
+        assert_eq!(
+            summary.frames,
+            TimingSummary {
+                count: 2,
+                mean_ms: 3.0,
+                max_ms: 4.0,
+            }
+        );
+        // Nothing was sent, so there's nothing to time.
+        assert_eq!(summary.logins.count, 0);
+    }
+}
@@ -0,0 +1,82 @@
+use crate::{Msg, Str};
+
+/// A dialog drawn over a dimmed screen, which takes every key until it is closed.
+#[derive(Debug, Clone)]
+pub struct Modal {
+    pub title: Str,
+    pub body: Vec<Str>,
+    pub buttons: Vec<Button>,
+    /// Index of the button Enter presses.
+    pub focused: usize,
+    /// Whether Esc closes it, as opposed to only its buttons.
+    pub closable: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct Button {
+    pub label: Str,
+    /// Sent when the button is pressed, after the modal closes.
+    pub msg: Msg,
+}
+
+impl Modal {
+    pub fn new(title: impl Into<Str>, body: &str) -> Self {
+        Self {
+            title: title.into(),
+            body: body.lines().map(Str::from).collect(),
+            buttons: Vec::new(),
+            focused: 0,
+            closable: true,
+        }
+    }
+
+    pub fn button(mut self, label: impl Into<Str>, msg: Msg) -> Self {
+        self.buttons.push(Button {
+            label: label.into(),
+            msg,
+        });
+        self
+    }
+
+    pub fn closable(mut self, closable: bool) -> Self {
+        self.closable = closable;
+        self
+    }
+
+    /// Moves the focus by `by` buttons, wrapping around.
+    pub fn move_focus(&mut self, by: isize) {
+        let len = self.buttons.len() as isize;
+        if len > 0 {
+            self.focused = (self.focused as isize + by).rem_euclid(len) as usize;
+        }
+    }
+
+    /// What pressing the focused button sends, `None` for a modal without buttons.
+    pub fn press(&self) -> Option<Msg> {
+        self.buttons
+            .get(self.focused)
+            .map(|button| button.msg.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Msg;
+    use crate::modal::Modal;
+
+    #[test]
+    fn focus_wraps() {
+        let mut modal = Modal::new("Start a shell?", "")
+            .button("Cancel", Msg::Nothing)
+            .button("Start", Msg::StartShell);
+
+        modal.move_focus(-1);
+        assert_eq!(modal.focused, 1);
+        modal.move_focus(1);
+        assert_eq!(modal.focused, 0);
+        modal.move_focus(3);
+        assert!(matches!(modal.press(), Some(Msg::StartShell)));
+
+        assert!(Modal::new("empty", "").press().is_none());
+    }
+}
@@ -0,0 +1,70 @@
+use std::io::ErrorKind;
+use std::path::PathBuf;
+
+use ansi_to_tui::IntoText;
+use color_eyre::{Result, eyre::Context};
+use ratatui::text::Line;
+use tracing::instrument;
+
+/// Reads and concatenates every message of the day in `paths`, skipping missing files.
+#[instrument(err)]
+pub fn load(paths: &[PathBuf]) -> Result<Vec<Line<'static>>> {
+    let mut lines = Vec::new();
+    for path in paths {
+        let bytes = match std::fs::read(path) {
+            Ok(bytes) => bytes,
+            Err(err) if err.kind() == ErrorKind::NotFound => continue,
+            Err(err) => {
+                return Err(err)
+                    .wrap_err_with(|| format!("failed to read motd at {}", path.display()));
+            }
+        };
+        let text = bytes
+            .into_text()
+            .wrap_err_with(|| format!("failed to parse ANSI escapes in {}", path.display()))?;
+        lines.extend(text.lines);
+    }
+    while lines.last().is_some_and(|line| line.width() == 0) {
+        lines.pop();
+    }
+    Ok(lines)
+}
+
+/// The lines of a `height` lines tall window scrolled down by `scroll`, and whether there is
+/// more below it.
+pub fn window<T>(lines: &[T], scroll: usize, height: usize) -> (&[T], bool) {
+    let start = scroll.min(lines.len().saturating_sub(height));
+    let end = (start + height).min(lines.len());
+    (&lines[start..end], end < lines.len())
+}
+
+/// The largest useful scroll offset.
+pub fn max_scroll<T>(lines: &[T], height: usize) -> usize {
+    lines.len().saturating_sub(height)
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::text::Line;
+
+    use crate::motd::window;
+
+    #[test]
+    fn window_clamps_scroll() {
+        let lines = (0..5)
+            .map(|n| Line::from(n.to_string()))
+            .collect::<Vec<_>>();
+
+        let (visible, more) = window(&lines, 0, 3);
+        assert_eq!(visible.len(), 3);
+        assert!(more);
+
+        let (visible, more) = window(&lines, 10, 3);
+        assert_eq!(visible[0], Line::from("2"));
+        assert!(!more);
+
+        let (visible, more) = window(&lines, 0, 10);
+        assert_eq!(visible.len(), 5);
+        assert!(!more);
+    }
+}
@@ -0,0 +1,187 @@
+use std::path::Path;
+
+use flume::Sender;
+use futures_util::StreamExt;
+use zbus::proxy;
+
+use crate::{Msg, queue};
+
+/// The part of NetworkManager's D-Bus API the network segment uses.
+#[proxy(
+    interface = "org.freedesktop.NetworkManager",
+    default_service = "org.freedesktop.NetworkManager",
+    default_path = "/org/freedesktop/NetworkManager"
+)]
+trait NetworkManager {
+    #[zbus(property)]
+    fn connectivity(&self) -> zbus::Result<u32>;
+}
+
+/// Whether the machine can reach anything, as far as the kernel knows.
+///
+/// Network logins (SSSD, LDAP, Kerberos) fail in confusing ways without a route, so this
+/// is shown before anyone starts typing.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NetworkState {
+    /// No interface besides loopback has a link.
+    Offline,
+    /// An interface is up but there is no default route, e.g. DHCP hasn't finished.
+    NoRoute {
+        interface: String,
+    },
+    Online {
+        interface: String,
+    },
+}
+
+/// Whether NetworkManager got through to the internet the last time it checked, which also
+/// catches captive portals a route alone doesn't.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Connectivity {
+    /// NetworkManager hasn't checked, so the kernel's view is shown instead.
+    Unknown,
+    None,
+    /// A login page intercepts requests, like hotel and airport Wi-Fi.
+    Portal,
+    /// On a network that doesn't reach the internet.
+    Limited,
+    Full,
+}
+
+impl Connectivity {
+    /// From `NMConnectivityState`.
+    fn from_nm(state: u32) -> Self {
+        match state {
+            1 => Connectivity::None,
+            2 => Connectivity::Portal,
+            3 => Connectivity::Limited,
+            4 => Connectivity::Full,
+            _ => Connectivity::Unknown,
+        }
+    }
+}
+
+/// Sends [`Msg::Connectivity`] whenever NetworkManager's connectivity changes. Returns right
+/// away on machines without NetworkManager.
+pub async fn watch(tx: Sender<Msg>) {
+    let proxy = async {
+        let bus = zbus::Connection::system().await?;
+        NetworkManagerProxy::new(&bus).await
+    };
+    let proxy = match proxy.await {
+        Ok(proxy) => proxy,
+        Err(err) => {
+            tracing::debug!("not watching NetworkManager: {err}");
+            return;
+        }
+    };
+    let mut changes = proxy.receive_connectivity_changed().await;
+    match proxy.connectivity().await {
+        Ok(state) => {
+            _ = queue::send(&tx, Msg::Connectivity(Connectivity::from_nm(state))).await;
+        }
+        Err(err) => {
+            tracing::debug!("NetworkManager isn't running: {err}");
+            return;
+        }
+    }
+    while let Some(change) = changes.next().await {
+        let Ok(state) = change.get().await else {
+            continue;
+        };
+        let msg = Msg::Connectivity(Connectivity::from_nm(state));
+        if queue::send(&tx, msg).await.is_err() {
+            break;
+        }
+    }
+}
+
+/// Reads the state from sysfs and procfs.
+pub fn read() -> NetworkState {
+    let routes = [
+        std::fs::read_to_string("/proc/net/route")
+            .ok()
+            .and_then(|routes| default_route_v4(&routes)),
+        std::fs::read_to_string("/proc/net/ipv6_route")
+            .ok()
+            .and_then(|routes| default_route_v6(&routes)),
+    ];
+    if let Some(interface) = routes.into_iter().flatten().next() {
+        return NetworkState::Online { interface };
+    }
+    match links_up(Path::new("/sys/class/net")).into_iter().next() {
+        Some(interface) => NetworkState::NoRoute { interface },
+        None => NetworkState::Offline,
+    }
+}
+
+fn links_up(root: &Path) -> Vec<String> {
+    let Ok(entries) = std::fs::read_dir(root) else {
+        return Vec::new();
+    };
+    let mut links = entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_name() != "lo")
+        .filter(|entry| {
+            std::fs::read_to_string(entry.path().join("operstate"))
+                .is_ok_and(|state| state.trim() == "up")
+        })
+        .map(|entry| entry.file_name().to_string_lossy().into_owned())
+        .collect::<Vec<_>>();
+    links.sort();
+    links
+}
+
+/// The interface of the IPv4 default route in `/proc/net/route`.
+fn default_route_v4(routes: &str) -> Option<String> {
+    routes.lines().skip(1).find_map(|line| {
+        let mut fields = line.split_whitespace();
+        let interface = fields.next()?;
+        let destination = fields.next()?;
+        (destination == "00000000").then(|| interface.to_string())
+    })
+}
+
+/// The interface of the IPv6 default route in `/proc/net/ipv6_route`.
+fn default_route_v6(routes: &str) -> Option<String> {
+    routes.lines().find_map(|line| {
+        let fields = line.split_whitespace().collect::<Vec<_>>();
+        let (&destination, &prefix, &interface) = (fields.first()?, fields.get(1)?, fields.last()?);
+        let default = destination.bytes().all(|b| b == b'0') && prefix == "00";
+        (default && interface != "lo").then(|| interface.to_string())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::network::{Connectivity, default_route_v4, default_route_v6};
+
+    #[test]
+    fn find_default_routes() {
+        let v4 = "\
+Iface\tDestination\tGateway \tFlags\tRefCnt\tUse\tMetric\tMask\t\tMTU\tWindow\tIRTT
+wlan0\t0001A8C0\t00000000\t0001\t0\t0\t600\t00FFFFFF\t0\t0\t0
+wlan0\t00000000\t0101A8C0\t0003\t0\t0\t600\t00000000\t0\t0\t0
+";
+        assert_eq!(default_route_v4(v4).as_deref(), Some("wlan0"));
+        assert_eq!(
+            default_route_v4(v4.lines().take(2).collect::<Vec<_>>().join("\n").as_str()),
+            None
+        );
+
+        let v6 = "\
+00000000000000000000000000000000 00 00000000000000000000000000000000 00 fe800000000000000000000000000001 00000400 00000001 00000000 00000003     eth0
+00000000000000000000000000000000 00 00000000000000000000000000000000 00 00000000000000000000000000000000 ffffffff 00000001 00000000 00200200       lo
+";
+        assert_eq!(default_route_v6(v6).as_deref(), Some("eth0"));
+        assert_eq!(default_route_v6(v6.lines().nth(1).unwrap()), None);
+    }
+
+    #[test]
+    fn network_manager_states() {
+        assert_eq!(Connectivity::from_nm(0), Connectivity::Unknown);
+        assert_eq!(Connectivity::from_nm(2), Connectivity::Portal);
+        assert_eq!(Connectivity::from_nm(4), Connectivity::Full);
+        assert_eq!(Connectivity::from_nm(9), Connectivity::Unknown);
+    }
+}
@@ -0,0 +1,45 @@
+use std::path::Path;
+
+/// Where nologin(5) is looked for, in the order pam_nologin checks them.
+const PATHS: [&str; 2] = ["/run/nologin", "/etc/nologin"];
+
+/// Shown when the file is there but empty, like pam_nologin does.
+const DEFAULT_MESSAGE: &str = "The system is closed for logins.";
+
+/// Why logins are turned off, while nologin(5) exists. Only root can log in until then.
+pub fn read() -> Option<String> {
+    PATHS.iter().find_map(|path| read_from(Path::new(path)))
+}
+
+fn read_from(path: &Path) -> Option<String> {
+    let contents = std::fs::read_to_string(path).ok()?;
+    let message = contents.trim();
+    Some(match message.is_empty() {
+        true => DEFAULT_MESSAGE.to_string(),
+        false => message.to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::nologin::{DEFAULT_MESSAGE, read_from};
+
+    #[test]
+    fn reads_the_message() -> std::io::Result<()> {
+        let path = std::env::temp_dir().join(format!("impolite-nologin-{}", std::process::id()));
+
+        assert_eq!(read_from(&path), None);
+        std::fs::write(&path, "\n")?;
+        assert_eq!(read_from(&path).as_deref(), Some(DEFAULT_MESSAGE));
+        std::fs::write(&path, "Down for maintenance until 14:00.\n")?;
+        let message = read_from(&path);
+        std::fs::remove_file(&path)?;
+
+        assert_eq!(
+            message.as_deref(),
+            Some("Down for maintenance until 14:00.")
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,123 @@
+use std::collections::HashMap;
+
+/// Where os-release(5) can live, in order of preference.
+const PATHS: [&str; 2] = ["/etc/os-release", "/usr/lib/os-release"];
+
+/// The `KEY=value` pairs of os-release(5).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OsRelease(HashMap<String, String>);
+
+impl OsRelease {
+    pub fn load() -> Option<Self> {
+        PATHS
+            .iter()
+            .find_map(|path| std::fs::read_to_string(path).ok())
+            .map(|contents| Self::parse(&contents))
+    }
+
+    pub fn parse(contents: &str) -> Self {
+        let fields = contents
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .filter_map(|line| line.split_once('='))
+            .map(|(key, value)| (key.trim().to_string(), unquote(value.trim())))
+            .collect();
+        Self(fields)
+    }
+
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.0.get(key).map(String::as_str)
+    }
+
+    /// `PRETTY_NAME`, falling back to `NAME` and then to "Linux" like the spec says.
+    pub fn pretty_name(&self) -> &str {
+        self.get("PRETTY_NAME")
+            .or_else(|| self.get("NAME"))
+            .unwrap_or("Linux")
+    }
+}
+
+impl OsRelease {
+    /// The Nerd Fonts logo of the distro, by `ID` and then `ID_LIKE`.
+    pub fn logo(&self) -> &'static str {
+        let ids = self.get("ID").into_iter().chain(
+            self.get("ID_LIKE")
+                .into_iter()
+                .flat_map(str::split_whitespace),
+        );
+        ids.map(|id| match id {
+            "alpine" => Some("\u{f300}"),
+            "arch" => Some("\u{f303}"),
+            "centos" => Some("\u{f304}"),
+            "debian" => Some("\u{f306}"),
+            "endeavouros" => Some("\u{f322}"),
+            "fedora" => Some("\u{f30a}"),
+            "gentoo" => Some("\u{f30d}"),
+            "linuxmint" => Some("\u{f30e}"),
+            "manjaro" => Some("\u{f312}"),
+            "nixos" => Some("\u{f313}"),
+            "opensuse" | "opensuse-tumbleweed" | "opensuse-leap" | "suse" => Some("\u{f314}"),
+            "pop" => Some("\u{f32a}"),
+            "rhel" => Some("\u{f316}"),
+            "ubuntu" => Some("\u{f31b}"),
+            "void" => Some("\u{f32e}"),
+            _ => None,
+        })
+        .find_map(|logo| logo)
+        .unwrap_or("\u{f31a}")
+    }
+}
+
+/// Strips shell style quotes and backslash escapes from a value.
+fn unquote(value: &str) -> String {
+    let quote = value.chars().next().filter(|c| matches!(c, '"' | '\''));
+    let inner = match quote {
+        Some(quote) => value
+            .strip_prefix(quote)
+            .and_then(|value| value.strip_suffix(quote))
+            .unwrap_or(value),
+        None => value,
+    };
+    let mut out = String::with_capacity(inner.len());
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => out.extend(chars.next()),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::os_release::OsRelease;
+
+    #[test]
+    fn parse_os_release() {
+        let os = OsRelease::parse(
+            r#"
+            # comment
+            NAME="Arch Linux"
+            PRETTY_NAME='Arch \"btw\" Linux'
+            ID=arch
+            "#,
+        );
+
+        assert_eq!(os.get("NAME"), Some("Arch Linux"));
+        assert_eq!(os.get("ID"), Some("arch"));
+        assert_eq!(os.pretty_name(), r#"Arch "btw" Linux"#);
+        assert_eq!(OsRelease::default().pretty_name(), "Linux");
+    }
+
+    #[test]
+    fn logo_falls_back_to_id_like() {
+        let endeavour = OsRelease::parse("ID=endeavouros");
+        let derivative = OsRelease::parse("ID=cachyos\nID_LIKE=arch");
+
+        assert_eq!(endeavour.logo(), "\u{f322}");
+        assert_eq!(derivative.logo(), "\u{f303}");
+        assert_eq!(OsRelease::default().logo(), "\u{f31a}");
+    }
+}
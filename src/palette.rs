@@ -0,0 +1,144 @@
+use std::cmp::Reverse;
+
+use tui_input::Input;
+
+use crate::{Msg, Str};
+
+/// How many matching commands the palette shows at once.
+pub const HEIGHT: usize = 8;
+
+/// An action the palette can run.
+#[derive(Debug, Clone)]
+pub struct Command {
+    pub label: Str,
+    pub msg: Msg,
+}
+
+impl Command {
+    pub fn new(label: impl Into<Str>, msg: Msg) -> Self {
+        Self {
+            label: label.into(),
+            msg,
+        }
+    }
+}
+
+/// The Ctrl+P command palette: what's been typed and which match is selected.
+#[derive(Debug, Clone, Default)]
+pub struct Palette {
+    pub query: Input,
+    selected: usize,
+}
+
+impl Palette {
+    pub fn set_query(&mut self, query: Input) {
+        self.query = query;
+        self.selected = 0;
+    }
+
+    pub fn selected(&self) -> usize {
+        self.selected
+    }
+
+    /// Moves the selection through `len` matches, wrapping around.
+    pub fn move_by(&mut self, by: isize, len: usize) {
+        if len == 0 {
+            return;
+        }
+        self.selected = (self.selected as isize + by).rem_euclid(len as isize) as usize;
+    }
+
+    /// The commands matching the query, best first and in their given order otherwise.
+    pub fn matches<'a>(&self, commands: &'a [Command]) -> Vec<&'a Command> {
+        let mut scored = commands
+            .iter()
+            .filter_map(|command| Some((score(self.query.value(), &command.label)?, command)))
+            .collect::<Vec<_>>();
+        scored.sort_by_key(|(score, _)| Reverse(*score));
+        scored.into_iter().map(|(_, command)| command).collect()
+    }
+
+    pub fn pick<'a>(&self, commands: &'a [Command]) -> Option<&'a Command> {
+        self.matches(commands).get(self.selected).copied()
+    }
+}
+
+/// How well `query` fuzzy matches `candidate`, ignoring case and spaces in the query.
+///
+/// Every character of the query has to show up in order. Runs of consecutive characters and
+/// matches at the start of a word count for more.
+pub fn score(query: &str, candidate: &str) -> Option<u32> {
+    let query = query
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .flat_map(char::to_lowercase)
+        .collect::<Vec<_>>();
+    let candidate = candidate
+        .chars()
+        .flat_map(char::to_lowercase)
+        .collect::<Vec<_>>();
+    let Some(&first) = query.first() else {
+        return Some(0);
+    };
+    // Matching greedily from the first place the query starts can miss a better match later
+    // on, like the "log" in "Toggle event log", so try each of them.
+    (0..candidate.len())
+        .filter(|&start| candidate[start] == first)
+        .filter_map(|start| score_from(&query, &candidate, start))
+        .max()
+}
+
+fn score_from(query: &[char], candidate: &[char], start: usize) -> Option<u32> {
+    let mut score = 0;
+    let mut from = start;
+    let mut last = None;
+    for &c in query {
+        let idx = from + candidate[from..].iter().position(|&other| other == c)?;
+        score += 1;
+        if last.is_some_and(|last| last + 1 == idx) {
+            score += 4;
+        }
+        if idx == 0 || !candidate[idx - 1].is_alphanumeric() {
+            score += 2;
+        }
+        last = Some(idx);
+        from = idx + 1;
+    }
+    Some(score)
+}
+
+#[cfg(test)]
+mod tests {
+    use tui_input::Input;
+
+    use crate::Msg;
+    use crate::palette::{Command, Palette, score};
+
+    #[test]
+    fn fuzzy_matches_rank_runs_and_word_starts() {
+        assert_eq!(score("", "Quit"), Some(0));
+        assert_eq!(score("xyz", "Quit"), None);
+        assert!(score("log", "Toggle event log") > score("log", "Reload config"));
+
+        let commands = [
+            Command::new("Toggle event log", Msg::Nothing),
+            Command::new("Reload config", Msg::Nothing),
+            Command::new("Reboot", Msg::Nothing),
+        ];
+        let mut palette = Palette::default();
+        palette.set_query(Input::new("reb".into()));
+        let labels = palette
+            .matches(&commands)
+            .into_iter()
+            .map(|command| &*command.label)
+            .collect::<Vec<_>>();
+
+        assert_eq!(labels, ["Reboot"]);
+        palette.set_query(Input::new("re".into()));
+        assert_eq!(palette.matches(&commands).len(), 2);
+        assert_eq!(
+            palette.pick(&commands).map(|command| &*command.label),
+            Some("Reload config")
+        );
+    }
+}
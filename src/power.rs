@@ -0,0 +1,293 @@
+use std::process::Stdio;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use color_eyre::{Result, eyre::Context, eyre::eyre};
+use flume::Sender;
+use tokio::process::Command;
+use tracing::instrument;
+use zbus::proxy;
+use zbus::proxy::CacheProperties;
+
+use crate::config::PowerConfig;
+use crate::{Msg, Str, queue};
+
+/// The part of systemd-logind's manager the power menu uses.
+#[proxy(
+    interface = "org.freedesktop.login1.Manager",
+    default_service = "org.freedesktop.login1",
+    default_path = "/org/freedesktop/login1"
+)]
+trait Login1 {
+    fn power_off(&self, interactive: bool) -> zbus::Result<()>;
+    fn reboot(&self, interactive: bool) -> zbus::Result<()>;
+    fn suspend(&self, interactive: bool) -> zbus::Result<()>;
+    fn hibernate(&self, interactive: bool) -> zbus::Result<()>;
+    fn can_power_off(&self) -> zbus::Result<String>;
+    fn can_reboot(&self) -> zbus::Result<String>;
+    fn can_suspend(&self) -> zbus::Result<String>;
+    fn can_hibernate(&self) -> zbus::Result<String>;
+    #[zbus(property)]
+    fn scheduled_shutdown(&self) -> zbus::Result<(String, u64)>;
+    #[zbus(property)]
+    fn wall_message(&self) -> zbus::Result<String>;
+}
+
+/// How often a scheduled shutdown is looked for, which is also how often its countdown moves.
+const SCHEDULE_INTERVAL: Duration = Duration::from_secs(30);
+
+/// A shutdown someone scheduled with shutdown(8), or just the wall message they left.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Scheduled {
+    /// What happens and when, `None` for a wall message on its own.
+    pub shutdown: Option<(PowerAction, SystemTime)>,
+    pub message: Option<Str>,
+}
+
+impl Scheduled {
+    /// From logind's `ScheduledShutdown` and `WallMessage`, `None` if neither is set.
+    fn from_logind((kind, usec): (String, u64), message: String) -> Option<Self> {
+        let action = match kind.trim_start_matches("dry-") {
+            "poweroff" | "halt" => Some(PowerAction::Shutdown),
+            "reboot" | "kexec" | "soft-reboot" => Some(PowerAction::Reboot),
+            _ => None,
+        };
+        let shutdown = action
+            .filter(|_| usec > 0)
+            .map(|action| (action, UNIX_EPOCH + Duration::from_micros(usec)));
+        let message = message.trim();
+        let message = (!message.is_empty()).then(|| message.into());
+        (shutdown.is_some() || message.is_some()).then_some(Self { shutdown, message })
+    }
+
+    /// Like "System reboots in 12 minutes: kernel update".
+    pub fn describe(&self, now: SystemTime) -> String {
+        let Some((action, at)) = self.shutdown else {
+            return self.message.as_deref().unwrap_or_default().to_string();
+        };
+        let verb = match action {
+            PowerAction::Reboot => "reboots",
+            _ => "shuts down",
+        };
+        let minutes = at
+            .duration_since(now)
+            .unwrap_or_default()
+            .as_secs()
+            .div_ceil(60);
+        let when = match minutes {
+            0 => "now".to_string(),
+            1 => "in 1 minute".to_string(),
+            2..120 => format!("in {minutes} minutes"),
+            _ => format!("in {} hours", minutes / 60),
+        };
+        match &self.message {
+            Some(message) => format!("System {verb} {when}: {message}"),
+            None => format!("System {verb} {when}"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PowerAction {
+    Shutdown,
+    Reboot,
+    Suspend,
+    Hibernate,
+}
+
+impl PowerAction {
+    /// In the order they appear in the power bar, which is also the order of their F keys.
+    pub const ALL: [PowerAction; 4] = [
+        PowerAction::Shutdown,
+        PowerAction::Reboot,
+        PowerAction::Suspend,
+        PowerAction::Hibernate,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            PowerAction::Shutdown => "Shutdown",
+            PowerAction::Reboot => "Reboot",
+            PowerAction::Suspend => "Suspend",
+            PowerAction::Hibernate => "Hibernate",
+        }
+    }
+
+    /// Whether it ends every session on the machine, rather than pausing them.
+    pub fn is_destructive(self) -> bool {
+        matches!(self, PowerAction::Shutdown | PowerAction::Reboot)
+    }
+
+    /// The command configured to run instead of asking logind.
+    pub fn command(self, config: &PowerConfig) -> Option<&str> {
+        match self {
+            PowerAction::Shutdown => config.shutdown.as_deref(),
+            PowerAction::Reboot => config.reboot.as_deref(),
+            PowerAction::Suspend => config.suspend.as_deref(),
+            PowerAction::Hibernate => config.hibernate.as_deref(),
+        }
+    }
+}
+
+/// Runs `action` through logind, or its configured command if it has one.
+#[instrument(err, skip(config))]
+pub async fn run(action: PowerAction, config: &PowerConfig) -> Result<()> {
+    if let Some(command) = action.command(config) {
+        return run_command(command).await;
+    }
+    let login1 = login1().await?;
+    // Nobody is logged in that polkit could ask for a password.
+    let result = match action {
+        PowerAction::Shutdown => login1.power_off(false).await,
+        PowerAction::Reboot => login1.reboot(false).await,
+        PowerAction::Suspend => login1.suspend(false).await,
+        PowerAction::Hibernate => login1.hibernate(false).await,
+    };
+    result.wrap_err("logind refused")
+}
+
+/// The actions that can run right now: the ones with a command, and the ones logind says are
+/// possible. Suspend and hibernate often aren't, without swap or in a VM, and none are if
+/// logind can't be reached.
+pub async fn available(config: &PowerConfig) -> Vec<PowerAction> {
+    let login1 = login1().await;
+    if let Err(err) = &login1 {
+        tracing::warn!("failed to ask logind which power actions are possible: {err:#}");
+    }
+    let mut actions = Vec::new();
+    for action in PowerAction::ALL {
+        let possible = match (action.command(config), &login1) {
+            (Some(_), _) => true,
+            (None, Ok(login1)) => {
+                let answer = match action {
+                    PowerAction::Shutdown => login1.can_power_off().await,
+                    PowerAction::Reboot => login1.can_reboot().await,
+                    PowerAction::Suspend => login1.can_suspend().await,
+                    PowerAction::Hibernate => login1.can_hibernate().await,
+                };
+                answer.is_ok_and(|answer| possible(&answer))
+            }
+            (None, Err(_)) => false,
+        };
+        if possible {
+            actions.push(action);
+        }
+    }
+    actions
+}
+
+/// Sends [`Msg::Scheduled`] with any scheduled shutdown every [`SCHEDULE_INTERVAL`], so its
+/// countdown stays current. Returns right away if logind can't be reached.
+pub async fn watch_scheduled(tx: Sender<Msg>) {
+    let login1 = async {
+        let bus = zbus::Connection::system().await?;
+        // Neither property is announced when it changes, so they are read fresh every time.
+        Login1Proxy::builder(&bus)
+            .cache_properties(CacheProperties::No)
+            .build()
+            .await
+    };
+    let login1 = match login1.await {
+        Ok(login1) => login1,
+        Err(err) => {
+            tracing::debug!("not watching for scheduled shutdowns: {err}");
+            return;
+        }
+    };
+    loop {
+        let scheduled = match login1.scheduled_shutdown().await {
+            Ok(shutdown) => {
+                let message = login1.wall_message().await.unwrap_or_default();
+                Scheduled::from_logind(shutdown, message)
+            }
+            Err(err) => {
+                tracing::debug!("failed to ask logind for a scheduled shutdown: {err}");
+                return;
+            }
+        };
+        if queue::send(&tx, Msg::Scheduled(scheduled)).await.is_err() {
+            break;
+        }
+        tokio::time::sleep(SCHEDULE_INTERVAL).await;
+    }
+}
+
+/// Whether logind's answer to one of its `Can*` calls means the action can run, maybe after
+/// polkit checks the greeter is allowed to.
+fn possible(answer: &str) -> bool {
+    matches!(answer, "yes" | "challenge")
+}
+
+async fn login1() -> Result<Login1Proxy<'static>> {
+    let bus = zbus::Connection::system()
+        .await
+        .wrap_err("failed to connect to the system bus")?;
+    Login1Proxy::new(&bus)
+        .await
+        .wrap_err("failed to reach logind")
+}
+
+/// Runs `command` with `sh -c`, failing if it exits unsuccessfully.
+async fn run_command(command: &str) -> Result<()> {
+    let status = Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .await
+        .wrap_err_with(|| format!("failed to run `{command}`"))?;
+    match status.success() {
+        true => Ok(()),
+        false => Err(eyre!("`{command}` exited with {status}")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::PowerConfig;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    use crate::power::{PowerAction, Scheduled, possible, run};
+
+    #[tokio::test]
+    async fn reports_failed_commands() {
+        let config = PowerConfig {
+            shutdown: Some("true".into()),
+            reboot: Some("exit 3".into()),
+            ..Default::default()
+        };
+
+        assert!(run(PowerAction::Shutdown, &config).await.is_ok());
+        assert!(run(PowerAction::Reboot, &config).await.is_err());
+    }
+
+    #[test]
+    fn logind_answers() {
+        assert!(possible("yes"));
+        assert!(possible("challenge"));
+        assert!(!possible("no"));
+        assert!(!possible("na"));
+    }
+
+    #[test]
+    fn describe_scheduled_shutdowns() {
+        let at = 1_700_000_000_000_000;
+        let now = UNIX_EPOCH + Duration::from_micros(at) - Duration::from_secs(11 * 60 + 30);
+        let scheduled = |kind: &str, message: &str| {
+            Scheduled::from_logind((kind.to_string(), at), message.to_string())
+                .map(|scheduled| scheduled.describe(now))
+        };
+
+        assert_eq!(
+            scheduled("reboot", "").as_deref(),
+            Some("System reboots in 12 minutes")
+        );
+        assert_eq!(
+            scheduled("dry-poweroff", "kernel update\n").as_deref(),
+            Some("System shuts down in 12 minutes: kernel update")
+        );
+        assert_eq!(scheduled("", "back soon").as_deref(), Some("back soon"));
+        assert_eq!(scheduled("", ""), None);
+    }
+}
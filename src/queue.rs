@@ -0,0 +1,102 @@
+use std::sync::LazyLock;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use flume::{Receiver, SendError, Sender};
+
+use crate::Msg;
+
+/// How many messages can wait for the UI before background tasks hold off on sending more.
+/// The runtime's queue has no bound of its own, so this is what keeps a task that sends faster
+/// than the UI keeps up from filling memory over weeks of running unattended.
+///
+/// The runtime makes that channel itself and effects only ever get its unbounded `Sender`, so
+/// the bound can't go on the channel. Every background task sends through here instead.
+pub const CAPACITY: usize = 256;
+
+/// How many tasks are held off by a full queue.
+static WAITING: AtomicUsize = AtomicUsize::new(0);
+
+/// Wakes a held off task once the UI has taken a message.
+static ROOM: LazyLock<(Sender<()>, Receiver<()>)> = LazyLock::new(flume::unbounded);
+
+/// Sends `msg` to the UI, waiting first while the queue is full.
+pub async fn send(tx: &Sender<Msg>, msg: Msg) -> Result<(), SendError<Msg>> {
+    while is_full(tx) {
+        if tx.is_disconnected() {
+            return Err(SendError(msg));
+        }
+        WAITING.fetch_add(1, Ordering::SeqCst);
+        // Looked at again now that `received` knows to wake this task, in case the UI caught up
+        // in between. A wake-up that was meant for an earlier task only costs another look.
+        if is_full(tx) {
+            _ = ROOM.1.recv_async().await;
+        }
+        WAITING.fetch_sub(1, Ordering::SeqCst);
+    }
+    tx.send_async(msg).await
+}
+
+/// Sends `msg` to the UI unless the queue is full, for messages like ticks that the next one
+/// makes up for. Returns whether it went.
+pub fn offer(tx: &Sender<Msg>, msg: Msg) -> Result<bool, SendError<Msg>> {
+    if is_full(tx) {
+        tracing::trace!("dropped {msg:?}, the UI is behind");
+        return Ok(false);
+    }
+    tx.send(msg).map(|()| true)
+}
+
+/// Called by the UI for each message it takes, to wake a task waiting on a full queue.
+pub fn received() {
+    if WAITING.load(Ordering::SeqCst) > 0 {
+        _ = ROOM.0.send(());
+    }
+}
+
+fn is_full(tx: &Sender<Msg>) -> bool {
+    tx.len() >= CAPACITY
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Msg;
+    use crate::queue::{CAPACITY, offer, received, send};
+
+    #[test]
+    fn drops_offers_when_full() -> color_eyre::Result<()> {
+        let (tx, rx) = flume::unbounded();
+        for _ in 0..CAPACITY {
+            assert!(offer(&tx, Msg::Tick)?);
+        }
+        assert!(!offer(&tx, Msg::Tick)?);
+        assert_eq!(rx.len(), CAPACITY);
+
+        _ = rx.recv()?;
+        assert!(offer(&tx, Msg::Tick)?);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn sends_wait_for_room() -> color_eyre::Result<()> {
+        let (tx, rx) = flume::unbounded();
+        for _ in 0..CAPACITY {
+            send(&tx, Msg::Tick).await?;
+        }
+        let waiting = tokio::spawn(async move { send(&tx, Msg::Quit).await });
+        tokio::task::yield_now().await;
+        assert_eq!(rx.len(), CAPACITY);
+
+        _ = rx.recv_async().await?;
+        received();
+        waiting.await??;
+        assert_eq!(rx.len(), CAPACITY);
+        assert!(
+            rx.drain()
+                .last()
+                .is_some_and(|msg| matches!(msg, Msg::Quit))
+        );
+
+        Ok(())
+    }
+}
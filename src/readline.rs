@@ -0,0 +1,125 @@
+use ratatui::crossterm::event::{Event, KeyCode, KeyEventKind, KeyModifiers};
+use serde::{Deserialize, Serialize};
+use tui_input::backend::crossterm::to_input_request;
+use tui_input::{Input, InputRequest, StateChanged};
+
+/// What happens to text pasted into the password field.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum PastePolicy {
+    #[default]
+    Allow,
+    /// Ask first, in case it was meant for somewhere else.
+    Confirm,
+    Block,
+}
+
+/// A text field readline can edit, tui_input's own or a [`SecretInput`].
+///
+/// [`SecretInput`]: crate::secret::SecretInput
+pub trait Edit {
+    fn cursor(&self) -> usize;
+    fn handle(&mut self, request: InputRequest) -> Option<StateChanged>;
+}
+
+impl Edit for Input {
+    fn cursor(&self) -> usize {
+        self.cursor()
+    }
+
+    fn handle(&mut self, request: InputRequest) -> Option<StateChanged> {
+        self.handle(request)
+    }
+}
+
+/// Edits `input` with the readline keys bash and most shells have, and tui_input's own keys
+/// for everything else.
+///
+/// tui_input only moves by words on Meta, which terminals send as Alt, deletes the previous
+/// word on Alt+D and the whole line on Ctrl+U, where readline kills up to the cursor.
+pub fn handle(input: &mut impl Edit, event: &Event) -> Option<StateChanged> {
+    let key = match event {
+        Event::Key(key) => key,
+        Event::Paste(text) => return paste(input, text),
+        _ => return None,
+    };
+    if key.kind == KeyEventKind::Release {
+        return None;
+    }
+    let request = match (key.code, key.modifiers) {
+        (KeyCode::Char('u'), KeyModifiers::CONTROL) => return kill_to_start(input),
+        // Ctrl+K is the previous field, not kill to the end of the line.
+        (KeyCode::Char('k'), KeyModifiers::CONTROL) => return None,
+        (KeyCode::Char('a'), KeyModifiers::CONTROL) => InputRequest::GoToStart,
+        (KeyCode::Char('e'), KeyModifiers::CONTROL) => InputRequest::GoToEnd,
+        (KeyCode::Char('b'), KeyModifiers::ALT) => InputRequest::GoToPrevWord,
+        (KeyCode::Char('f'), KeyModifiers::ALT) => InputRequest::GoToNextWord,
+        (KeyCode::Char('d'), KeyModifiers::ALT) => InputRequest::DeleteNextWord,
+        (KeyCode::Char('w'), KeyModifiers::CONTROL) | (KeyCode::Backspace, KeyModifiers::ALT) => {
+            InputRequest::DeletePrevWord
+        }
+        _ => to_input_request(event)?,
+    };
+    input.handle(request)
+}
+
+/// Inserts pasted text at the cursor. Line breaks and other control characters are left out,
+/// so a copied password with a trailing newline doesn't log in before it can be checked.
+fn paste(input: &mut impl Edit, text: &str) -> Option<StateChanged> {
+    let mut changed = None;
+    for c in text.chars().filter(|c| !c.is_control()) {
+        changed = input.handle(InputRequest::InsertChar(c)).or(changed);
+    }
+    changed
+}
+
+fn kill_to_start(input: &mut impl Edit) -> Option<StateChanged> {
+    let mut changed = None;
+    for _ in 0..input.cursor() {
+        changed = input.handle(InputRequest::DeletePrevChar).or(changed);
+    }
+    changed
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+    use tui_input::Input;
+
+    use crate::readline::handle;
+
+    fn press(input: &mut Input, code: char, modifiers: KeyModifiers) {
+        handle(
+            input,
+            &Event::Key(KeyEvent::new(KeyCode::Char(code), modifiers)),
+        );
+    }
+
+    #[test]
+    fn readline_keys() {
+        let mut input = Input::new("one two three".into());
+        press(&mut input, 'a', KeyModifiers::CONTROL);
+        press(&mut input, 'f', KeyModifiers::ALT);
+        press(&mut input, 'd', KeyModifiers::ALT);
+        assert_eq!(input.value(), "one three");
+
+        press(&mut input, 'e', KeyModifiers::CONTROL);
+        press(&mut input, 'w', KeyModifiers::CONTROL);
+        assert_eq!(input.value(), "one ");
+
+        let mut input = Input::new("user name".into()).with_cursor(4);
+        press(&mut input, 'u', KeyModifiers::CONTROL);
+        assert_eq!((input.value(), input.cursor()), (" name", 0));
+
+        press(&mut input, 'k', KeyModifiers::CONTROL);
+        assert_eq!(input.value(), " name");
+    }
+
+    #[test]
+    fn paste_without_line_breaks() {
+        let mut input = Input::new("ab".into()).with_cursor(1);
+        handle(&mut input, &Event::Paste("x\ty\r\n".into()));
+
+        assert_eq!((input.value(), input.cursor()), ("axyb", 3));
+    }
+}
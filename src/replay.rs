@@ -0,0 +1,441 @@
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::os::fd::{AsRawFd, FromRawFd, OwnedFd, RawFd};
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use color_eyre::{Result, eyre::Context};
+use flume::{Receiver, Sender};
+use ratatui::crossterm::event::{
+    Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::greetd::{Request, Response};
+use crate::{Msg, queue};
+
+/// Stands in for every character typed or pasted into the password field.
+const MASK: char = '*';
+/// Readable only by whoever recorded it: usernames and login errors are in it.
+const FILE_MODE: u32 = 0o600;
+/// The least time between two events played back, so each is read on its own and a lone
+/// Escape isn't taken for Alt held with the key after it.
+const MIN_GAP: Duration = Duration::from_millis(10);
+
+/// One line of a trace.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct Entry {
+    /// Milliseconds since the greeter started.
+    at_ms: u64,
+    #[serde(flatten)]
+    what: What,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "snake_case")]
+enum What {
+    Event(Event),
+    Greetd(Response),
+}
+
+/// Writes every terminal event and answer from greetd to a file, one JSON object per line, so
+/// a session can be played back with [`attach`] and [`answer`].
+///
+/// What's typed into the password field is masked, but how many keys were pressed isn't, or
+/// the trace wouldn't play back the same. Keys for the on-screen keyboard are left out while
+/// it types into the password field, since where it moves gives the characters away.
+#[derive(Clone)]
+pub struct Recorder {
+    file: Arc<Mutex<File>>,
+    start: Instant,
+}
+
+impl Recorder {
+    pub fn create(path: &Path) -> io::Result<Self> {
+        let file = OpenOptions::new()
+            .create(true)
+            .write(true)
+            .truncate(true)
+            .mode(FILE_MODE)
+            .custom_flags(libc::O_NOFOLLOW)
+            .open(path)?;
+        Ok(Self {
+            file: Arc::new(Mutex::new(file)),
+            start: Instant::now(),
+        })
+    }
+
+    /// Records `event`, masked when it went to the password field.
+    pub fn event(&self, event: &Event, secret: bool) {
+        let event = match secret {
+            true => mask(event),
+            false => event.clone(),
+        };
+        self.write(What::Event(event));
+    }
+
+    pub fn greetd(&self, res: &Response) {
+        self.write(What::Greetd(res.clone()));
+    }
+
+    /// Written straight away, so a trace of a crash has everything up to it.
+    fn write(&self, what: What) {
+        let entry = Entry {
+            at_ms: self.start.elapsed().as_millis() as u64,
+            what,
+        };
+        let result = serde_json::to_string(&entry)
+            .map_err(io::Error::other)
+            .and_then(|line| {
+                let mut file = self.file.lock().unwrap();
+                writeln!(file, "{line}")
+            });
+        if let Err(err) = result {
+            tracing::debug!("failed to record {entry:?}: {err}");
+        }
+    }
+}
+
+/// `event` with whatever it types swapped for [`MASK`]. Keys held with Ctrl or Alt are
+/// bindings, not text, and are kept.
+fn mask(event: &Event) -> Event {
+    match event {
+        Event::Key(key)
+            if matches!(key.code, KeyCode::Char(_))
+                && !key
+                    .modifiers
+                    .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT) =>
+        {
+            Event::Key(KeyEvent {
+                code: KeyCode::Char(MASK),
+                ..*key
+            })
+        }
+        Event::Paste(text) => Event::Paste(text.chars().map(|_| MASK).collect()),
+        event => event.clone(),
+    }
+}
+
+fn load(path: &Path) -> Result<Vec<Entry>> {
+    let file = File::open(path).wrap_err_with(|| format!("failed to open {}", path.display()))?;
+    BufReader::new(file)
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.as_ref().is_ok_and(|line| line.trim().is_empty()))
+        .map(|(idx, line)| {
+            let line = line.wrap_err("failed to read the trace")?;
+            serde_json::from_str(&line)
+                .wrap_err_with(|| format!("{}:{}: not a trace entry", path.display(), idx + 1))
+        })
+        .collect()
+}
+
+/// The events of the trace at `path`, and when they happened.
+fn events(path: &Path) -> Result<Vec<(Duration, Event)>> {
+    Ok(load(path)?
+        .into_iter()
+        .filter_map(|entry| match entry.what {
+            What::Event(event) => Some((Duration::from_millis(entry.at_ms), event)),
+            What::Greetd(_) => None,
+        })
+        .collect())
+}
+
+/// Puts a pseudo-terminal in place of stdin and types the events of the trace at `path` into
+/// it at the times they were recorded, so they go through the same event handling as real
+/// keys. Has to happen before the UI starts reading events.
+///
+/// The real terminal stops echoing in the meantime. Once the trace runs out, what's typed on
+/// it is passed through, to carry on from where the trace ends.
+pub fn attach(path: &Path) -> Result<()> {
+    let events = events(path)?;
+    let (master, slave) = open_pty().wrap_err("failed to open a pseudo-terminal")?;
+    // SAFETY: dup takes no pointers, and the descriptor it returns is ours to own.
+    let tty = check(unsafe { libc::dup(libc::STDIN_FILENO) })?;
+    // SAFETY: `tty` was just returned by dup and nothing else owns it.
+    let tty = File::from(unsafe { OwnedFd::from_raw_fd(tty) });
+    copy_window_size(tty.as_raw_fd(), slave.as_raw_fd());
+    if let Err(err) = no_echo(tty.as_raw_fd()) {
+        tracing::warn!("failed to turn off echo while replaying: {err}");
+    }
+    // SAFETY: both descriptors are open, and stdin being replaced is the point.
+    check(unsafe { libc::dup2(slave.as_raw_fd(), libc::STDIN_FILENO) })?;
+    let start = Instant::now();
+    tokio::spawn(async move {
+        let mut master = master;
+        let mut last = Duration::ZERO;
+        for (at, event) in events {
+            let at = at.max(last + MIN_GAP);
+            tokio::time::sleep_until((start + at).into()).await;
+            last = at;
+            let Some(bytes) = encode(&event) else {
+                tracing::debug!("can't replay {event:?}");
+                continue;
+            };
+            if let Err(err) = master.write_all(&bytes) {
+                tracing::warn!("replay stopped: {err}");
+                return;
+            }
+        }
+        tracing::info!("replay finished");
+        std::thread::spawn(move || passthrough(tty, master));
+    });
+    Ok(())
+}
+
+/// Copies what's typed on the real terminal to the pseudo-terminal, until either closes.
+fn passthrough(mut tty: File, mut master: File) {
+    let mut buf = [0u8; 256];
+    while let Ok(len @ 1..) = tty.read(&mut buf) {
+        if master.write_all(&buf[..len]).is_err() {
+            return;
+        }
+    }
+}
+
+fn open_pty() -> io::Result<(File, File)> {
+    // SAFETY: posix_openpt takes no pointers.
+    let master = check(unsafe { libc::posix_openpt(libc::O_RDWR | libc::O_NOCTTY) })?;
+    // SAFETY: `master` was just opened and nothing else owns it.
+    let master = File::from(unsafe { OwnedFd::from_raw_fd(master) });
+    // SAFETY: `master` is an open pseudo-terminal master.
+    check(unsafe { libc::grantpt(master.as_raw_fd()) })?;
+    // SAFETY: as above.
+    check(unsafe { libc::unlockpt(master.as_raw_fd()) })?;
+    let mut name = [0 as libc::c_char; 128];
+    // SAFETY: `name` outlives the call and its length is passed along.
+    let ret = unsafe { libc::ptsname_r(master.as_raw_fd(), name.as_mut_ptr(), name.len()) };
+    if ret != 0 {
+        return Err(io::Error::from_raw_os_error(ret));
+    }
+    // SAFETY: ptsname_r wrote a NUL-terminated path into `name`.
+    let name = unsafe { std::ffi::CStr::from_ptr(name.as_ptr()) };
+    let slave = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .custom_flags(libc::O_NOCTTY)
+        .open(name.to_string_lossy().as_ref())?;
+    Ok((master, slave))
+}
+
+fn copy_window_size(from: RawFd, to: RawFd) {
+    let mut size = libc::winsize {
+        ws_row: 0,
+        ws_col: 0,
+        ws_xpixel: 0,
+        ws_ypixel: 0,
+    };
+    // SAFETY: TIOCGWINSZ fills in `size`, which outlives both calls.
+    unsafe {
+        if libc::ioctl(from, libc::TIOCGWINSZ, &mut size) == 0 {
+            libc::ioctl(to, libc::TIOCSWINSZ, &size);
+        }
+    }
+}
+
+/// Keeps keys pressed during the replay from showing up over the UI.
+fn no_echo(fd: RawFd) -> io::Result<()> {
+    let mut termios = std::mem::MaybeUninit::<libc::termios>::uninit();
+    // SAFETY: tcgetattr fills in `termios` when it succeeds.
+    check(unsafe { libc::tcgetattr(fd, termios.as_mut_ptr()) })?;
+    // SAFETY: tcgetattr succeeded.
+    let mut termios = unsafe { termios.assume_init() };
+    // SAFETY: `termios` is initialized and outlives the calls.
+    unsafe { libc::cfmakeraw(&mut termios) };
+    // SAFETY: as above.
+    check(unsafe { libc::tcsetattr(fd, libc::TCSANOW, &termios) }).map(|_| ())
+}
+
+fn check(ret: libc::c_int) -> io::Result<libc::c_int> {
+    match ret {
+        -1 => Err(io::Error::last_os_error()),
+        ret => Ok(ret),
+    }
+}
+
+/// Answers requests with the greetd responses of the trace at `path` in the order they were
+/// recorded, in place of greetd. Once they run out, requests go unanswered.
+pub async fn answer(path: &Path, req_rx: Receiver<Request>, tx: Sender<Msg>) -> Result<()> {
+    let mut responses = load(path)?
+        .into_iter()
+        .filter_map(|entry| match entry.what {
+            What::Greetd(res) => Some(res),
+            What::Event(_) => None,
+        });
+    while let Ok(req) = req_rx.recv_async().await {
+        match responses.next() {
+            Some(res) => queue::send(&tx, Msg::GreetdRes(res)).await?,
+            None => tracing::warn!("the trace has no answer to {req:?}"),
+        }
+    }
+    Ok(())
+}
+
+/// The bytes a terminal sends for `event`, `None` for the ones that don't come from input,
+/// like resizes.
+fn encode(event: &Event) -> Option<Vec<u8>> {
+    match event {
+        Event::Key(key) if key.kind != KeyEventKind::Release => encode_key(key),
+        Event::Key(_) | Event::Resize(..) => None,
+        Event::Paste(text) => Some(format!("\x1b[200~{text}\x1b[201~").into_bytes()),
+        Event::FocusGained => Some(b"\x1b[I".to_vec()),
+        Event::FocusLost => Some(b"\x1b[O".to_vec()),
+        Event::Mouse(mouse) => encode_mouse(mouse),
+    }
+}
+
+/// In the xterm encoding, modifiers added to 1 as a parameter of the escape.
+fn encode_key(key: &KeyEvent) -> Option<Vec<u8>> {
+    let modifiers = key.modifiers;
+    let param = 1
+        + modifiers.contains(KeyModifiers::SHIFT) as u8
+        + 2 * modifiers.contains(KeyModifiers::ALT) as u8
+        + 4 * modifiers.contains(KeyModifiers::CONTROL) as u8;
+    let csi = |last: char| match param {
+        1 => format!("\x1b[{last}"),
+        _ => format!("\x1b[1;{param}{last}"),
+    };
+    let tilde = |code: u8| match param {
+        1 => format!("\x1b[{code}~"),
+        _ => format!("\x1b[{code};{param}~"),
+    };
+    let alt = |bytes: &[u8]| {
+        let mut out = Vec::with_capacity(bytes.len() + 1);
+        if modifiers.contains(KeyModifiers::ALT) {
+            out.push(0x1b);
+        }
+        out.extend_from_slice(bytes);
+        out
+    };
+    let bytes = match key.code {
+        KeyCode::Char(' ') if modifiers.contains(KeyModifiers::CONTROL) => alt(&[0]),
+        KeyCode::Char(c)
+            if modifiers.contains(KeyModifiers::CONTROL) && c.is_ascii_alphabetic() =>
+        {
+            alt(&[c.to_ascii_lowercase() as u8 & 0x1f])
+        }
+        KeyCode::Char(c) => alt(c.encode_utf8(&mut [0; 4]).as_bytes()),
+        KeyCode::Enter => alt(b"\r"),
+        KeyCode::Tab => alt(b"\t"),
+        KeyCode::Backspace => alt(b"\x7f"),
+        KeyCode::Esc => alt(b"\x1b"),
+        KeyCode::BackTab => b"\x1b[Z".to_vec(),
+        KeyCode::Up => csi('A').into_bytes(),
+        KeyCode::Down => csi('B').into_bytes(),
+        KeyCode::Right => csi('C').into_bytes(),
+        KeyCode::Left => csi('D').into_bytes(),
+        KeyCode::Home => csi('H').into_bytes(),
+        KeyCode::End => csi('F').into_bytes(),
+        KeyCode::Insert => tilde(2).into_bytes(),
+        KeyCode::Delete => tilde(3).into_bytes(),
+        KeyCode::PageUp => tilde(5).into_bytes(),
+        KeyCode::PageDown => tilde(6).into_bytes(),
+        KeyCode::F(n @ 1..=4) if param == 1 => format!("\x1bO{}", (b'O' + n) as char).into_bytes(),
+        KeyCode::F(n @ 1..=4) => format!("\x1b[1;{param}{}", (b'O' + n) as char).into_bytes(),
+        KeyCode::F(n @ 5..=12) => {
+            let code = [15, 17, 18, 19, 20, 21, 23, 24][n as usize - 5];
+            tilde(code).into_bytes()
+        }
+        _ => return None,
+    };
+    Some(bytes)
+}
+
+/// In the SGR mouse encoding, which has room for any column and row.
+fn encode_mouse(mouse: &MouseEvent) -> Option<Vec<u8>> {
+    let button = |button: MouseButton| match button {
+        MouseButton::Left => 0,
+        MouseButton::Middle => 1,
+        MouseButton::Right => 2,
+    };
+    let (code, last) = match mouse.kind {
+        MouseEventKind::Down(pressed) => (button(pressed), 'M'),
+        MouseEventKind::Up(released) => (button(released), 'm'),
+        MouseEventKind::Drag(held) => (32 + button(held), 'M'),
+        MouseEventKind::Moved => (35, 'M'),
+        MouseEventKind::ScrollUp => (64, 'M'),
+        MouseEventKind::ScrollDown => (65, 'M'),
+        MouseEventKind::ScrollLeft => (66, 'M'),
+        MouseEventKind::ScrollRight => (67, 'M'),
+    };
+    let modifiers = mouse.modifiers;
+    let code = code
+        + 4 * modifiers.contains(KeyModifiers::SHIFT) as u8
+        + 8 * modifiers.contains(KeyModifiers::ALT) as u8
+        + 16 * modifiers.contains(KeyModifiers::CONTROL) as u8;
+    let (x, y) = (mouse.column + 1, mouse.row + 1);
+    Some(format!("\x1b[<{code};{x};{y}{last}").into_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+
+    use crate::greetd::Response;
+    use crate::replay::{Entry, What, encode, mask};
+
+    fn key(code: KeyCode, modifiers: KeyModifiers) -> Event {
+        Event::Key(KeyEvent::new(code, modifiers))
+    }
+
+    #[test]
+    fn masks_what_is_typed() {
+        assert_eq!(
+            mask(&key(KeyCode::Char('h'), KeyModifiers::NONE)),
+            key(KeyCode::Char('*'), KeyModifiers::NONE)
+        );
+        assert_eq!(
+            mask(&key(KeyCode::Char('H'), KeyModifiers::SHIFT)),
+            key(KeyCode::Char('*'), KeyModifiers::SHIFT)
+        );
+        assert_eq!(
+            mask(&key(KeyCode::Char('u'), KeyModifiers::CONTROL)),
+            key(KeyCode::Char('u'), KeyModifiers::CONTROL)
+        );
+        assert_eq!(
+            mask(&Event::Paste("hunter2".into())),
+            Event::Paste("*******".into())
+        );
+    }
+
+    #[test]
+    fn encodes_keys_like_a_terminal() {
+        let bytes = |event| encode(&event).unwrap();
+
+        assert_eq!(bytes(key(KeyCode::Char('a'), KeyModifiers::NONE)), b"a");
+        assert_eq!(bytes(key(KeyCode::Char('k'), KeyModifiers::ALT)), b"\x1bk");
+        assert_eq!(
+            bytes(key(KeyCode::Char('u'), KeyModifiers::CONTROL)),
+            b"\x15"
+        );
+        assert_eq!(bytes(key(KeyCode::Enter, KeyModifiers::NONE)), b"\r");
+        assert_eq!(bytes(key(KeyCode::Up, KeyModifiers::NONE)), b"\x1b[A");
+        assert_eq!(
+            bytes(key(KeyCode::Left, KeyModifiers::CONTROL)),
+            b"\x1b[1;5D"
+        );
+        assert_eq!(bytes(key(KeyCode::F(2), KeyModifiers::NONE)), b"\x1bOQ");
+        assert_eq!(
+            bytes(key(KeyCode::F(5), KeyModifiers::SHIFT)),
+            b"\x1b[15;2~"
+        );
+        assert_eq!(encode(&Event::Resize(80, 24)), None);
+    }
+
+    #[test]
+    fn entries_are_json_lines() -> color_eyre::Result<()> {
+        let entry = Entry {
+            at_ms: 1200,
+            what: What::Greetd(Response::Success),
+        };
+
+        assert_eq!(
+            serde_json::to_string(&entry)?,
+            r#"{"at_ms":1200,"greetd":{"type":"success"}}"#
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,369 @@
+use std::fs;
+use std::io;
+use std::sync::Once;
+
+/// Locking memory as root keeps needing this for every allocation, see [`crate::harden`].
+const CAP_IPC_LOCK: u32 = 14;
+const CAPABILITY_VERSION_3: u32 = 0x2008_0522;
+
+#[repr(C)]
+struct CapHeader {
+    version: u32,
+    pid: libc::c_int,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Default)]
+struct CapData {
+    effective: u32,
+    permitted: u32,
+    inheritable: u32,
+}
+
+/// Shuts the greeter in further once the greetd socket and the TTY are open: checks that no
+/// thread kept a capability past [`drop_capabilities`] and, with the `hardening` feature, only
+/// lets through the system calls a greeter makes. Only happens once, later reconnects to greetd
+/// are already inside.
+///
+/// A step that fails is logged and the others still go ahead, like [`crate::harden::harden`].
+pub fn enter() {
+    static ENTERED: Once = Once::new();
+    ENTERED.call_once(|| {
+        if let Err(err) = check_threads() {
+            tracing::warn!("failed to check the capabilities of each thread: {err}");
+        }
+        #[cfg(feature = "hardening")]
+        if let Err(err) = seccomp::install() {
+            tracing::warn!("failed to install the seccomp filter: {err}");
+        }
+    });
+}
+
+fn check(ret: libc::c_long) -> io::Result<()> {
+    match ret {
+        0 => Ok(()),
+        _ => Err(io::Error::last_os_error()),
+    }
+}
+
+/// Drops every capability but `CAP_IPC_LOCK` from this thread, and all of them from what it
+/// or anything it runs could ever get back.
+///
+/// Capabilities are per thread and only copied to a thread when it starts, so this has to run
+/// while the main thread is the only one, before the log's writer thread or the runtime's
+/// blocking pool exist. Nothing opened after needs them.
+pub fn drop_capabilities() -> io::Result<()> {
+    let mut header = CapHeader {
+        version: CAPABILITY_VERSION_3,
+        pid: 0,
+    };
+    let mut data = [CapData::default(); 2];
+    // SAFETY: capget fills in the two structs version 3 takes.
+    check(unsafe { libc::syscall(libc::SYS_capget, &mut header, data.as_mut_ptr()) })?;
+    // The usual greeter user has none to begin with.
+    if data.iter().all(|data| data.permitted == 0) {
+        return Ok(());
+    }
+    for cap in (0..64).filter(|&cap| cap != CAP_IPC_LOCK) {
+        // SAFETY: takes no pointers.
+        let ret = unsafe { libc::prctl(libc::PR_CAPBSET_DROP, cap as libc::c_ulong) };
+        if ret != 0 {
+            let err = io::Error::last_os_error();
+            // Past the last capability the kernel knows.
+            if err.raw_os_error() == Some(libc::EINVAL) {
+                break;
+            }
+            return Err(err);
+        }
+    }
+    // SAFETY: takes no pointers.
+    let ret = unsafe {
+        libc::prctl(
+            libc::PR_CAP_AMBIENT,
+            libc::PR_CAP_AMBIENT_CLEAR_ALL as libc::c_ulong,
+            0,
+            0,
+            0,
+        )
+    };
+    check(ret as libc::c_long)?;
+    let keep = 1 << CAP_IPC_LOCK;
+    let data = [
+        CapData {
+            effective: data[0].effective & keep,
+            permitted: data[0].permitted & keep,
+            inheritable: 0,
+        },
+        CapData::default(),
+    ];
+    // SAFETY: as for capget, and capset only reads them.
+    check(unsafe { libc::syscall(libc::SYS_capset, &header, data.as_ptr()) })
+}
+
+/// Warns about every thread that has a capability besides `CAP_IPC_LOCK` in effect, like one
+/// started before [`drop_capabilities`].
+fn check_threads() -> io::Result<()> {
+    for task in fs::read_dir("/proc/self/task")? {
+        let path = task?.path().join("status");
+        let status = match fs::read_to_string(&path) {
+            Ok(status) => status,
+            // The thread has exited since.
+            Err(err) if err.kind() == io::ErrorKind::NotFound => continue,
+            Err(err) => return Err(err),
+        };
+        let effective = effective_caps(&status).ok_or(io::ErrorKind::InvalidData)?;
+        if effective & !(1 << CAP_IPC_LOCK) != 0 {
+            tracing::warn!("{} still has capabilities {effective:#x}", path.display());
+        }
+    }
+    Ok(())
+}
+
+/// The effective capabilities on the `CapEff` line of a `/proc/*/status`.
+fn effective_caps(status: &str) -> Option<u64> {
+    let hex = status
+        .lines()
+        .find_map(|line| line.strip_prefix("CapEff:"))?;
+    u64::from_str_radix(hex.trim(), 16).ok()
+}
+
+#[cfg(feature = "hardening")]
+mod seccomp {
+    use std::io;
+
+    use libc::{
+        BPF_ABS, BPF_JEQ, BPF_JMP, BPF_K, BPF_LD, BPF_RET, BPF_W, SECCOMP_RET_ALLOW,
+        SECCOMP_RET_ERRNO, SECCOMP_RET_KILL_PROCESS, sock_filter, sock_fprog,
+    };
+
+    #[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64")))]
+    compile_error!("the seccomp filter only knows the system calls of x86_64 and aarch64");
+
+    #[cfg(target_arch = "x86_64")]
+    const AUDIT_ARCH: u32 = 0xc000_003e;
+    #[cfg(target_arch = "aarch64")]
+    const AUDIT_ARCH: u32 = 0xc000_00b7;
+
+    /// Offsets into `struct seccomp_data`.
+    const NR: u32 = 0;
+    const ARCH: u32 = 4;
+
+    /// What the greeter, the runtime under it and the commands it runs for widgets and power
+    /// actions call. These inherit the filter, and also can't gain privileges through setuid.
+    const ALLOWED: &[libc::c_long] = &[
+        // Files and the TTY.
+        libc::SYS_read,
+        libc::SYS_write,
+        libc::SYS_readv,
+        libc::SYS_writev,
+        libc::SYS_pread64,
+        libc::SYS_pwrite64,
+        libc::SYS_openat,
+        libc::SYS_close,
+        libc::SYS_close_range,
+        libc::SYS_fstat,
+        libc::SYS_newfstatat,
+        libc::SYS_statx,
+        libc::SYS_statfs,
+        libc::SYS_fstatfs,
+        libc::SYS_lseek,
+        libc::SYS_ioctl,
+        libc::SYS_fcntl,
+        libc::SYS_dup,
+        libc::SYS_dup3,
+        libc::SYS_pipe2,
+        libc::SYS_getdents64,
+        libc::SYS_readlinkat,
+        libc::SYS_faccessat,
+        libc::SYS_faccessat2,
+        libc::SYS_getcwd,
+        libc::SYS_chdir,
+        libc::SYS_mkdirat,
+        libc::SYS_renameat,
+        libc::SYS_renameat2,
+        libc::SYS_unlinkat,
+        libc::SYS_fchmod,
+        libc::SYS_fsync,
+        libc::SYS_fdatasync,
+        libc::SYS_ftruncate,
+        libc::SYS_fadvise64,
+        libc::SYS_umask,
+        // Waiting on the TTY, the greetd socket and timers.
+        libc::SYS_ppoll,
+        libc::SYS_pselect6,
+        libc::SYS_epoll_create1,
+        libc::SYS_epoll_ctl,
+        libc::SYS_epoll_pwait,
+        libc::SYS_eventfd2,
+        libc::SYS_futex,
+        libc::SYS_clock_gettime,
+        libc::SYS_clock_nanosleep,
+        libc::SYS_nanosleep,
+        libc::SYS_gettimeofday,
+        libc::SYS_sched_yield,
+        // Sockets, greetd's and the one syslog gets the audit log over.
+        libc::SYS_socket,
+        libc::SYS_connect,
+        libc::SYS_socketpair,
+        libc::SYS_sendto,
+        libc::SYS_recvfrom,
+        libc::SYS_sendmsg,
+        libc::SYS_recvmsg,
+        libc::SYS_shutdown,
+        libc::SYS_getsockopt,
+        libc::SYS_setsockopt,
+        libc::SYS_getsockname,
+        libc::SYS_getpeername,
+        // Memory.
+        libc::SYS_brk,
+        libc::SYS_mmap,
+        libc::SYS_munmap,
+        libc::SYS_mremap,
+        libc::SYS_mprotect,
+        libc::SYS_madvise,
+        libc::SYS_membarrier,
+        // Threads, signals and the commands it runs.
+        libc::SYS_clone,
+        libc::SYS_clone3,
+        libc::SYS_execve,
+        libc::SYS_wait4,
+        libc::SYS_waitid,
+        libc::SYS_exit,
+        libc::SYS_exit_group,
+        libc::SYS_kill,
+        libc::SYS_tgkill,
+        libc::SYS_pidfd_open,
+        libc::SYS_pidfd_send_signal,
+        libc::SYS_rt_sigaction,
+        libc::SYS_rt_sigprocmask,
+        libc::SYS_rt_sigreturn,
+        libc::SYS_sigaltstack,
+        libc::SYS_restart_syscall,
+        libc::SYS_set_robust_list,
+        libc::SYS_set_tid_address,
+        libc::SYS_rseq,
+        libc::SYS_prctl,
+        libc::SYS_prlimit64,
+        libc::SYS_setsid,
+        libc::SYS_setpgid,
+        libc::SYS_sched_getaffinity,
+        // Who and where it is.
+        libc::SYS_getpid,
+        libc::SYS_getppid,
+        libc::SYS_gettid,
+        libc::SYS_getuid,
+        libc::SYS_geteuid,
+        libc::SYS_getgid,
+        libc::SYS_getegid,
+        libc::SYS_getresuid,
+        libc::SYS_getresgid,
+        libc::SYS_uname,
+        libc::SYS_sysinfo,
+        libc::SYS_getrandom,
+        // The older calls x86_64 still has, which libc and the commands it runs use too.
+        #[cfg(target_arch = "x86_64")]
+        libc::SYS_open,
+        #[cfg(target_arch = "x86_64")]
+        libc::SYS_stat,
+        #[cfg(target_arch = "x86_64")]
+        libc::SYS_lstat,
+        #[cfg(target_arch = "x86_64")]
+        libc::SYS_access,
+        #[cfg(target_arch = "x86_64")]
+        libc::SYS_readlink,
+        #[cfg(target_arch = "x86_64")]
+        libc::SYS_pipe,
+        #[cfg(target_arch = "x86_64")]
+        libc::SYS_dup2,
+        #[cfg(target_arch = "x86_64")]
+        libc::SYS_poll,
+        #[cfg(target_arch = "x86_64")]
+        libc::SYS_select,
+        #[cfg(target_arch = "x86_64")]
+        libc::SYS_epoll_wait,
+        #[cfg(target_arch = "x86_64")]
+        libc::SYS_rename,
+        #[cfg(target_arch = "x86_64")]
+        libc::SYS_mkdir,
+        #[cfg(target_arch = "x86_64")]
+        libc::SYS_unlink,
+        #[cfg(target_arch = "x86_64")]
+        libc::SYS_getdents,
+        #[cfg(target_arch = "x86_64")]
+        libc::SYS_fork,
+        #[cfg(target_arch = "x86_64")]
+        libc::SYS_vfork,
+        #[cfg(target_arch = "x86_64")]
+        libc::SYS_arch_prctl,
+    ];
+
+    /// Installs the allow-list on every thread. Anything else fails with `EPERM` rather than
+    /// killing the greeter, and a system call made the 32-bit way kills it, since the numbers
+    /// mean something else there.
+    pub fn install() -> io::Result<()> {
+        let filter = filter();
+        let prog = sock_fprog {
+            len: filter.len() as u16,
+            filter: filter.as_ptr().cast_mut(),
+        };
+        // SAFETY: takes no pointers. Needed to install a filter without CAP_SYS_ADMIN.
+        if unsafe { libc::prctl(libc::PR_SET_NO_NEW_PRIVS, 1, 0, 0, 0) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        // SAFETY: `prog` points at `filter`, which outlives the call, and the kernel copies it.
+        let ret = unsafe {
+            libc::syscall(
+                libc::SYS_seccomp,
+                libc::SECCOMP_SET_MODE_FILTER,
+                libc::SECCOMP_FILTER_FLAG_TSYNC,
+                &prog,
+            )
+        };
+        match ret {
+            0 => Ok(()),
+            _ => Err(io::Error::last_os_error()),
+        }
+    }
+
+    fn filter() -> Vec<sock_filter> {
+        let stmt = |code: u32, k: u32| sock_filter {
+            code: code as u16,
+            jt: 0,
+            jf: 0,
+            k,
+        };
+        let jump = |k: u32, jt: u8, jf: u8| sock_filter {
+            code: (BPF_JMP | BPF_JEQ | BPF_K) as u16,
+            jt,
+            jf,
+            k,
+        };
+        let mut filter = vec![
+            stmt(BPF_LD | BPF_W | BPF_ABS, ARCH),
+            jump(AUDIT_ARCH, 1, 0),
+            stmt(BPF_RET | BPF_K, SECCOMP_RET_KILL_PROCESS),
+            stmt(BPF_LD | BPF_W | BPF_ABS, NR),
+        ];
+        for &nr in ALLOWED {
+            filter.push(jump(nr as u32, 0, 1));
+            filter.push(stmt(BPF_RET | BPF_K, SECCOMP_RET_ALLOW));
+        }
+        filter.push(stmt(
+            BPF_RET | BPF_K,
+            SECCOMP_RET_ERRNO | libc::EPERM as u32,
+        ));
+        filter
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::sandbox::effective_caps;
+
+    #[test]
+    fn reads_effective_caps() {
+        let status = "Name:\timpolite\nCapPrm:\t0000000000004000\nCapEff:\t0000000000004000\n";
+        assert_eq!(effective_caps(status), Some(1 << 14));
+        assert_eq!(effective_caps("Name:\timpolite\n"), None);
+    }
+}
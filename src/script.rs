@@ -0,0 +1,242 @@
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use color_eyre::Result;
+use color_eyre::eyre::{Context, bail, eyre};
+use tui_input::InputRequest;
+
+use crate::greetd::Response;
+use crate::{Focus, FormState, Model, Msg, update};
+
+/// One line of a script.
+#[derive(Debug)]
+enum Step {
+    /// Types the rest of the line into the focused field.
+    Type(String),
+    Focus(Focus),
+    Submit,
+    Answer,
+    Cancel,
+    StartShell,
+    /// greetd answers with this response, written as JSON.
+    Greetd(Response),
+    /// Moves the clock on, for the login throttle and anything else that looks at it, then
+    /// ticks so going idle happens.
+    Wait(Duration),
+    /// Logins are turned off with this message as if nologin(5) said so, or back on with
+    /// `nologin off`.
+    Nologin(Option<String>),
+    ExpectState(String),
+    /// The next request sent to greetd is of this type, like `create_session`.
+    ExpectRequest(String),
+    /// The login failed with an error that says this.
+    ExpectError(String),
+}
+
+/// Runs the script at `path` against `model` with a clock of its own, failing at the first
+/// expectation that doesn't hold. Nothing is drawn, greetd is whatever the script says it
+/// answers, nologin(5) is whatever the script says it is, and effects like animations and
+/// toasts that go away are dropped, so a script runs the same every time. Returns how many
+/// steps it ran.
+///
+/// A script has a step on each line, and `#` starts a comment:
+///
+/// ```text
+/// type alice
+/// focus password
+/// type hunter2
+/// submit
+/// expect request create_session
+/// greetd {"type": "auth_message", "auth_message_type": "secret", "auth_message": "Password:"}
+/// expect request post_auth_message_response
+/// cancel
+/// expect state idle
+/// ```
+pub async fn run(path: &Path, mut model: Model) -> Result<usize> {
+    let contents = std::fs::read_to_string(path)
+        .wrap_err_with(|| format!("failed to read {}", path.display()))?;
+    let steps = parse(&contents).wrap_err_with(|| format!("invalid script {}", path.display()))?;
+    model.clock = Some(Instant::now());
+    model.last_input = model.now();
+    model.nologin = None;
+    let count = steps.len();
+    for (line, step) in steps {
+        model = apply(model, step)
+            .await
+            .wrap_err_with(|| format!("{}:{line}", path.display()))?;
+    }
+    Ok(count)
+}
+
+/// The steps of `script` with their line numbers.
+fn parse(script: &str) -> Result<Vec<(usize, Step)>> {
+    script
+        .lines()
+        .enumerate()
+        .filter_map(|(idx, line)| {
+            let line = line.split_once('#').map_or(line, |(step, _)| step).trim();
+            (!line.is_empty()).then_some((idx + 1, line))
+        })
+        .map(|(line, text)| {
+            let step = parse_step(text).wrap_err_with(|| format!("line {line}: {text}"))?;
+            Ok((line, step))
+        })
+        .collect()
+}
+
+fn parse_step(text: &str) -> Result<Step> {
+    let (word, rest) = text.split_once(' ').unwrap_or((text, ""));
+    let rest = rest.trim();
+    let step = match word {
+        "type" => Step::Type(rest.to_string()),
+        "focus" => Step::Focus(match rest {
+            "username" => Focus::UsernameField,
+            "password" => Focus::PasswordField,
+            "users" => Focus::UserMenu,
+            "sessions" => Focus::DesktopPicker,
+            "power" => Focus::PowerMenu,
+            "keyboard" => Focus::Keyboard,
+            _ => bail!("unknown field {rest:?}"),
+        }),
+        "submit" => Step::Submit,
+        "answer" => Step::Answer,
+        "cancel" => Step::Cancel,
+        "start-shell" => Step::StartShell,
+        "greetd" => Step::Greetd(serde_json::from_str(rest).wrap_err("not a greetd response")?),
+        "wait" => Step::Wait(parse_duration(rest)?),
+        "nologin" => Step::Nologin(match rest {
+            "" => bail!("nologin needs a message, or off"),
+            "off" => None,
+            message => Some(message.to_string()),
+        }),
+        "expect" => {
+            let (what, value) = rest.split_once(' ').unwrap_or((rest, ""));
+            let value = value.trim().to_string();
+            match what {
+                "state" => Step::ExpectState(value),
+                "request" => Step::ExpectRequest(value),
+                "error" => Step::ExpectError(value),
+                _ => bail!("can't expect {what:?}"),
+            }
+        }
+        _ => bail!("unknown step {word:?}"),
+    };
+    Ok(step)
+}
+
+/// `500ms` or `2s`.
+fn parse_duration(text: &str) -> Result<Duration> {
+    let invalid = || eyre!("{text:?} isn't a duration like 500ms or 2s");
+    if let Some(ms) = text.strip_suffix("ms") {
+        return Ok(Duration::from_millis(ms.parse().map_err(|_| invalid())?));
+    }
+    if let Some(secs) = text.strip_suffix('s') {
+        return Ok(Duration::from_secs(secs.parse().map_err(|_| invalid())?));
+    }
+    Err(invalid())
+}
+
+async fn apply(mut model: Model, step: Step) -> Result<Model> {
+    let msg = match step {
+        Step::Type(text) => match model.focus {
+            Focus::UsernameField => {
+                let mut username = model.username.clone();
+                for c in text.chars() {
+                    username.handle(InputRequest::InsertChar(c));
+                }
+                Msg::UsernameUpdate(username)
+            }
+            Focus::PasswordField => {
+                let mut password = model.password.clone();
+                for c in text.chars() {
+                    password.handle(InputRequest::InsertChar(c));
+                }
+                Msg::PasswordUpdate(password)
+            }
+            ref focus => bail!("can't type with {focus:?} focused"),
+        },
+        Step::Focus(focus) => Msg::FocusOn(focus),
+        Step::Submit => Msg::SubmitLogin,
+        Step::Answer => Msg::AnswerPrompt,
+        Step::Cancel => Msg::CancelSession,
+        Step::StartShell => Msg::StartShell,
+        Step::Greetd(res) => Msg::GreetdRes(res),
+        Step::Wait(by) => {
+            model.clock = Some(model.now() + by);
+            Msg::Tick
+        }
+        Step::Nologin(message) => {
+            model.nologin = message;
+            return Ok(model);
+        }
+        Step::ExpectState(name) => {
+            let state = model.form_state.name();
+            if state != name {
+                bail!("expected state {name}, but it's {state}");
+            }
+            return Ok(model);
+        }
+        Step::ExpectRequest(kind) => {
+            let Ok(req) = model.req_rx.try_recv() else {
+                bail!("expected a {kind} request, but nothing was sent");
+            };
+            let sent = serde_json::to_value(&req)?;
+            let sent = sent["type"].as_str().unwrap_or_default();
+            if sent != kind {
+                bail!("expected a {kind} request, but {sent} was sent");
+            }
+            return Ok(model);
+        }
+        Step::ExpectError(text) => {
+            match &model.form_state {
+                FormState::LoginFailed(_, description) if description.contains(&*text) => {}
+                FormState::LoginFailed(_, description) => {
+                    bail!("expected an error saying {text:?}, but it says {description:?}")
+                }
+                state => bail!("expected an error, but the state is {}", state.name()),
+            }
+            return Ok(model);
+        }
+    };
+    let (model, _) = update(model, msg).await;
+    Ok(model)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::script::{Step, parse, parse_duration};
+
+    #[test]
+    fn parses_steps() -> color_eyre::Result<()> {
+        let steps = parse(
+            "# cancel while authenticating\n\
+             type alice  \n\
+             \n\
+             greetd {\"type\": \"success\"}\n\
+             wait 250ms # the throttle\n\
+             expect state idle\n\
+             nologin Down for maintenance.\n",
+        )?;
+
+        let lines = steps.iter().map(|(line, _)| *line).collect::<Vec<_>>();
+        assert_eq!(lines, [2, 4, 5, 6, 7]);
+        assert!(matches!(&steps[0].1, Step::Type(text) if text == "alice"));
+        assert!(matches!(&steps[2].1, Step::Wait(by) if *by == Duration::from_millis(250)));
+        assert!(matches!(&steps[3].1, Step::ExpectState(state) if state == "idle"));
+        assert!(
+            matches!(&steps[4].1, Step::Nologin(Some(message)) if message == "Down for maintenance.")
+        );
+        assert!(parse("dance").is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn durations() {
+        assert_eq!(parse_duration("2s").ok(), Some(Duration::from_secs(2)));
+        assert_eq!(parse_duration("15ms").ok(), Some(Duration::from_millis(15)));
+        assert!(parse_duration("soon").is_err());
+    }
+}
@@ -0,0 +1,345 @@
+use std::borrow::Cow;
+use std::fmt;
+use std::ops::Deref;
+use std::sync::atomic::{Ordering, compiler_fence};
+
+use serde::{Deserialize, Serialize};
+use tui_input::{Input, InputRequest, StateChanged};
+
+use crate::readline::Edit;
+
+/// What stands in for each character of the password while it's edited.
+const MASK: char = '*';
+
+/// What [`PasswordMask::Fixed`] shows.
+const FIXED: &str = "********";
+
+/// How the password field shows what was typed.
+///
+/// A `*` per character is what people expect, but gives away how long the password is to
+/// anyone looking over a shoulder or through a camera.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum PasswordMask {
+    #[default]
+    Asterisks,
+    /// The same few `*`s however long it is, once anything is typed.
+    Fixed,
+    /// Nothing at all.
+    Hidden,
+}
+
+impl PasswordMask {
+    /// What the field shows for `input`, and where the cursor goes in that.
+    pub fn apply(self, input: &SecretInput) -> (&str, usize) {
+        match self {
+            PasswordMask::Asterisks => (input.mask(), input.cursor()),
+            PasswordMask::Fixed if !input.value.is_empty() => (FIXED, FIXED.len()),
+            PasswordMask::Fixed | PasswordMask::Hidden => ("", 0),
+        }
+    }
+}
+
+/// The contents of the password field, zeroed in memory when cleared or dropped.
+///
+/// tui_input's `Input` keeps its value in a `String` that grows by reallocating and is cloned
+/// on every key press, leaving copies of the password around the heap. Here the text lives in
+/// a buffer that is wiped before it's freed or outgrown, while the cursor and editing run on
+/// an `Input` of `*`s of the same length. That also makes the whole password one word for
+/// Ctrl+W and Alt+B, so they don't give away where its spaces are.
+#[derive(Clone, Default)]
+pub struct SecretInput {
+    value: String,
+    mask: Input,
+}
+
+impl SecretInput {
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+
+    /// One `*` per character, for drawing the field.
+    pub fn mask(&self) -> &str {
+        self.mask.value()
+    }
+
+    pub fn cursor(&self) -> usize {
+        self.mask.cursor()
+    }
+
+    pub fn handle(&mut self, request: InputRequest) -> Option<StateChanged> {
+        let (cursor, len) = (self.cursor(), self.mask.value().len());
+        let (request, typed) = match request {
+            InputRequest::InsertChar(c) => (InputRequest::InsertChar(MASK), Some(c)),
+            request => (request, None),
+        };
+        let changed = self.mask.handle(request);
+        if let Some(c) = typed {
+            self.insert(cursor, c);
+        }
+        // Every character of the mask is the same, so only how many went and where the cursor
+        // ended up tell what was deleted.
+        let removed = len.saturating_sub(self.mask.value().len());
+        if removed > 0 {
+            self.remove(self.cursor(), removed);
+        }
+        changed
+    }
+
+    pub fn clear(&mut self) {
+        wipe(&mut self.value);
+        self.mask = Input::default();
+    }
+
+    fn byte_index(&self, cursor: usize) -> usize {
+        self.value
+            .char_indices()
+            .nth(cursor)
+            .map_or(self.value.len(), |(idx, _)| idx)
+    }
+
+    fn insert(&mut self, cursor: usize, c: char) {
+        if self.value.len() + c.len_utf8() > self.value.capacity() {
+            let mut grown = String::with_capacity((self.value.capacity() * 2).max(64));
+            grown.push_str(&self.value);
+            wipe(&mut self.value);
+            self.value = grown;
+        }
+        let idx = self.byte_index(cursor);
+        self.value.insert(idx, c);
+    }
+
+    fn remove(&mut self, cursor: usize, count: usize) {
+        let start = self.byte_index(cursor);
+        let end = self.byte_index(cursor + count);
+        self.value.replace_range(start..end, "");
+        // What came after the deleted text was moved up, leaving a copy of its end behind.
+        wipe_spare(&mut self.value);
+    }
+}
+
+impl Edit for SecretInput {
+    fn cursor(&self) -> usize {
+        self.cursor()
+    }
+
+    fn handle(&mut self, request: InputRequest) -> Option<StateChanged> {
+        self.handle(request)
+    }
+}
+
+impl Drop for SecretInput {
+    fn drop(&mut self) {
+        wipe(&mut self.value);
+    }
+}
+
+impl fmt::Debug for SecretInput {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("SecretInput").field(&Redacted).finish()
+    }
+}
+
+/// A copy of the password on its way out of the form, like an answer to PAM in a greetd request,
+/// zeroed in memory when dropped.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(transparent)]
+pub struct Secret(String);
+
+impl From<&str> for Secret {
+    fn from(value: &str) -> Self {
+        Self(value.to_string())
+    }
+}
+
+impl Deref for Secret {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Drop for Secret {
+    fn drop(&mut self) {
+        wipe(&mut self.0);
+    }
+}
+
+impl fmt::Debug for Secret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&Redacted, f)
+    }
+}
+
+/// Stands in for a secret in `Debug` output.
+pub struct Redacted;
+
+impl fmt::Debug for Redacted {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("<redacted>")
+    }
+}
+
+/// Keys of greetd request bodies whose values are secret: answers to PAM and the session
+/// environment.
+const SECRET_KEYS: [&str; 2] = ["response", "env"];
+
+/// `text` with the rest of the line cut off wherever a greetd request body quoted in it has a
+/// secret, for error and crash output.
+///
+/// The whole rest of the line goes rather than just the value, which holds up against a body
+/// that was escaped again by `{:?}` or cut short.
+pub fn scrub(text: &str) -> Cow<'_, str> {
+    if !text.lines().any(|line| secret_at(line).is_some()) {
+        return Cow::Borrowed(text);
+    }
+    let scrubbed = text
+        .split_inclusive('\n')
+        .map(|line| match secret_at(line) {
+            Some(idx) => {
+                let end = match line.ends_with('\n') {
+                    true => "\n",
+                    false => "",
+                };
+                format!("{}<redacted>{end}", &line[..idx])
+            }
+            None => line.to_string(),
+        })
+        .collect();
+    Cow::Owned(scrubbed)
+}
+
+/// Where the value of the first secret key in `line` starts, quoted as JSON or escaped JSON.
+fn secret_at(line: &str) -> Option<usize> {
+    SECRET_KEYS
+        .iter()
+        .flat_map(|key| line.match_indices(key))
+        .filter_map(|(idx, key)| {
+            if !line[..idx].ends_with('"') {
+                return None;
+            }
+            let rest = line[idx + key.len()..].trim_start_matches('\\');
+            let value = rest.strip_prefix('"')?.trim_start().strip_prefix(':')?;
+            Some(line.len() - value.len())
+        })
+        .min()
+}
+
+/// Zeroes all of `value`'s buffer and leaves it empty.
+fn wipe(value: &mut String) {
+    value.clear();
+    wipe_spare(value);
+}
+
+/// Zeroes all of `buf`, like a serialized request with the password in it, and leaves it empty.
+pub fn wipe_bytes(buf: &mut Vec<u8>) {
+    buf.clear();
+    wipe_spare_bytes(buf);
+}
+
+/// Zeroes the buffer past the end of `value`, where deleted text can linger.
+fn wipe_spare(value: &mut String) {
+    // SAFETY: only the spare capacity past the end is written, which isn't part of the
+    // string, so it stays valid UTF-8.
+    wipe_spare_bytes(unsafe { value.as_mut_vec() });
+}
+
+fn wipe_spare_bytes(buf: &mut Vec<u8>) {
+    for byte in buf.spare_capacity_mut() {
+        // SAFETY: the pointer comes from a live `&mut`, so it is valid and aligned. Volatile
+        // so the writes aren't optimized out for never being read.
+        unsafe { std::ptr::write_volatile(byte.as_mut_ptr(), 0) };
+    }
+    compiler_fence(Ordering::SeqCst);
+}
+
+#[cfg(test)]
+mod tests {
+    use tui_input::InputRequest;
+
+    use std::borrow::Cow;
+
+    use crate::secret::{PasswordMask, SecretInput, scrub, wipe_bytes};
+
+    fn typed(text: &str) -> SecretInput {
+        let mut input = SecretInput::default();
+        for c in text.chars() {
+            input.handle(InputRequest::InsertChar(c));
+        }
+        input
+    }
+
+    #[test]
+    fn edits_like_an_input() {
+        let mut input = typed("pässword");
+        assert_eq!((input.mask(), input.cursor()), ("********", 8));
+
+        input.handle(InputRequest::GoToStart);
+        input.handle(InputRequest::GoToNextChar);
+        input.handle(InputRequest::DeleteNextChar);
+        input.handle(InputRequest::InsertChar('a'));
+        assert_eq!((input.value(), input.cursor()), ("password", 2));
+
+        input.handle(InputRequest::DeletePrevChar);
+        input.handle(InputRequest::DeleteTillEnd);
+        assert_eq!(input.value(), "p");
+
+        let mut input = typed("two words");
+        input.handle(InputRequest::DeletePrevWord);
+        assert_eq!(input.value(), "");
+    }
+
+    #[test]
+    fn masks_hide_the_length() {
+        let (short, long) = (typed("ab"), typed("correct horse"));
+
+        assert_eq!(PasswordMask::Asterisks.apply(&short), ("**", 2));
+        assert_eq!(
+            PasswordMask::Fixed.apply(&short),
+            PasswordMask::Fixed.apply(&long)
+        );
+        assert_eq!(PasswordMask::Fixed.apply(&SecretInput::default()), ("", 0));
+        assert_eq!(PasswordMask::Hidden.apply(&long), ("", 0));
+    }
+
+    #[test]
+    fn scrubs_request_bodies() {
+        let body = r#"{"type":"post_auth_message_response","response":"hunter2"}"#;
+        assert_eq!(
+            scrub(&format!("wrote {body}\nnext line")),
+            "wrote {\"type\":\"post_auth_message_response\",\"response\":<redacted>\nnext line"
+        );
+        assert!(!scrub(&format!("{body:?}")).contains("hunter2"));
+        assert!(!scrub(r#"{"env": ["TOKEN=hunter2"]}"#).contains("hunter2"));
+
+        assert!(matches!(
+            scrub("response: Some(<redacted>)"),
+            Cow::Borrowed(_)
+        ));
+    }
+
+    #[test]
+    fn deleted_text_is_wiped() {
+        let mut input = typed("hunter2");
+        input.handle(InputRequest::GoToStart);
+        input.handle(InputRequest::DeleteNextChar);
+        input.clear();
+
+        // SAFETY: wiping initialized the whole spare capacity.
+        let spare = unsafe { input.value.as_mut_vec() }.spare_capacity_mut();
+        assert!(spare.iter().all(|byte| unsafe { byte.assume_init() } == 0));
+        assert!(!spare.is_empty());
+    }
+
+    #[test]
+    fn wiped_bytes_are_zero() {
+        let mut buf = b"hunter2".to_vec();
+        wipe_bytes(&mut buf);
+
+        assert!(buf.is_empty());
+        // SAFETY: wiping initialized the whole spare capacity.
+        let spare = buf.spare_capacity_mut();
+        assert!(spare.iter().all(|byte| unsafe { byte.assume_init() } == 0));
+    }
+}
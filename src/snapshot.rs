@@ -0,0 +1,163 @@
+use color_eyre::Result;
+use color_eyre::eyre::eyre;
+use mana_tui::prelude::*;
+use ratatui::Terminal;
+use ratatui::backend::TestBackend;
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::style::{Color, Modifier, Style};
+
+use crate::styling;
+
+/// Lays `view` out in a `width` by `height` screen and draws it, the way the terminal would
+/// have shown it.
+pub fn render(view: View, width: u16, height: u16) -> Result<Buffer> {
+    Ok(backend(view, width, height)?.buffer().clone())
+}
+
+/// Draws `view` to a [`TestBackend`] of `width` by `height` through a [`Terminal`], for tests
+/// of a whole screen or any one subview of it.
+pub fn backend(view: View, width: u16, height: u16) -> Result<TestBackend> {
+    let area = Rect::new(0, 0, width, height);
+    let mut terminal = Terminal::new(TestBackend::new(width, height))?;
+    let mut ctx = ElementCtx::new();
+    let root = ctx.spawn_ui(view);
+    ctx.calculate_layout(root, area)
+        .map_err(|err| eyre!("failed to lay out the view: {err}"))?;
+    terminal.draw(|frame| ctx.render(root, area, frame.buffer_mut()))?;
+    Ok(terminal.backend().clone())
+}
+
+/// Checks the text of `buf` against `snapshots/{name}.txt`.
+///
+/// A snapshot that isn't there fails like one that doesn't match. With `UPDATE_SNAPSHOTS` set
+/// they're all written instead, to be looked over in the diff and committed.
+#[cfg(any(test, feature = "test-util"))]
+pub fn assert_golden(name: &str, buf: &Buffer) {
+    let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("snapshots")
+        .join(format!("{name}.txt"));
+    let actual = dump(buf, false);
+    if std::env::var_os("UPDATE_SNAPSHOTS").is_some() {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, &actual).unwrap();
+        return;
+    }
+    let expected = match std::fs::read_to_string(&path) {
+        Ok(expected) => expected,
+        Err(err) => panic!(
+            "can't read {}: {err}, run with UPDATE_SNAPSHOTS=1 to write it\nactual:\n{actual}",
+            path.display()
+        ),
+    };
+    assert!(
+        actual == expected,
+        "{name} doesn't match {}, rerun with UPDATE_SNAPSHOTS=1 if that's intended\n\
+         expected:\n{expected}\nactual:\n{actual}",
+        path.display()
+    );
+}
+
+/// The text of `buf` line by line, with SGR escapes for its colors and modifiers when `ansi`.
+pub fn dump(buf: &Buffer, ansi: bool) -> String {
+    let mut out = String::new();
+    for y in buf.area.top()..buf.area.bottom() {
+        let mut style = None;
+        // Cells covered by the wide character before them.
+        let mut skip = 0;
+        for x in buf.area.left()..buf.area.right() {
+            let cell = &buf[(x, y)];
+            if skip > 0 {
+                skip -= 1;
+                continue;
+            }
+            let cell_style = cell.style();
+            if ansi && style != Some(cell_style) {
+                out.push_str(&sgr(cell_style));
+                style = Some(cell_style);
+            }
+            out.push_str(cell.symbol());
+            skip = styling::width(cell.symbol()).saturating_sub(1);
+        }
+        if ansi {
+            out.push_str("\x1b[0m");
+        }
+        out.push('\n');
+    }
+    out
+}
+
+/// The escape that switches to `style` from whatever came before.
+fn sgr(style: Style) -> String {
+    let mut codes = vec!["0".to_string()];
+    let modifiers = [
+        (Modifier::BOLD, "1"),
+        (Modifier::DIM, "2"),
+        (Modifier::ITALIC, "3"),
+        (Modifier::UNDERLINED, "4"),
+        (Modifier::SLOW_BLINK, "5"),
+        (Modifier::RAPID_BLINK, "6"),
+        (Modifier::REVERSED, "7"),
+        (Modifier::HIDDEN, "8"),
+        (Modifier::CROSSED_OUT, "9"),
+    ];
+    for (modifier, code) in modifiers {
+        if style.add_modifier.contains(modifier) {
+            codes.push(code.to_string());
+        }
+    }
+    codes.extend(style.fg.and_then(|color| color_code(color, false)));
+    codes.extend(style.bg.and_then(|color| color_code(color, true)));
+    format!("\x1b[{}m", codes.join(";"))
+}
+
+fn color_code(color: Color, background: bool) -> Option<String> {
+    let (extended, offset) = match background {
+        true => (48, 10),
+        false => (38, 0),
+    };
+    let named = |code: u8| Some((code + offset).to_string());
+    match color {
+        Color::Reset => None,
+        Color::Black => named(30),
+        Color::Red => named(31),
+        Color::Green => named(32),
+        Color::Yellow => named(33),
+        Color::Blue => named(34),
+        Color::Magenta => named(35),
+        Color::Cyan => named(36),
+        Color::Gray => named(37),
+        Color::DarkGray => named(90),
+        Color::LightRed => named(91),
+        Color::LightGreen => named(92),
+        Color::LightYellow => named(93),
+        Color::LightBlue => named(94),
+        Color::LightMagenta => named(95),
+        Color::LightCyan => named(96),
+        Color::White => named(97),
+        Color::Indexed(idx) => Some(format!("{extended};5;{idx}")),
+        Color::Rgb(r, g, b) => Some(format!("{extended};2;{r};{g};{b}")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::buffer::Buffer;
+    use ratatui::layout::Rect;
+    use ratatui::style::{Color, Style, Stylize};
+
+    use crate::snapshot::dump;
+
+    #[test]
+    fn dumps_text_and_colors() {
+        let mut buf = Buffer::empty(Rect::new(0, 0, 6, 2));
+        buf.set_string(0, 0, "hi 日", Style::new().fg(Color::Rgb(255, 0, 0)).bold());
+        buf.set_string(0, 1, "ok", Style::new().bg(Color::Blue));
+
+        assert_eq!(dump(&buf, false), "hi 日 \nok    \n");
+        assert_eq!(
+            dump(&buf, true),
+            "\x1b[0;1;38;2;255;0;0mhi 日\x1b[0m \x1b[0m\n\x1b[0;44mok\x1b[0m    \x1b[0m\n"
+        );
+    }
+}
@@ -0,0 +1,289 @@
+use ratatui::style::{Color, Style};
+use ratatui::text::Span;
+use serde::{Deserialize, Serialize};
+
+use crate::battery;
+use crate::clock;
+use crate::config::Config;
+use crate::disk;
+use crate::idle::IdleLevel;
+use crate::network::{self, Connectivity, NetworkState};
+use crate::os_release::OsRelease;
+use crate::term;
+use crate::theme::Theme;
+use crate::uptime;
+use crate::vi;
+
+/// What segments get to look at when they render.
+pub struct SegmentContext<'a> {
+    pub config: &'a Config,
+    pub theme: Theme,
+    pub idle: IdleLevel,
+    pub caps_lock: bool,
+    pub keymap: Option<&'a str>,
+    pub seat: Option<&'a str>,
+    /// What NetworkManager says, `None` without it.
+    pub connectivity: Option<Connectivity>,
+    /// The vi mode of the focused field, if it has one.
+    pub vi_mode: Option<vi::Mode>,
+}
+
+/// One piece of the status bar.
+pub trait Segment {
+    /// What to show right now, `None` to leave the segment out.
+    fn render(&self, ctx: &SegmentContext) -> Option<Span<'static>>;
+}
+
+/// The segments that can be put in the status bar, by their config name.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum SegmentKind {
+    Clock,
+    Keymap,
+    CapsLock,
+    Battery,
+    Seat,
+    Uptime,
+    Network,
+    Disk,
+    ViMode,
+}
+
+impl SegmentKind {
+    pub fn segment(self) -> &'static dyn Segment {
+        match self {
+            SegmentKind::Clock => &ClockSegment,
+            SegmentKind::Keymap => &KeymapSegment,
+            SegmentKind::CapsLock => &CapsLockSegment,
+            SegmentKind::Battery => &BatterySegment,
+            SegmentKind::Seat => &SeatSegment,
+            SegmentKind::Uptime => &UptimeSegment,
+            SegmentKind::Network => &NetworkSegment,
+            SegmentKind::Disk => &DiskSegment,
+            SegmentKind::ViMode => &ViModeSegment,
+        }
+    }
+}
+
+/// Renders `kinds` in order, skipping the ones with nothing to show.
+pub fn render(kinds: &[SegmentKind], ctx: &SegmentContext) -> Vec<Span<'static>> {
+    kinds
+        .iter()
+        .filter_map(|kind| kind.segment().render(ctx))
+        .collect()
+}
+
+/// The local time, in the idle format while the screen is dimmed.
+pub struct ClockSegment;
+
+impl Segment for ClockSegment {
+    fn render(&self, ctx: &SegmentContext) -> Option<Span<'static>> {
+        let format = match ctx.idle {
+            IdleLevel::Active => &ctx.config.clock.format,
+            IdleLevel::Dimmed | IdleLevel::Blank => &ctx.config.clock.idle_format,
+        };
+        Some(Span::styled(
+            clock::now(format),
+            Style::new().fg(ctx.theme.text_dim),
+        ))
+    }
+}
+
+pub struct KeymapSegment;
+
+impl Segment for KeymapSegment {
+    fn render(&self, ctx: &SegmentContext) -> Option<Span<'static>> {
+        let icon = match ctx.config.symbols.nerd_fonts {
+            true => "\u{f030c} ",
+            false => "kbd ",
+        };
+        let keymap = ctx.keymap?;
+        Some(Span::styled(
+            format!("{icon}{keymap}"),
+            Style::new().fg(ctx.theme.text_dim),
+        ))
+    }
+}
+
+/// Only shown while a field with vi mode is focused.
+pub struct ViModeSegment;
+
+impl Segment for ViModeSegment {
+    fn render(&self, ctx: &SegmentContext) -> Option<Span<'static>> {
+        let (mode, theme) = (ctx.vi_mode?, ctx.theme);
+        let style = match mode {
+            vi::Mode::Insert => Style::new().fg(theme.text_dim),
+            vi::Mode::Normal => Style::new().fg(theme.accent).bold(),
+        };
+        Some(Span::styled(mode.label(), style))
+    }
+}
+
+/// Only shown while caps lock is known to be on.
+pub struct CapsLockSegment;
+
+impl Segment for CapsLockSegment {
+    fn render(&self, ctx: &SegmentContext) -> Option<Span<'static>> {
+        ctx.caps_lock
+            .then(|| Span::styled("CAPS", Style::new().fg(ctx.theme.warning).bold()))
+    }
+}
+
+/// Charge of the batteries, colored by the configured thresholds.
+pub struct BatterySegment;
+
+impl Segment for BatterySegment {
+    fn render(&self, ctx: &SegmentContext) -> Option<Span<'static>> {
+        let battery = battery::read()?;
+        let (config, theme) = (&ctx.config.battery, ctx.theme);
+        let color = match battery.capacity {
+            _ if battery.is_charging() => theme.success,
+            capacity if capacity < config.critical => theme.error,
+            capacity if capacity < config.warning => theme.warning,
+            _ => theme.text_dim,
+        };
+        let icon = match (ctx.config.symbols.nerd_fonts, battery.is_charging()) {
+            (true, true) => "\u{f0084} ",
+            (true, false) => "\u{f0079} ",
+            (false, true) => "bat+ ",
+            (false, false) => "bat ",
+        };
+        Some(Span::styled(
+            format!("{icon}{}%", battery.capacity),
+            Style::new().fg(color),
+        ))
+    }
+}
+
+pub struct SeatSegment;
+
+impl Segment for SeatSegment {
+    fn render(&self, ctx: &SegmentContext) -> Option<Span<'static>> {
+        let seat = ctx.seat?;
+        Some(Span::styled(
+            seat.to_string(),
+            Style::new().fg(ctx.theme.text_dim),
+        ))
+    }
+}
+
+pub struct UptimeSegment;
+
+impl Segment for UptimeSegment {
+    fn render(&self, ctx: &SegmentContext) -> Option<Span<'static>> {
+        let up = uptime::format(uptime::read()?);
+        Some(Span::styled(up, Style::new().fg(ctx.theme.text_dim)))
+    }
+}
+
+pub struct NetworkSegment;
+
+impl Segment for NetworkSegment {
+    fn render(&self, ctx: &SegmentContext) -> Option<Span<'static>> {
+        let theme = ctx.theme;
+        let (text, color) = match ctx.connectivity {
+            Some(Connectivity::Full) => ("online".to_string(), theme.text_dim),
+            Some(Connectivity::Portal) => ("captive portal".to_string(), theme.warning),
+            Some(Connectivity::Limited) => ("no internet".to_string(), theme.warning),
+            Some(Connectivity::None) => ("offline".to_string(), theme.error),
+            Some(Connectivity::Unknown) | None => kernel_state(theme),
+        };
+        Some(Span::styled(text, Style::new().fg(color)))
+    }
+}
+
+/// What the kernel's links and routes say, for when NetworkManager can't.
+fn kernel_state(theme: Theme) -> (String, Color) {
+    match network::read() {
+        NetworkState::Online { interface } => (format!("net {interface}"), theme.text_dim),
+        NetworkState::NoRoute { interface } => {
+            (format!("net {interface}, no route"), theme.warning)
+        }
+        NetworkState::Offline => ("offline".to_string(), theme.error),
+    }
+}
+
+/// Only shown once the configured filesystem is filling up.
+pub struct DiskSegment;
+
+impl Segment for DiskSegment {
+    fn render(&self, ctx: &SegmentContext) -> Option<Span<'static>> {
+        let config = &ctx.config.disk;
+        let used = disk::read(&config.path)?.used_percent();
+        let color = match used {
+            used if used >= config.critical => ctx.theme.error,
+            used if used >= config.warning => ctx.theme.warning,
+            _ => return None,
+        };
+        let path = config.path.display();
+        Some(Span::styled(
+            format!("disk {path} {used}% full"),
+            Style::new().fg(color).bold(),
+        ))
+    }
+}
+
+/// The console keymap from vconsole.conf(5), or the X11 layout Debian keeps in
+/// `/etc/default/keyboard`. Both use the same `KEY=value` format as os-release(5).
+pub fn detect_keymap() -> Option<String> {
+    [
+        ("/etc/vconsole.conf", "KEYMAP"),
+        ("/etc/default/keyboard", "XKBLAYOUT"),
+    ]
+    .into_iter()
+    .find_map(|(path, key)| {
+        let contents = std::fs::read_to_string(path).ok()?;
+        OsRelease::parse(&contents).get(key).map(str::to_string)
+    })
+}
+
+/// The logind seat and the tty the greeter runs on, like `seat0 tty1`.
+pub fn detect_seat() -> Option<String> {
+    let parts = [std::env::var("XDG_SEAT").ok(), term::tty_name()];
+    let seat = parts.into_iter().flatten().collect::<Vec<_>>().join(" ");
+    (!seat.is_empty()).then_some(seat)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::Config;
+    use crate::idle::IdleLevel;
+    use crate::status_bar::{SegmentContext, SegmentKind, render};
+    use crate::term::Appearance;
+    use crate::theme::ThemePreset;
+    use crate::vi::Mode;
+
+    #[test]
+    fn skips_segments_without_anything_to_show() {
+        let config = Config::default();
+        let mut ctx = SegmentContext {
+            config: &config,
+            theme: ThemePreset::Lipgloss.theme(Appearance::Dark),
+            idle: IdleLevel::Active,
+            caps_lock: false,
+            keymap: None,
+            seat: Some("seat0 tty1"),
+            connectivity: None,
+            vi_mode: None,
+        };
+        let kinds = [
+            SegmentKind::CapsLock,
+            SegmentKind::Keymap,
+            SegmentKind::Seat,
+            SegmentKind::ViMode,
+        ];
+
+        let text = |ctx: &SegmentContext| {
+            render(&kinds, ctx)
+                .iter()
+                .map(|span| span.content.to_string())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(text(&ctx), ["seat0 tty1"]);
+
+        ctx.caps_lock = true;
+        ctx.keymap = Some("de");
+        ctx.vi_mode = Some(Mode::Normal);
+        assert_eq!(text(&ctx), ["CAPS", "kbd de", "seat0 tty1", "NORMAL"]);
+    }
+}
@@ -0,0 +1,140 @@
+use std::ffi::OsString;
+use std::fs::{DirBuilder, Metadata, OpenOptions, Permissions};
+use std::io::{ErrorKind, Read, Write};
+use std::os::unix::fs::{DirBuilderExt, MetadataExt, OpenOptionsExt, PermissionsExt};
+use std::path::{Path, PathBuf};
+
+use color_eyre::Result;
+use color_eyre::eyre::{Context, bail};
+use tracing::instrument;
+
+/// Files the greeter keeps are for the greeter user only.
+const FILE_MODE: u32 = 0o600;
+const DIR_MODE: u32 = 0o700;
+
+/// Reads a file the greeter keeps, `None` if it isn't there yet.
+///
+/// Refuses a file someone else owns or anyone can write to, since what's in it ends up on the
+/// login screen.
+#[instrument(err)]
+pub fn read(path: &Path) -> Result<Option<String>> {
+    let file = OpenOptions::new()
+        .read(true)
+        .custom_flags(libc::O_NOFOLLOW)
+        .open(path);
+    let mut file = match file {
+        Ok(file) => file,
+        Err(err) if err.kind() == ErrorKind::NotFound => return Ok(None),
+        Err(err) => return Err(err).wrap_err("failed to open the file"),
+    };
+    check(&file.metadata()?, path)?;
+    let mut contents = String::new();
+    file.read_to_string(&mut contents)
+        .wrap_err("failed to read the file")?;
+    Ok(Some(contents))
+}
+
+/// Replaces a file the greeter keeps with `contents`, readable by the greeter user only.
+///
+/// Its directory is created `0700` if it's missing, and otherwise has to belong to the greeter
+/// user, so nobody else can swap the file out. The file is written next to it first and moved
+/// over, so a crash never leaves half of it behind.
+#[instrument(err, skip(contents))]
+pub fn write(path: &Path, contents: &[u8]) -> Result<()> {
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    DirBuilder::new()
+        .recursive(true)
+        .mode(DIR_MODE)
+        .create(dir)
+        .wrap_err("failed to create the directory")?;
+    let meta = dir.metadata().wrap_err("failed to look at the directory")?;
+    check(&meta, dir)?;
+    if meta.mode() & 0o020 != 0 {
+        bail!("{} is writable by its group", dir.display());
+    }
+
+    let temp = temp_path(path);
+    // Left over if writing it crashed before.
+    _ = std::fs::remove_file(&temp);
+    let mut file = OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .mode(FILE_MODE)
+        .custom_flags(libc::O_NOFOLLOW)
+        .open(&temp)
+        .wrap_err("failed to create the file")?;
+    // The mode above only ever loses bits to the umask, this makes it exact.
+    file.set_permissions(Permissions::from_mode(FILE_MODE))?;
+    file.write_all(contents)
+        .and_then(|()| file.sync_all())
+        .wrap_err("failed to write the file")?;
+    std::fs::rename(&temp, path).wrap_err("failed to move the file in place")
+}
+
+/// Fails unless files can be kept in `dir` the way [`write`] would: it's there, belongs to the
+/// greeter user and nobody else can write to it.
+pub fn check_dir(dir: &Path) -> Result<()> {
+    let meta = dir.metadata().wrap_err("failed to look at the directory")?;
+    if !meta.is_dir() {
+        bail!("{} isn't a directory", dir.display());
+    }
+    check(&meta, dir)?;
+    if meta.mode() & 0o020 != 0 {
+        bail!("{} is writable by its group", dir.display());
+    }
+    Ok(())
+}
+
+/// Fails unless `path`, described by `meta`, belongs to the greeter user and isn't
+/// world-writable.
+fn check(meta: &Metadata, path: &Path) -> Result<()> {
+    // SAFETY: geteuid can't fail.
+    let uid = unsafe { libc::geteuid() };
+    if meta.uid() != uid {
+        bail!(
+            "{} belongs to uid {}, not the greeter's {uid}",
+            path.display(),
+            meta.uid()
+        );
+    }
+    if meta.mode() & 0o002 != 0 {
+        bail!("{} is world-writable", path.display());
+    }
+    Ok(())
+}
+
+fn temp_path(path: &Path) -> PathBuf {
+    let mut name = OsString::from(path.as_os_str());
+    name.push(".tmp");
+    name.into()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::os::unix::fs::PermissionsExt;
+
+    use crate::storage::{read, write};
+
+    #[test]
+    fn private_files() -> color_eyre::Result<()> {
+        let root = std::env::temp_dir().join(format!("impolite-storage-{}", std::process::id()));
+        let path = root.join("cache").join("usernames");
+        assert_eq!(read(&path)?, None);
+
+        write(&path, b"alice\n")?;
+        assert_eq!(read(&path)?.as_deref(), Some("alice\n"));
+        let mode = |path| std::fs::metadata(path).map(|meta| meta.permissions().mode() & 0o777);
+        assert_eq!(mode(&path)?, 0o600);
+        assert_eq!(mode(&root.join("cache"))?, 0o700);
+
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o666))?;
+        let refused = read(&path);
+        std::fs::remove_dir_all(&root)?;
+
+        assert!(refused.is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,216 @@
+use ratatui::style::{Color, Style};
+use ratatui::text::Span;
+use serde::{Deserialize, Serialize};
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+use crate::color;
+
+/// How many cells `text` takes up, with wide characters like CJK and emoji counting twice.
+pub fn width(text: &str) -> usize {
+    text.width()
+}
+
+/// Which half of a cell a gradient is applied to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GradientTarget {
+    Fg,
+    Bg,
+}
+
+/// Splits `text` into one span per character, each colored along a horizontal gradient
+/// from `from` to `to`, lipgloss style.
+pub fn gradient(
+    text: &str,
+    from: Color,
+    to: Color,
+    base: Style,
+    target: GradientTarget,
+) -> Vec<Span<'static>> {
+    let len = text.chars().count();
+    let steps = len.saturating_sub(1).max(1) as f32;
+    text.chars()
+        .enumerate()
+        .map(|(idx, ch)| {
+            let color = color::mix(from, to, idx as f32 / steps);
+            let style = match target {
+                GradientTarget::Fg => base.fg(color),
+                GradientTarget::Bg => base.bg(color),
+            };
+            Span::styled(ch.to_string(), style)
+        })
+        .collect()
+}
+
+/// How the cursor is drawn in the focused field.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum CursorShape {
+    #[default]
+    Block,
+    Bar,
+    Underline,
+    Hidden,
+}
+
+/// Splits `text` into spans with a cursor drawn before the `cursor`th character.
+///
+/// The terminal's own cursor stays hidden while the UI runs, so it is drawn as part of the
+/// text instead. It covers a whole grapheme, so an accent typed as a combining character
+/// stays on its letter and a wide character is covered completely.
+pub fn with_cursor(
+    text: &str,
+    cursor: usize,
+    shape: CursorShape,
+    style: Style,
+) -> Vec<Span<'static>> {
+    let mut chars = 0;
+    let grapheme = text
+        .grapheme_indices(true)
+        .find(|(_, grapheme)| {
+            chars += grapheme.chars().count();
+            chars > cursor
+        })
+        .map(|(idx, grapheme)| (idx, grapheme.len()));
+    let (split, under_len) = grapheme.unwrap_or((text.len(), 0));
+    let (before, rest) = text.split_at(split);
+    let (under, after) = rest.split_at(under_len);
+    let under = match under {
+        "" => " ".to_string(),
+        under => under.to_string(),
+    };
+    let cursor = match shape {
+        CursorShape::Block => vec![Span::styled(under, style.reversed())],
+        CursorShape::Underline => vec![Span::styled(under, style.underlined())],
+        CursorShape::Bar => vec![
+            Span::styled("▏", style),
+            Span::styled(rest.to_string(), style),
+        ],
+        CursorShape::Hidden => vec![Span::styled(rest.to_string(), style)],
+    };
+    let after = match shape {
+        CursorShape::Block | CursorShape::Underline => Some(Span::styled(after.to_string(), style)),
+        CursorShape::Bar | CursorShape::Hidden => None,
+    };
+    std::iter::once(Span::styled(before.to_string(), style))
+        .chain(cursor)
+        .chain(after)
+        .filter(|span| !span.content.is_empty())
+        .collect()
+}
+
+/// Word wraps `text` into lines at most `width` cells wide, splitting words too long for a
+/// line of their own, and keeps the first `max_lines` of them.
+///
+/// Text that had to be cut off ends in `…`, and the second half of the result says so.
+pub fn wrap(text: &str, width: usize, max_lines: usize) -> (Vec<String>, bool) {
+    let width = width.max(1);
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    for mut word in text.split_whitespace() {
+        loop {
+            let (line_len, word_len) = (self::width(&line), self::width(word));
+            let space = usize::from(line_len > 0);
+            if line_len + space + word_len <= width {
+                if space > 0 {
+                    line.push(' ');
+                }
+                line.push_str(word);
+                break;
+            }
+            if line_len > 0 {
+                lines.push(std::mem::take(&mut line));
+                continue;
+            }
+            // At least one character goes on the line, even one wider than it.
+            let mut used = 0;
+            let split = word
+                .char_indices()
+                .find(|&(idx, c)| {
+                    used += c.width().unwrap_or(0);
+                    idx > 0 && used > width
+                })
+                .map_or(word.len(), |(idx, _)| idx);
+            lines.push(word[..split].to_string());
+            word = &word[split..];
+        }
+    }
+    if !line.is_empty() {
+        lines.push(line);
+    }
+    let cut = lines.len() > max_lines;
+    if cut {
+        lines.truncate(max_lines);
+        if let Some(last) = lines.last_mut() {
+            while self::width(last) >= width {
+                last.pop();
+            }
+            last.push('…');
+        }
+    }
+    (lines, cut)
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::style::{Color, Modifier, Style};
+
+    use crate::styling::{CursorShape, GradientTarget, gradient, with_cursor, wrap};
+
+    #[test]
+    fn gradient_hits_both_endpoints() {
+        let from = Color::Rgb(0, 0, 0);
+        let to = Color::Rgb(200, 200, 200);
+        let spans = gradient("abc", from, to, Style::new(), GradientTarget::Bg);
+
+        assert_eq!(spans.len(), 3);
+        assert_eq!(spans[0].style.bg, Some(from));
+        assert_eq!(spans[1].style.bg, Some(Color::Rgb(100, 100, 100)));
+        assert_eq!(spans[2].style.bg, Some(to));
+    }
+
+    #[test]
+    fn wrap_breaks_words_and_cuts_off() {
+        assert_eq!(
+            wrap("the quick brown fox", 10, 3),
+            (
+                vec!["the quick".to_string(), "brown fox".to_string()],
+                false
+            )
+        );
+        assert_eq!(
+            wrap("pam_unix(login:auth)", 8, 2),
+            (vec!["pam_unix".to_string(), "(login:…".to_string()], true)
+        );
+        assert_eq!(wrap("", 10, 3), (Vec::new(), false));
+        assert_eq!(
+            wrap("こんにちは世界", 6, 3),
+            (
+                vec!["こんに".to_string(), "ちは世".to_string(), "界".to_string()],
+                false
+            )
+        );
+    }
+
+    #[test]
+    fn cursor_lands_on_the_right_character() {
+        let spans = with_cursor("héllo", 1, CursorShape::Block, Style::new());
+        let contents = spans
+            .iter()
+            .map(|span| span.content.as_ref())
+            .collect::<Vec<_>>();
+
+        assert_eq!(contents, ["h", "é", "llo"]);
+        assert!(spans[1].style.add_modifier.contains(Modifier::REVERSED));
+
+        let spans = with_cursor("e\u{301}t世", 1, CursorShape::Block, Style::new());
+        let contents = spans
+            .iter()
+            .map(|span| span.content.as_ref())
+            .collect::<Vec<_>>();
+        assert_eq!(contents, ["e\u{301}", "t世"]);
+
+        let spans = with_cursor("ab", 2, CursorShape::Underline, Style::new());
+        assert_eq!(spans.last().map(|span| span.content.as_ref()), Some(" "));
+    }
+}
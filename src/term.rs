@@ -0,0 +1,487 @@
+use std::ffi::CStr;
+use std::io::{IsTerminal, Read, Write};
+use std::os::fd::AsRawFd;
+use std::path::PathBuf;
+use std::task::Poll;
+use std::time::{Duration, Instant};
+
+use color_eyre::config::PanicHook;
+use ratatui::crossterm::event::{
+    DisableBracketedPaste, EnableBracketedPaste, KeyEvent, KeyEventState,
+};
+use ratatui::crossterm::{cursor, execute, terminal};
+use serde::{Deserialize, Serialize};
+use tokio::signal::unix::{Signal, SignalKind, signal};
+
+use crate::crash;
+use crate::secret;
+
+/// How many colors the terminal we're drawing on can show.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ColorDepth {
+    Ansi16,
+    Ansi256,
+    TrueColor,
+}
+
+impl ColorDepth {
+    pub fn detect() -> Self {
+        let colorterm = std::env::var("COLORTERM").ok();
+        let term = std::env::var("TERM").ok();
+        Self::from_env(colorterm.as_deref(), term.as_deref())
+    }
+
+    pub fn from_env(colorterm: Option<&str>, term: Option<&str>) -> Self {
+        if matches!(colorterm, Some("truecolor" | "24bit")) {
+            return ColorDepth::TrueColor;
+        }
+        match term {
+            Some(term) if term.ends_with("-direct") => ColorDepth::TrueColor,
+            Some(term) if term.contains("256color") => ColorDepth::Ansi256,
+            _ => ColorDepth::Ansi16,
+        }
+    }
+}
+
+/// Whether the terminal background is light or dark.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum Appearance {
+    #[default]
+    Dark,
+    Light,
+}
+
+/// Configured appearance, `auto` asks the terminal.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum AppearancePreference {
+    #[default]
+    Auto,
+    Dark,
+    Light,
+}
+
+impl AppearancePreference {
+    pub fn resolve(self) -> Appearance {
+        match self {
+            AppearancePreference::Dark => Appearance::Dark,
+            AppearancePreference::Light => Appearance::Light,
+            AppearancePreference::Auto => query_background(Duration::from_millis(100))
+                .map(Appearance::from_background)
+                .unwrap_or_default(),
+        }
+    }
+}
+
+impl From<Appearance> for AppearancePreference {
+    fn from(appearance: Appearance) -> Self {
+        match appearance {
+            Appearance::Dark => AppearancePreference::Dark,
+            Appearance::Light => AppearancePreference::Light,
+        }
+    }
+}
+
+impl Appearance {
+    pub fn from_background((r, g, b): (u8, u8, u8)) -> Self {
+        let luma = 0.299 * r as f32 + 0.587 * g as f32 + 0.114 * b as f32;
+        match luma > 127.5 {
+            true => Appearance::Light,
+            false => Appearance::Dark,
+        }
+    }
+}
+
+/// Asks the terminal for its background color with OSC 11.
+///
+/// Must run before the UI starts reading events, or the reply gets eaten as key presses.
+/// The Linux console doesn't answer, so it is skipped outright instead of waiting out the
+/// timeout.
+pub fn query_background(timeout: Duration) -> Option<(u8, u8, u8)> {
+    let stdin = std::io::stdin();
+    if !stdin.is_terminal() || std::env::var("TERM").is_ok_and(|term| term == "linux") {
+        return None;
+    }
+    terminal::enable_raw_mode().ok()?;
+    let reply = (|| {
+        let mut stdout = std::io::stdout();
+        stdout.write_all(b"\x1b]11;?\x1b\\").ok()?;
+        stdout.flush().ok()?;
+
+        let deadline = Instant::now() + timeout;
+        let mut reply = Vec::new();
+        while !(reply.ends_with(b"\x07") || reply.ends_with(b"\x1b\\")) {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            let mut pollfd = libc::pollfd {
+                fd: stdin.as_raw_fd(),
+                events: libc::POLLIN,
+                revents: 0,
+            };
+            // SAFETY: `pollfd` is a valid, initialized pollfd and we pass a count of one.
+            let ready = unsafe { libc::poll(&mut pollfd, 1, remaining.as_millis() as i32) };
+            if ready <= 0 {
+                return None;
+            }
+            let mut buf = [0u8; 64];
+            let len = stdin.lock().read(&mut buf).ok()?;
+            reply.extend_from_slice(&buf[..len]);
+        }
+        parse_osc11(&reply)
+    })();
+    _ = terminal::disable_raw_mode();
+    reply
+}
+
+/// Turns bracketed paste on or off, so a paste arrives as one event instead of key presses
+/// that could hit Enter or other bindings on the way.
+pub fn bracketed_paste(enable: bool) {
+    let mut stdout = std::io::stdout();
+    let result = match enable {
+        true => execute!(stdout, EnableBracketedPaste),
+        false => execute!(stdout, DisableBracketedPaste),
+    };
+    if let Err(err) = result {
+        tracing::warn!("failed to set bracketed paste: {err}");
+    }
+}
+
+/// Puts the terminal back the way it was before the UI: out of raw mode, the alternate screen
+/// and bracketed paste, with the cursor showing. Safe to call more than once.
+pub fn restore() {
+    let mut stdout = std::io::stdout();
+    _ = terminal::disable_raw_mode();
+    let result = execute!(
+        stdout,
+        DisableBracketedPaste,
+        terminal::LeaveAlternateScreen,
+        cursor::Show
+    );
+    if let Err(err) = result {
+        tracing::warn!("failed to restore the terminal: {err}");
+    }
+}
+
+/// Waits for the greeter to be told to go away: SIGTERM from greetd or systemd, SIGHUP when
+/// the terminal hangs up, or SIGINT. Returns the signal's name, and never returns if none of
+/// them could be listened for.
+pub async fn terminated() -> &'static str {
+    let mut listening = Vec::new();
+    for (kind, name) in [
+        (SignalKind::terminate(), "SIGTERM"),
+        (SignalKind::hangup(), "SIGHUP"),
+        (SignalKind::interrupt(), "SIGINT"),
+    ] {
+        match signal(kind) {
+            Ok(signal) => listening.push((signal, name)),
+            Err(err) => tracing::warn!("failed to listen for {name}: {err}"),
+        }
+    }
+    if listening.is_empty() {
+        return std::future::pending().await;
+    }
+    std::future::poll_fn(|cx| {
+        listening
+            .iter_mut()
+            .find_map(|(signal, name)| signal.poll_recv(cx).is_ready().then_some(*name))
+            .map_or(Poll::Pending, Poll::Ready)
+    })
+    .await
+}
+
+/// `struct vt_mode` from `<linux/vt.h>`.
+#[repr(C)]
+#[derive(Default)]
+struct VtMode {
+    mode: libc::c_char,
+    waitv: libc::c_char,
+    relsig: libc::c_short,
+    acqsig: libc::c_short,
+    frsig: libc::c_short,
+}
+
+const VT_GETMODE: libc::c_ulong = 0x5601;
+const VT_SETMODE: libc::c_ulong = 0x5602;
+const VT_RELDISP: libc::c_ulong = 0x5605;
+const VT_AUTO: libc::c_char = 0;
+const VT_PROCESS: libc::c_char = 1;
+const VT_ACKACQ: libc::c_int = 2;
+
+/// Which way the user switched virtual terminals.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VtSwitch {
+    /// Away from the greeter's.
+    Release,
+    /// Back to it.
+    Acquire,
+}
+
+/// Switches away from and back to the greeter's virtual terminal, which the kernel asks about
+/// with SIGUSR1 and SIGUSR2 instead of just doing them. That gives the greeter a chance to put
+/// the terminal modes back before another VT shows, and set them again on the way back.
+///
+/// Switching is left to the kernel again when dropped.
+pub struct VtSwitching {
+    release: Signal,
+    acquire: Signal,
+}
+
+impl VtSwitching {
+    /// Takes over switches of the VT on stdin, `None` when it isn't one.
+    pub fn take() -> Option<Self> {
+        let mut mode = VtMode::default();
+        // SAFETY: VT_GETMODE fills in a `struct vt_mode` through the pointer.
+        if unsafe { libc::ioctl(libc::STDIN_FILENO, VT_GETMODE as _, &mut mode) } != 0 {
+            return None;
+        }
+        // Listened for first, since either one kills the greeter by default.
+        let release = signal(SignalKind::user_defined1()).ok()?;
+        let acquire = signal(SignalKind::user_defined2()).ok()?;
+        let mode = VtMode {
+            mode: VT_PROCESS,
+            relsig: libc::SIGUSR1 as _,
+            acqsig: libc::SIGUSR2 as _,
+            ..VtMode::default()
+        };
+        // SAFETY: VT_SETMODE only reads the `struct vt_mode` behind the pointer.
+        if unsafe { libc::ioctl(libc::STDIN_FILENO, VT_SETMODE as _, &mode) } != 0 {
+            tracing::warn!(
+                "failed to take over VT switching: {}",
+                std::io::Error::last_os_error()
+            );
+            return None;
+        }
+        Some(Self { release, acquire })
+    }
+
+    /// Waits for the user to switch VTs.
+    pub async fn next(&mut self) -> VtSwitch {
+        tokio::select! {
+            _ = self.release.recv() => VtSwitch::Release,
+            _ = self.acquire.recv() => VtSwitch::Acquire,
+        }
+    }
+
+    /// Puts the terminal modes back with [`restore`] and lets the switch away go ahead.
+    pub fn release(&self) {
+        restore();
+        // SAFETY: VT_RELDISP takes its argument by value.
+        if unsafe { libc::ioctl(libc::STDIN_FILENO, VT_RELDISP as _, 1 as libc::c_int) } != 0 {
+            tracing::warn!(
+                "failed to release the VT: {}",
+                std::io::Error::last_os_error()
+            );
+        }
+    }
+
+    /// Acknowledges the switch back and sets the terminal up for the UI again. The console kept
+    /// what was on screen.
+    pub fn acquire(&self) {
+        // SAFETY: VT_RELDISP takes its argument by value.
+        if unsafe { libc::ioctl(libc::STDIN_FILENO, VT_RELDISP as _, VT_ACKACQ) } != 0 {
+            tracing::warn!(
+                "failed to acquire the VT: {}",
+                std::io::Error::last_os_error()
+            );
+        }
+        _ = terminal::enable_raw_mode();
+        let result = execute!(
+            std::io::stdout(),
+            terminal::EnterAlternateScreen,
+            EnableBracketedPaste,
+            cursor::Hide
+        );
+        if let Err(err) = result {
+            tracing::warn!("failed to set the terminal up again: {err}");
+        }
+    }
+}
+
+impl Drop for VtSwitching {
+    fn drop(&mut self) {
+        let mode = VtMode {
+            mode: VT_AUTO,
+            ..VtMode::default()
+        };
+        // SAFETY: as in `take`.
+        unsafe { libc::ioctl(libc::STDIN_FILENO, VT_SETMODE as _, &mode) };
+    }
+}
+
+/// Reports panics with `hook`, restoring the terminal first so the report can be read and the
+/// console isn't left in raw mode for greetd's next greeter. The report goes through
+/// [`secret::scrub`], in case the panic quoted a request to greetd.
+///
+/// The full report is written to a crash file, see [`crash::write`], and only the panic message
+/// and where the file is are printed. Without a file, the report is printed.
+pub fn install_panic_hook(hook: PanicHook) {
+    install_panic_hook_in(hook, crash::dirs(), std::io::stderr);
+}
+
+/// [`install_panic_hook`], writing crash reports to the first of `dirs` that works and telling
+/// the user on `stderr`.
+fn install_panic_hook_in<W: Write>(
+    hook: PanicHook,
+    dirs: Vec<PathBuf>,
+    stderr: impl Fn() -> W + Send + Sync + 'static,
+) {
+    std::panic::set_hook(Box::new(move |info| {
+        restore();
+        let report = hook.panic_report(info).to_string();
+        let report = secret::scrub(&report);
+        let mut stderr = stderr();
+        match crash::write(&dirs, &report) {
+            Ok(path) => {
+                let message = info.payload_as_str().unwrap_or("panicked");
+                _ = writeln!(stderr, "impolite crashed: {}", secret::scrub(message));
+                _ = writeln!(stderr, "The report is in {}", path.display());
+            }
+            Err(_) => _ = writeln!(stderr, "{report}"),
+        }
+    }));
+}
+
+/// Whether caps lock is on, if it can be told.
+///
+/// Terminals with the kitty keyboard protocol report it with every key. The Linux console
+/// doesn't, but its keyboard LEDs can be read with `KDGKBLED`.
+pub fn caps_lock(event: &KeyEvent) -> Option<bool> {
+    if event.state.contains(KeyEventState::CAPS_LOCK) {
+        return Some(true);
+    }
+    if !std::env::var("TERM").is_ok_and(|term| term == "linux") {
+        return None;
+    }
+    const KDGKBLED: libc::c_ulong = 0x4b64;
+    const K_CAPSLOCK: libc::c_char = 0x04;
+    let mut leds: libc::c_char = 0;
+    // SAFETY: KDGKBLED writes a single char of LED flags through the pointer.
+    let ok = unsafe { libc::ioctl(libc::STDIN_FILENO, KDGKBLED as _, &mut leds) } == 0;
+    ok.then_some(leds & K_CAPSLOCK != 0)
+}
+
+/// The name of the tty on stdin, like `tty1`.
+pub fn tty_name() -> Option<String> {
+    // SAFETY: ttyname returns either null or a NUL terminated string we copy right away.
+    let tty = unsafe { libc::ttyname(libc::STDIN_FILENO) };
+    if tty.is_null() {
+        return None;
+    }
+    let tty = unsafe { CStr::from_ptr(tty) }.to_string_lossy();
+    Some(tty.trim_start_matches("/dev/").to_string())
+}
+
+/// Parses an `ESC ] 11 ; rgb:RRRR/GGGG/BBBB` reply into 8 bit channels.
+fn parse_osc11(reply: &[u8]) -> Option<(u8, u8, u8)> {
+    let reply = std::str::from_utf8(reply).ok()?;
+    let start = reply.find("rgb:")? + "rgb:".len();
+    let end = reply[start..]
+        .find(['\x07', '\x1b'])
+        .map_or(reply.len(), |end| start + end);
+    let mut channels = reply[start..end].split('/').map(|channel| {
+        let value = u32::from_str_radix(channel, 16).ok()?;
+        let max = 16u32.checked_pow(channel.len() as u32)? - 1;
+        Some((value * 255 / max.max(1)) as u8)
+    });
+    Some((channels.next()??, channels.next()??, channels.next()??))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{self, Write};
+    use std::sync::{Arc, Mutex};
+
+    use crate::greetd::Request;
+    use crate::term::{Appearance, ColorDepth, install_panic_hook_in, parse_osc11};
+
+    /// Stands in for stderr, keeping what the hook printed.
+    #[derive(Clone, Default)]
+    struct Captured(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Captured {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn crash_during_login_leaves_no_password() -> color_eyre::Result<()> {
+        let dir = std::env::temp_dir().join(format!("impolite-crash-{}", std::process::id()));
+        std::fs::create_dir_all(&dir)?;
+        let stderr = Captured::default();
+        let (panic_hook, _) = color_eyre::config::HookBuilder::default().into_hooks();
+        let captured = stderr.clone();
+        install_panic_hook_in(panic_hook, vec![dir.clone()], move || captured.clone());
+
+        let msg = Request::PostAuthMessageResponse {
+            response: Some("hunter2".into()),
+        };
+        let body = serde_json::to_string(&msg)?;
+        let crash = std::panic::catch_unwind(|| panic!("failed to write {body} for {msg:?}"));
+        _ = std::panic::take_hook();
+        assert!(crash.is_err());
+
+        let stderr = String::from_utf8(stderr.0.lock().unwrap().clone())?;
+        assert!(stderr.contains("impolite crashed: failed to write"));
+        assert!(!stderr.contains("hunter2"));
+        let reports = std::fs::read_dir(&dir)?
+            .map(|entry| Ok(std::fs::read_to_string(entry?.path())?))
+            .collect::<color_eyre::Result<Vec<_>>>()?;
+        std::fs::remove_dir_all(&dir)?;
+        assert_eq!(reports.len(), 1);
+        assert!(reports[0].contains("failed to write"));
+        assert!(!reports[0].contains("hunter2"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn detect_color_depth() {
+        assert_eq!(
+            ColorDepth::from_env(Some("truecolor"), Some("linux")),
+            ColorDepth::TrueColor
+        );
+        assert_eq!(
+            ColorDepth::from_env(None, Some("xterm-256color")),
+            ColorDepth::Ansi256
+        );
+        assert_eq!(
+            ColorDepth::from_env(None, Some("foot-direct")),
+            ColorDepth::TrueColor
+        );
+        assert_eq!(
+            ColorDepth::from_env(None, Some("linux")),
+            ColorDepth::Ansi16
+        );
+        assert_eq!(ColorDepth::from_env(None, None), ColorDepth::Ansi16);
+    }
+
+    #[test]
+    fn parse_osc11_reply() {
+        assert_eq!(
+            parse_osc11(b"\x1b]11;rgb:ffff/8080/0000\x1b\\"),
+            Some((255, 128, 0))
+        );
+        assert_eq!(
+            parse_osc11(b"\x1b]11;rgb:ff/00/7f\x07"),
+            Some((255, 0, 127))
+        );
+        assert_eq!(parse_osc11(b"garbage"), None);
+    }
+
+    #[test]
+    fn appearance_from_background() {
+        assert_eq!(
+            Appearance::from_background((0x1e, 0x1e, 0x2e)),
+            Appearance::Dark
+        );
+        assert_eq!(
+            Appearance::from_background((0xee, 0xee, 0xe0)),
+            Appearance::Light
+        );
+    }
+}
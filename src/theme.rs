@@ -0,0 +1,332 @@
+use std::io::ErrorKind;
+use std::path::Path;
+
+use color_eyre::{Result, eyre::Context};
+use ratatui::style::Color;
+use serde::{Deserialize, Serialize};
+use tracing::instrument;
+
+use crate::color;
+use crate::config::ThemeConfig;
+use crate::lipgloss_colors::LIPGLOSS;
+use crate::term::Appearance;
+use crate::users;
+
+/// Declares the theme roles once, generating [`Theme`], the all-optional [`ThemeOverrides`]
+/// that config files fill in, and the glue between the two.
+macro_rules! theme_roles {
+    ($($(#[$doc:meta])* $role:ident),* $(,)?) => {
+        /// Every color the UI draws with, by what it is used for.
+        #[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+        #[serde(rename_all = "kebab-case")]
+        pub struct Theme {
+            $($(#[$doc])* pub $role: Color,)*
+        }
+
+        /// A partial [`Theme`], applied on top of a preset.
+        #[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+        #[serde(default, rename_all = "kebab-case")]
+        pub struct ThemeOverrides {
+            $(pub $role: Option<Color>,)*
+        }
+
+        impl From<Theme> for ThemeOverrides {
+            fn from(theme: Theme) -> Self {
+                Self {
+                    $($role: Some(theme.$role),)*
+                }
+            }
+        }
+
+        impl Theme {
+            pub fn apply(&mut self, overrides: &ThemeOverrides) {
+                $(
+                    if let Some(color) = overrides.$role {
+                        self.$role = color;
+                    }
+                )*
+            }
+
+            /// Every role faded `t` of the way out over `to`.
+            pub fn fade(&self, to: Color, t: f32) -> Self {
+                Self {
+                    $($role: color::blend(self.$role, to, 1.0 - t),)*
+                }
+            }
+        }
+    };
+}
+
+theme_roles! {
+    /// Highlights: the focused field marker, spinners, selections.
+    accent,
+    /// Start and end of the banner gradient.
+    banner_from,
+    banner_to,
+    /// Start and end of the hostname badge's background gradient.
+    badge_from,
+    badge_to,
+    badge_fg,
+    label,
+    label_focused,
+    text,
+    text_dim,
+    /// Key names in the help line.
+    help_key,
+    /// Descriptions in the help line.
+    help_desc,
+    error,
+    warning,
+    success,
+    border,
+    /// What everything fades towards while the UI is idle.
+    dim,
+}
+
+impl Theme {
+    /// The configured preset in the given appearance, with the config's overrides applied.
+    pub fn resolve(config: &ThemeConfig, appearance: Appearance) -> Self {
+        let mut theme = config.preset.theme(appearance);
+        theme.apply(&config.overrides);
+        theme
+    }
+}
+
+/// Writes the theme `config` resolves to as a standalone theme file, to `path` or stdout.
+///
+/// Every role is spelled out, so the file can be used as a per-user theme or copied into the
+/// `[theme]` section of the config as is.
+#[instrument(err)]
+pub fn export(config: &ThemeConfig, appearance: Appearance, path: Option<&Path>) -> Result<()> {
+    let contents = export_toml(config, appearance)?;
+    match path {
+        Some(path) => std::fs::write(path, contents)
+            .wrap_err_with(|| format!("failed to write theme to {}", path.display())),
+        None => {
+            print!("{contents}");
+            Ok(())
+        }
+    }
+}
+
+fn export_toml(config: &ThemeConfig, appearance: Appearance) -> Result<String> {
+    let theme = Theme::resolve(config, appearance);
+    let export = ThemeConfig {
+        preset: config.preset,
+        appearance: appearance.into(),
+        per_user: false,
+        overrides: theme.into(),
+    };
+    toml::to_string(&export).wrap_err("failed to serialize theme")
+}
+
+/// Where an admin can drop per-user themes as `<username>.toml`, for users whose home
+/// directory the greeter can't read.
+pub const USER_THEME_DIR: &str = "/etc/impolite/users";
+
+/// Looks up `username`'s own theme, in [`USER_THEME_DIR`] first and then in
+/// `~/.config/impolite/theme.toml`.
+///
+/// Files the greeter isn't allowed to read are skipped like missing ones.
+#[instrument(err)]
+pub fn load_user(username: &str) -> Result<Option<ThemeConfig>> {
+    if username.is_empty() || username.starts_with('.') || username.contains('/') {
+        return Ok(None);
+    }
+    let candidates = [
+        Some(Path::new(USER_THEME_DIR).join(format!("{username}.toml"))),
+        users::home_dir(username).map(|home| home.join(".config/impolite/theme.toml")),
+    ];
+    for path in candidates.into_iter().flatten() {
+        let contents = match std::fs::read_to_string(&path) {
+            Ok(contents) => contents,
+            Err(err)
+                if matches!(
+                    err.kind(),
+                    ErrorKind::NotFound | ErrorKind::PermissionDenied
+                ) =>
+            {
+                continue;
+            }
+            Err(err) => {
+                return Err(err)
+                    .wrap_err_with(|| format!("failed to read theme at {}", path.display()));
+            }
+        };
+        return parse_file(&path, &contents).map(Some);
+    }
+    Ok(None)
+}
+
+/// Loads a standalone theme file, like the ones [`export`] writes.
+#[instrument(err)]
+pub fn load_file(path: &Path) -> Result<ThemeConfig> {
+    let contents = std::fs::read_to_string(path)
+        .wrap_err_with(|| format!("failed to read theme at {}", path.display()))?;
+    parse_file(path, &contents)
+}
+
+fn parse_file(path: &Path, contents: &str) -> Result<ThemeConfig> {
+    toml::from_str(contents).wrap_err_with(|| format!("invalid theme at {}", path.display()))
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum ThemePreset {
+    #[default]
+    Lipgloss,
+    /// Lipgloss with status colors from the Okabe-Ito palette, told apart by brightness as
+    /// well as hue for red-green color blindness.
+    Deuteranopia,
+    /// Like [`ThemePreset::Deuteranopia`], with a brighter error color since reds look darker
+    /// with protanopia.
+    Protanopia,
+}
+
+impl ThemePreset {
+    pub fn theme(self, appearance: Appearance) -> Theme {
+        match (self, appearance) {
+            (ThemePreset::Lipgloss, Appearance::Dark) => Theme {
+                accent: LIPGLOSS[6][11],
+                banner_from: LIPGLOSS[0][0],
+                banner_to: LIPGLOSS[7][0],
+                badge_from: LIPGLOSS[0][0],
+                badge_to: LIPGLOSS[0][13],
+                badge_fg: Color::Black,
+                label: Color::from_u32(0x4e4e4e),
+                label_focused: LIPGLOSS[6][11],
+                text: Color::Reset,
+                text_dim: Color::from_u32(0x626262),
+                help_key: Color::from_u32(0x626262),
+                help_desc: Color::from_u32(0x4e4e4e),
+                error: LIPGLOSS[0][0],
+                warning: LIPGLOSS[0][10],
+                success: LIPGLOSS[6][13],
+                border: Color::from_u32(0x3a3a3a),
+                dim: Color::from_u32(0x121212),
+            },
+            (ThemePreset::Lipgloss, Appearance::Light) => Theme {
+                accent: LIPGLOSS[7][0],
+                banner_from: LIPGLOSS[0][0],
+                banner_to: LIPGLOSS[7][0],
+                badge_from: LIPGLOSS[0][0],
+                badge_to: LIPGLOSS[0][13],
+                badge_fg: Color::Black,
+                label: Color::from_u32(0x8a8a8a),
+                label_focused: LIPGLOSS[7][0],
+                text: Color::Reset,
+                text_dim: Color::from_u32(0x8a8a8a),
+                help_key: Color::from_u32(0x3a3a3a),
+                help_desc: Color::from_u32(0x8a8a8a),
+                error: Color::from_u32(0xc0245c),
+                warning: Color::from_u32(0xb07a00),
+                success: Color::from_u32(0x00875f),
+                border: Color::from_u32(0xbcbcbc),
+                dim: Color::from_u32(0xeeeeee),
+            },
+            (ThemePreset::Deuteranopia | ThemePreset::Protanopia, appearance) => {
+                let (accent, error, warning, success) = match (self, appearance) {
+                    (ThemePreset::Protanopia, Appearance::Dark) => {
+                        (0x56b4e9, 0xe69f00, 0xf0e442, 0x56b4e9)
+                    }
+                    (ThemePreset::Protanopia, Appearance::Light) => {
+                        (0x0072b2, 0xc77c00, 0x9e8f00, 0x004d7a)
+                    }
+                    (_, Appearance::Dark) => (0x56b4e9, 0xd55e00, 0xf0e442, 0x56b4e9),
+                    (_, Appearance::Light) => (0x0072b2, 0xd55e00, 0x9e8f00, 0x004d7a),
+                };
+                Theme {
+                    accent: Color::from_u32(accent),
+                    label_focused: Color::from_u32(accent),
+                    error: Color::from_u32(error),
+                    warning: Color::from_u32(warning),
+                    success: Color::from_u32(success),
+                    ..ThemePreset::Lipgloss.theme(appearance)
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::style::Color;
+
+    use crate::color::{self, Hsl};
+    use crate::config::ThemeConfig;
+    use crate::term::Appearance;
+    use crate::theme::{Theme, ThemeOverrides, ThemePreset, export_toml, load_user};
+
+    #[test]
+    fn overrides_replace_only_set_roles() {
+        let mut theme = ThemePreset::Lipgloss.theme(Appearance::Dark);
+        let before = theme;
+        theme.apply(&ThemeOverrides {
+            accent: Some(Color::Rgb(1, 2, 3)),
+            ..Default::default()
+        });
+
+        assert_eq!(theme.accent, Color::Rgb(1, 2, 3));
+        assert_eq!(theme.label, before.label);
+    }
+
+    #[test]
+    fn color_blind_presets_differ_in_brightness() {
+        let lightness = |color| color::to_rgb(color).map(|rgb| Hsl::from_rgb(rgb).l);
+        for preset in [ThemePreset::Deuteranopia, ThemePreset::Protanopia] {
+            for appearance in [Appearance::Dark, Appearance::Light] {
+                let theme = preset.theme(appearance);
+                let error = lightness(theme.error).unwrap();
+                let success = lightness(theme.success).unwrap();
+                assert!(
+                    (error - success).abs() > 0.12,
+                    "{preset:?} {appearance:?}: {error} vs {success}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn user_theme_rejects_paths() -> color_eyre::Result<()> {
+        assert!(load_user("../../etc/shadow")?.is_none());
+        assert!(load_user(".hidden")?.is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn export_round_trips() -> color_eyre::Result<()> {
+        let config = ThemeConfig {
+            preset: ThemePreset::Protanopia,
+            overrides: ThemeOverrides {
+                accent: Some(Color::Indexed(42)),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let exported: ThemeConfig = toml::from_str(&export_toml(&config, Appearance::Light)?)?;
+
+        assert_eq!(
+            Theme::resolve(&exported, Appearance::Light),
+            Theme::resolve(&config, Appearance::Light)
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn parse_overrides() -> color_eyre::Result<()> {
+        let overrides: ThemeOverrides = toml::from_str(
+            r##"
+            accent = "#ff00ff"
+            help-key = "light-blue"
+            "##,
+        )?;
+
+        assert_eq!(overrides.accent, Some(Color::Rgb(255, 0, 255)));
+        assert_eq!(overrides.help_key, Some(Color::LightBlue));
+        assert_eq!(overrides.error, None);
+
+        Ok(())
+    }
+}
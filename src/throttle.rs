@@ -0,0 +1,61 @@
+use std::time::{Duration, Instant};
+
+/// A token bucket, letting through a burst of `capacity` at once and then one every
+/// `interval`.
+#[derive(Debug, Clone)]
+pub struct Throttle {
+    capacity: u32,
+    interval: Duration,
+    tokens: u32,
+    /// When the next token is due, `None` while the bucket is full.
+    refill_at: Option<Instant>,
+}
+
+impl Throttle {
+    pub fn new(capacity: u32, interval: Duration) -> Self {
+        Self {
+            capacity,
+            interval,
+            tokens: capacity,
+            refill_at: None,
+        }
+    }
+
+    /// Takes a token if there's one left at `now`, or says no.
+    pub fn allow(&mut self, now: Instant) -> bool {
+        while let Some(refill_at) = self.refill_at.filter(|&at| at <= now) {
+            self.tokens += 1;
+            self.refill_at = (self.tokens < self.capacity).then(|| refill_at + self.interval);
+        }
+        if self.tokens == 0 {
+            return false;
+        }
+        self.tokens -= 1;
+        self.refill_at.get_or_insert(now + self.interval);
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use crate::throttle::Throttle;
+
+    #[test]
+    fn bursts_then_refills() {
+        let start = Instant::now();
+        let second = Duration::from_secs(1);
+        let mut throttle = Throttle::new(3, second);
+
+        let allowed = (0..5).filter(|_| throttle.allow(start)).count();
+        assert_eq!(allowed, 3);
+        assert!(!throttle.allow(start + second / 2));
+        assert!(throttle.allow(start + second));
+        assert!(!throttle.allow(start + second));
+
+        let later = start + second * 60;
+        let allowed = (0..5).filter(|_| throttle.allow(later)).count();
+        assert_eq!(allowed, 3);
+    }
+}
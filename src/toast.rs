@@ -0,0 +1,97 @@
+use std::time::Duration;
+
+use mana_tui::mana_tui_potion::Effect;
+use serde::Serialize;
+
+use crate::{Msg, Str, queue};
+
+/// How long a toast stays up unless something dismisses it earlier.
+pub const TOAST_DURATION: Duration = Duration::from_secs(4);
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ToastLevel {
+    Info,
+    Warning,
+    Error,
+}
+
+/// A short-lived message about something that didn't need to stop the user.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Toast {
+    pub id: u64,
+    pub text: Str,
+    pub level: ToastLevel,
+}
+
+/// The toasts on screen, oldest first.
+#[derive(Debug, Default)]
+pub struct Toasts {
+    next_id: u64,
+    items: Vec<Toast>,
+}
+
+impl Toasts {
+    /// At most this many toasts are shown, older ones make room for new ones.
+    pub const MAX: usize = 3;
+
+    /// Shows `text`, replacing an identical toast instead of stacking a second copy.
+    pub fn push(&mut self, text: Str, level: ToastLevel) -> u64 {
+        self.items.retain(|toast| toast.text != text);
+        let id = self.next_id;
+        self.next_id += 1;
+        self.items.push(Toast { id, text, level });
+        if self.items.len() > Self::MAX {
+            self.items.remove(0);
+        }
+        id
+    }
+
+    /// Takes toast `id` down, returning whether it was still up.
+    pub fn dismiss(&mut self, id: u64) -> bool {
+        let len = self.items.len();
+        self.items.retain(|toast| toast.id != id);
+        self.items.len() != len
+    }
+
+    /// The id the next toast pushed gets.
+    pub fn next_id(&self) -> u64 {
+        self.next_id
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Toast> {
+        self.items.iter()
+    }
+}
+
+/// Dismisses toast `id` after `duration`.
+pub fn dismiss_after(id: u64, duration: Duration) -> Effect<Msg> {
+    Effect::new(move |tx| async move {
+        tokio::time::sleep(duration).await;
+        _ = queue::send(&tx, Msg::DismissToast(id)).await;
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::toast::{ToastLevel, Toasts};
+
+    #[test]
+    fn push_replaces_duplicates_and_caps() {
+        let mut toasts = Toasts::default();
+        let first = toasts.push("caps lock on".into(), ToastLevel::Warning);
+        toasts.push("caps lock on".into(), ToastLevel::Warning);
+        assert_eq!(toasts.iter().count(), 1);
+        assert_ne!(toasts.iter().next().map(|toast| toast.id), Some(first));
+
+        for n in 0..5 {
+            toasts.push(n.to_string().into(), ToastLevel::Info);
+        }
+        let texts = toasts.iter().map(|toast| &*toast.text).collect::<Vec<_>>();
+        assert_eq!(texts, ["2", "3", "4"]);
+
+        let last = toasts.iter().last().map(|toast| toast.id).unwrap();
+        toasts.dismiss(last);
+        assert_eq!(toasts.iter().count(), 2);
+    }
+}
@@ -0,0 +1,27 @@
+use std::time::Duration;
+
+use crate::animation::{Easing, Tween};
+
+/// The greeting line typing itself out, one character every `delay`.
+#[derive(Debug, Clone, Copy)]
+pub struct Typewriter {
+    tween: Tween<f32>,
+}
+
+impl Typewriter {
+    pub fn start(len: usize, delay: Duration) -> Self {
+        Self {
+            tween: Tween::new(0.0, len as f32, delay * len as u32, Easing::Linear),
+        }
+    }
+
+    /// How long until the whole line is visible.
+    pub fn duration(&self) -> Duration {
+        self.tween.duration()
+    }
+
+    /// How many characters of a `len` characters long line are visible right now.
+    pub fn visible(&self, len: usize) -> usize {
+        (self.tween.value().floor() as usize).min(len)
+    }
+}
@@ -0,0 +1,42 @@
+use std::time::Duration;
+
+/// How long the system has been up, from `/proc/uptime`.
+pub fn read() -> Option<Duration> {
+    parse(&std::fs::read_to_string("/proc/uptime").ok()?)
+}
+
+fn parse(contents: &str) -> Option<Duration> {
+    let secs = contents.split_whitespace().next()?.parse::<f64>().ok()?;
+    Duration::try_from_secs_f64(secs).ok()
+}
+
+/// `up 3d 4h 12m`, leaving out leading zero units.
+pub fn format(uptime: Duration) -> String {
+    let minutes = uptime.as_secs() / 60;
+    let (days, hours, minutes) = (minutes / (24 * 60), minutes / 60 % 24, minutes % 60);
+    match (days, hours) {
+        (0, 0) => format!("up {minutes}m"),
+        (0, _) => format!("up {hours}h {minutes}m"),
+        _ => format!("up {days}d {hours}h {minutes}m"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::uptime::{format, parse};
+
+    #[test]
+    fn parse_and_format() {
+        assert_eq!(
+            parse("350735.47 234388.90\n"),
+            Some(Duration::from_secs_f64(350735.47))
+        );
+        assert_eq!(parse("garbage"), None);
+
+        assert_eq!(format(Duration::from_secs(59)), "up 0m");
+        assert_eq!(format(Duration::from_secs(3 * 3600 + 120)), "up 3h 2m");
+        assert_eq!(format(Duration::from_secs(350735)), "up 4d 1h 25m");
+    }
+}
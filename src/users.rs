@@ -0,0 +1,180 @@
+use std::ffi::{CStr, CString, OsStr};
+use std::ops::RangeInclusive;
+use std::os::unix::ffi::OsStrExt;
+use std::path::PathBuf;
+
+use crate::Str;
+
+const LOGIN_DEFS: &str = "/etc/login.defs";
+
+/// Longest username useradd(8) creates.
+pub const MAX_NAME_LEN: usize = 32;
+
+/// Why a username can't belong to anyone, caught before greetd is asked.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InvalidUsername {
+    Empty,
+    Whitespace,
+    /// Colons separate the fields of passwd(5).
+    Colon,
+    TooLong,
+}
+
+impl InvalidUsername {
+    pub fn hint(self) -> &'static str {
+        match self {
+            InvalidUsername::Empty => "enter a username",
+            InvalidUsername::Whitespace => "usernames can't contain spaces",
+            InvalidUsername::Colon => "usernames can't contain colons",
+            InvalidUsername::TooLong => "that's longer than any username can be",
+        }
+    }
+}
+
+pub fn check_username(username: &str) -> Option<InvalidUsername> {
+    match username {
+        "" => Some(InvalidUsername::Empty),
+        _ if username.chars().any(char::is_whitespace) => Some(InvalidUsername::Whitespace),
+        _ if username.contains(':') => Some(InvalidUsername::Colon),
+        _ if username.len() > MAX_NAME_LEN => Some(InvalidUsername::TooLong),
+        _ => None,
+    }
+}
+
+/// An account someone can log in to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct User {
+    pub name: Str,
+    /// The first GECOS field, usually the full name, or the one set in AccountsService.
+    pub real_name: Option<Str>,
+    /// The picture set in AccountsService.
+    pub icon: Option<PathBuf>,
+}
+
+impl User {
+    /// The real name if there is one, the username otherwise.
+    pub fn display_name(&self) -> &str {
+        self.real_name.as_deref().unwrap_or(&self.name)
+    }
+}
+
+/// `username`'s home directory, as NSS has it.
+pub fn home_dir(username: &str) -> Option<PathBuf> {
+    let name = CString::new(username).ok()?;
+    // SAFETY: `name` is NUL terminated, and the entry getpwnam returns is copied out before
+    // anything else on this thread can call into getpw* again.
+    unsafe {
+        let passwd = libc::getpwnam(name.as_ptr());
+        if passwd.is_null() {
+            return None;
+        }
+        let dir = CStr::from_ptr((*passwd).pw_dir);
+        Some(PathBuf::from(OsStr::from_bytes(dir.to_bytes())))
+    }
+}
+
+/// `username`'s UID, as NSS has it.
+pub fn uid(username: &str) -> Option<u32> {
+    let name = CString::new(username).ok()?;
+    // SAFETY: `name` is NUL terminated, and the UID is copied out of the entry right away.
+    unsafe {
+        let passwd = libc::getpwnam(name.as_ptr());
+        (!passwd.is_null()).then(|| (*passwd).pw_uid)
+    }
+}
+
+/// The UIDs of regular users according to login.defs(5).
+pub fn uid_range() -> RangeInclusive<u32> {
+    parse_login_defs(&std::fs::read_to_string(LOGIN_DEFS).unwrap_or_default())
+}
+
+/// `UID_MIN..=UID_MAX`, with shadow-utils' defaults for the ones that aren't set.
+fn parse_login_defs(contents: &str) -> RangeInclusive<u32> {
+    let value = |key: &str| {
+        contents
+            .lines()
+            .rev()
+            .map(str::trim)
+            .filter(|line| !line.starts_with('#'))
+            .find_map(|line| {
+                let mut parts = line.split_whitespace();
+                match parts.next() == Some(key) {
+                    true => parts.next()?.parse().ok(),
+                    false => None,
+                }
+            })
+    };
+    value("UID_MIN").unwrap_or(1000)..=value("UID_MAX").unwrap_or(60000)
+}
+
+/// Every user NSS knows with a UID in `uids` and a login shell, sorted by name.
+///
+/// Must not run while anything else is looking up users, since the passwd enumeration is
+/// global state.
+pub fn list(uids: RangeInclusive<u32>) -> Vec<User> {
+    let mut users = Vec::new();
+    // SAFETY: each entry getpwent returns is copied out before the next call, and nothing
+    // else walks the passwd database while this runs.
+    unsafe {
+        libc::setpwent();
+        loop {
+            let passwd = libc::getpwent();
+            if passwd.is_null() {
+                break;
+            }
+            let passwd = &*passwd;
+            if !uids.contains(&passwd.pw_uid) {
+                continue;
+            }
+            let field = |ptr: *const libc::c_char| match ptr.is_null() {
+                true => &[][..],
+                false => CStr::from_ptr(ptr).to_bytes(),
+            };
+            let shell = field(passwd.pw_shell);
+            if shell.ends_with(b"/nologin") || shell.ends_with(b"/false") {
+                continue;
+            }
+            let real_name = String::from_utf8_lossy(field(passwd.pw_gecos));
+            let real_name = real_name.split(',').next().unwrap_or_default().trim();
+            users.push(User {
+                name: String::from_utf8_lossy(field(passwd.pw_name)).into(),
+                real_name: (!real_name.is_empty()).then(|| real_name.into()),
+                icon: None,
+            });
+        }
+        libc::endpwent();
+    }
+    users.sort_by(|a, b| a.name.cmp(&b.name));
+    // The same user can come from more than one NSS source.
+    users.dedup_by(|a, b| a.name == b.name);
+    users
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::users::{InvalidUsername, check_username, parse_login_defs};
+
+    #[test]
+    fn login_defs_uid_range() {
+        let contents = "
+            # UID_MIN 5
+            UID_MIN\t\t 500
+            SYS_UID_MIN 100
+        ";
+        assert_eq!(parse_login_defs(contents), 500..=60000);
+        assert_eq!(parse_login_defs(""), 1000..=60000);
+    }
+
+    #[test]
+    fn username_checks() {
+        assert_eq!(check_username("alice"), None);
+        assert_eq!(check_username("first.last-2"), None);
+        assert_eq!(check_username(""), Some(InvalidUsername::Empty));
+        assert_eq!(check_username("alice "), Some(InvalidUsername::Whitespace));
+        assert_eq!(check_username("a:b"), Some(InvalidUsername::Colon));
+        assert_eq!(
+            check_username(&"a".repeat(33)),
+            Some(InvalidUsername::TooLong)
+        );
+    }
+}
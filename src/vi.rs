@@ -0,0 +1,166 @@
+use ratatui::crossterm::event::{Event, KeyCode, KeyEventKind, KeyModifiers};
+use tui_input::{Input, InputRequest};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Mode {
+    #[default]
+    Insert,
+    Normal,
+}
+
+impl Mode {
+    pub fn label(self) -> &'static str {
+        match self {
+            Mode::Insert => "INSERT",
+            Mode::Normal => "NORMAL",
+        }
+    }
+}
+
+/// What a key did to a field in vi mode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    /// Typed into the field as usual, in insert mode.
+    Insert,
+    /// Changed the mode or the text, or was swallowed by normal mode.
+    Edited,
+    /// `j` or `k`, moving to the next or previous field.
+    MoveField(isize),
+    /// Left to the key bindings, like Enter or Ctrl+C.
+    Unhandled,
+}
+
+/// Vi style modal editing of a text field, starting out in insert mode so typing a username
+/// works right away.
+#[derive(Debug, Clone, Default)]
+pub struct Vi {
+    mode: Mode,
+    /// An operator like `d` waiting for its motion.
+    pending: Option<char>,
+}
+
+impl Vi {
+    pub fn mode(&self) -> Mode {
+        self.mode
+    }
+
+    pub fn handle(&mut self, input: &mut Input, event: &Event) -> Outcome {
+        let key = match event {
+            Event::Key(key) => key,
+            // Pastes go in as they are, in either mode.
+            Event::Paste(_) => return Outcome::Insert,
+            _ => return Outcome::Unhandled,
+        };
+        if key.kind == KeyEventKind::Release {
+            return Outcome::Unhandled;
+        }
+        if self.mode == Mode::Insert {
+            if key.code != KeyCode::Esc {
+                return Outcome::Insert;
+            }
+            self.mode = Mode::Normal;
+            input.handle(InputRequest::GoToPrevChar);
+            return Outcome::Edited;
+        }
+        if key
+            .modifiers
+            .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT)
+        {
+            self.pending = None;
+            return Outcome::Unhandled;
+        }
+        let requests: &[InputRequest] = match (self.pending.take(), key.code) {
+            (Some('d'), KeyCode::Char('w')) => &[InputRequest::DeleteNextWord],
+            (Some('c'), KeyCode::Char('w')) => {
+                self.mode = Mode::Insert;
+                &[InputRequest::DeleteNextWord]
+            }
+            (Some('d'), KeyCode::Char('d')) => &[InputRequest::DeleteLine],
+            (Some('c'), KeyCode::Char('c')) => {
+                self.mode = Mode::Insert;
+                &[InputRequest::DeleteLine]
+            }
+            // Anything else cancels the operator, like in vim.
+            (Some(_), _) => &[],
+            (None, KeyCode::Char(operator @ ('d' | 'c'))) => {
+                self.pending = Some(operator);
+                &[]
+            }
+            (None, KeyCode::Char('h') | KeyCode::Left) => &[InputRequest::GoToPrevChar],
+            (None, KeyCode::Char('l') | KeyCode::Right) => &[InputRequest::GoToNextChar],
+            (None, KeyCode::Char('w')) => &[InputRequest::GoToNextWord],
+            (None, KeyCode::Char('b')) => &[InputRequest::GoToPrevWord],
+            (None, KeyCode::Char('0' | '^') | KeyCode::Home) => &[InputRequest::GoToStart],
+            (None, KeyCode::Char('$') | KeyCode::End) => &[InputRequest::GoToEnd],
+            (None, KeyCode::Char('x')) => &[InputRequest::DeleteNextChar],
+            (None, KeyCode::Char('D')) => &[InputRequest::DeleteTillEnd],
+            (None, KeyCode::Char('C')) => {
+                self.mode = Mode::Insert;
+                &[InputRequest::DeleteTillEnd]
+            }
+            (None, KeyCode::Char('i')) => {
+                self.mode = Mode::Insert;
+                &[]
+            }
+            (None, KeyCode::Char('a')) => {
+                self.mode = Mode::Insert;
+                &[InputRequest::GoToNextChar]
+            }
+            (None, KeyCode::Char('I')) => {
+                self.mode = Mode::Insert;
+                &[InputRequest::GoToStart]
+            }
+            (None, KeyCode::Char('A')) => {
+                self.mode = Mode::Insert;
+                &[InputRequest::GoToEnd]
+            }
+            (None, KeyCode::Char('j')) => return Outcome::MoveField(1),
+            (None, KeyCode::Char('k')) => return Outcome::MoveField(-1),
+            (None, KeyCode::Char(_) | KeyCode::Backspace | KeyCode::Delete) => &[],
+            (None, _) => return Outcome::Unhandled,
+        };
+        for request in requests {
+            input.handle(*request);
+        }
+        Outcome::Edited
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+    use tui_input::Input;
+
+    use crate::vi::{Mode, Outcome, Vi};
+
+    fn keys(vi: &mut Vi, input: &mut Input, keys: &str) -> Vec<Outcome> {
+        keys.chars()
+            .map(|c| {
+                let code = match c {
+                    '\x1b' => KeyCode::Esc,
+                    c => KeyCode::Char(c),
+                };
+                vi.handle(input, &Event::Key(KeyEvent::new(code, KeyModifiers::NONE)))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn modes_and_operators() {
+        let mut vi = Vi::default();
+        let mut input = Input::new("alice smith".into());
+
+        assert_eq!(keys(&mut vi, &mut input, "x"), [Outcome::Insert]);
+        keys(&mut vi, &mut input, "\x1b0dw");
+        assert_eq!(vi.mode(), Mode::Normal);
+        assert_eq!(input.value(), "smith");
+
+        keys(&mut vi, &mut input, "xqx");
+        assert_eq!(input.value(), "ith");
+        assert_eq!(keys(&mut vi, &mut input, "j"), [Outcome::MoveField(1)]);
+
+        keys(&mut vi, &mut input, "cc");
+        assert_eq!(input.value(), "");
+        assert_eq!(vi.mode(), Mode::Insert);
+    }
+}
@@ -0,0 +1,32 @@
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use color_eyre::{Result, eyre::Context, eyre::eyre};
+use ratatui_image::picker::{Picker, ProtocolType};
+use ratatui_image::protocol::StatefulProtocol;
+use tracing::instrument;
+
+pub type Wallpaper = Arc<Mutex<StatefulProtocol>>;
+
+/// Loads `path` as a wallpaper if the terminal can draw real graphics (kitty, sixel, iTerm2).
+///
+/// Returns `Ok(None)` on terminals that would only get a half-block approximation, like the
+/// raw Linux console.
+#[instrument(err)]
+pub fn load(path: &Path) -> Result<Option<Wallpaper>> {
+    if std::env::var("TERM").is_ok_and(|term| term == "linux") {
+        return Ok(None);
+    }
+    let picker = Picker::from_query_stdio()
+        .map_err(|err| eyre!("terminal graphics query failed: {err:?}"))?;
+    if picker.protocol_type() == ProtocolType::Halfblocks {
+        tracing::info!("terminal has no graphics protocol, skipping wallpaper");
+        return Ok(None);
+    }
+    let image = image::ImageReader::open(path)
+        .wrap_err_with(|| format!("failed to open wallpaper at {}", path.display()))?
+        .decode()
+        .wrap_err("failed to decode wallpaper")?;
+    let protocol = picker.new_resize_protocol(image);
+    Ok(Some(Arc::new(Mutex::new(protocol))))
+}
@@ -0,0 +1,108 @@
+use std::process::Stdio;
+use std::time::Duration;
+
+use color_eyre::{Result, eyre::Context, eyre::eyre};
+use flume::Sender;
+use serde::{Deserialize, Serialize};
+use tokio::process::Command;
+use tokio::task::JoinSet;
+
+use crate::config::CommandWidgetConfig;
+use crate::{Msg, Str, queue};
+
+/// Which edge of the screen a widget is pinned to.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum WidgetPosition {
+    Header,
+    #[default]
+    Footer,
+}
+
+/// Runs every command widget on its own interval until the UI goes away, sending each
+/// output that changed as [`Msg::WidgetOutput`].
+pub async fn run_all(tx: Sender<Msg>, widgets: &'static [CommandWidgetConfig]) {
+    let mut tasks = JoinSet::new();
+    for (idx, widget) in widgets.iter().enumerate() {
+        tasks.spawn(run(tx.clone(), idx, widget));
+    }
+    while tasks.join_next().await.is_some() {}
+}
+
+async fn run(tx: Sender<Msg>, idx: usize, widget: &'static CommandWidgetConfig) {
+    let mut interval = tokio::time::interval(Duration::from_secs(widget.interval_secs.max(1)));
+    let mut last = None;
+    loop {
+        interval.tick().await;
+        let output = match output(widget).await {
+            Ok(output) => output,
+            Err(err) => {
+                tracing::warn!("widget {} failed: {err:?}", widget.name);
+                "?".into()
+            }
+        };
+        // The same output again would only redraw the screen as it is.
+        if last.as_ref() == Some(&output) {
+            continue;
+        }
+        last = Some(output.clone());
+        if queue::send(&tx, Msg::WidgetOutput(idx, output))
+            .await
+            .is_err()
+        {
+            break;
+        }
+    }
+}
+
+/// Runs the widget's command with `sh -c` and returns the first line it prints, cleaned up
+/// for display.
+async fn output(widget: &CommandWidgetConfig) -> Result<Str> {
+    let child = Command::new("sh")
+        .arg("-c")
+        .arg(&*widget.command)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .kill_on_drop(true)
+        .spawn()
+        .wrap_err_with(|| format!("failed to spawn `{}`", widget.command))?;
+    let output = tokio::time::timeout(
+        Duration::from_secs(widget.timeout_secs),
+        child.wait_with_output(),
+    )
+    .await
+    .map_err(|_| eyre!("`{}` timed out", widget.command))??;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    Ok(clean(&stdout, widget.max_len).into())
+}
+
+/// The first line of `output` without control characters, cut down to `max_len` characters.
+fn clean(output: &str, max_len: usize) -> String {
+    let line = output.lines().next().unwrap_or_default().trim();
+    let line = line.chars().filter(|c| !c.is_control()).collect::<String>();
+    match line.chars().count() > max_len {
+        true => {
+            let mut cut = line
+                .chars()
+                .take(max_len.saturating_sub(1))
+                .collect::<String>();
+            cut.push('…');
+            cut
+        }
+        false => line,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::widgets::clean;
+
+    #[test]
+    fn clean_output() {
+        assert_eq!(clean("  22°C \nsecond line", 20), "22°C");
+        assert_eq!(clean("\x1b[1mbold\x07", 20), "[1mbold");
+        assert_eq!(clean("a very long line", 6), "a ver…");
+        assert_eq!(clean("", 6), "");
+    }
+}